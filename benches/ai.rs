@@ -0,0 +1,84 @@
+//! How long each AI strategy takes to pick a cell, on a few representative positions.
+//!
+//! There's no separate heuristic strategy, the closest are `mixed`, which is minimax with the
+//! occasional random cell, and `humanlike`, which is minimax overlooking some threats. The larger
+//! boards show why minimax stops after a few moves there, see `LOOKAHEAD` in `src/ai.rs`.
+
+// There's no library to link against, so the game modules are compiled right into the benchmark.
+// Not all of them are used here.
+#[allow(dead_code)]
+// Checking benchmarks compiles test modules, but without the tests in them, so the imports of the
+// ones in src/ai.rs are left unused.
+#[allow(unused_imports)]
+#[path = "../src/ai.rs"]
+mod ai;
+#[allow(dead_code)]
+#[path = "../src/game.rs"]
+mod game;
+
+use {
+    ai::AiStrategy,
+    criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion},
+    game::{Cell, Faction, Lines},
+    rand::{rngs::StdRng, SeedableRng},
+};
+
+// Each with the faction on the move and the lines played for. Ring goes first, so it's on the
+// move whenever both have marked equally many cells.
+#[rustfmt::skip]
+fn positions() -> [(&'static str, Vec<Cell>, Faction, Lines); 5] {
+    use Cell::{Cross as X, Empty as E, Ring as O};
+
+    [
+        ("empty", vec![E; 9], Faction::Ring, Lines::default()),
+        ("opening", vec![
+            E, E, E,
+            E, O, E,
+            E, E, E,
+        ], Faction::Cross, Lines::default()),
+        ("midgame", vec![
+            O, E, X,
+            E, O, E,
+            E, E, X,
+        ], Faction::Ring, Lines::default()),
+        // too large to search completely, so minimax only looks a few moves ahead here
+        ("midgame 4x4", vec![
+            O, E, E, X,
+            E, O, X, E,
+            E, E, O, E,
+            X, E, E, E,
+        ], Faction::Ring, Lines::full(4)),
+        ("midgame 5x5, 4 in a row", vec![
+            E, E, E, E, E,
+            E, O, X, E, E,
+            E, X, O, O, E,
+            E, E, X, E, E,
+            E, E, E, E, E,
+        ], Faction::Ring, Lines::in_a_row(5, 4)),
+    ]
+}
+
+fn ai_turn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ai_turn");
+
+    for strategy in [
+        AiStrategy::Random,
+        AiStrategy::Mixed,
+        AiStrategy::Minimax,
+        AiStrategy::Humanlike,
+    ] {
+        for (name, board, faction, lines) in positions() {
+            let mut rng = StdRng::seed_from_u64(0);
+            group.bench_with_input(
+                BenchmarkId::new(strategy.to_string(), name),
+                &board,
+                |b, board| b.iter(|| strategy.pick(black_box(board), faction, &lines, &mut rng)),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, ai_turn);
+criterion_main!(benches);
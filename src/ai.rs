@@ -0,0 +1,495 @@
+use {
+    super::game::{self, Cell, Faction, Game, Lines, BOARD_SIZE},
+    rand::Rng,
+    std::{fmt, str::FromStr},
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+#[error(
+    "Unknown AI strategy {0:?}, expected one of `random`, `mixed`, `minimax`, `humanlike`, `easy`, \
+    `medium` or `hard`"
+)]
+pub struct UnknownStrategyError(String);
+
+/// How likely the mixed AI marks a random cell instead of the best one.
+const RANDOM_MOVE_CHANCE: f64 = 0.3;
+
+/// How likely the humanlike AI overlooks something when there's exactly one threat on the board.
+/// Every further threat gives it another chance of the same size to overlook one.
+const OVERLOOK_CHANCE: f64 = 0.15;
+
+/// How many moves ahead minimax looks on boards larger than the classic one. Searching them
+/// completely would take ages.
+const LOOKAHEAD: i32 = 3;
+
+/// How an AI player decides on its next cell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AiStrategy {
+    /// Picks any empty cell, no thoughts involved.
+    Random,
+    /// Plays like minimax most of the time, but like random every now and then. Beatable, but
+    /// only by taking the chances it gives.
+    Mixed,
+    /// Simulates all possible games and picks the best cell. Can't be beaten, at least on the
+    /// classic board. Larger ones are only searched a few moves ahead.
+    Minimax,
+    /// Plays like minimax, but sometimes overlooks a cell completing a line, be it its own or the
+    /// opponent's. The more of them there are, the likelier it misses one.
+    Humanlike,
+}
+
+/// The strategies as plain difficulty levels, for anyone who doesn't care how the AI thinks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Plays [`AiStrategy::Random`].
+    Easy,
+    /// Plays [`AiStrategy::Mixed`].
+    Medium,
+    /// Plays [`AiStrategy::Minimax`].
+    Hard,
+}
+
+impl Difficulty {
+    /// The next harder difficulty, wrapping around to the easiest after the hardest.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Easy => Self::Medium,
+            Self::Medium => Self::Hard,
+            Self::Hard => Self::Easy,
+        }
+    }
+}
+
+/// How hard the given strategy is to beat. [`AiStrategy::Humanlike`] makes mistakes, just
+/// different ones than [`AiStrategy::Mixed`], so it's medium as well.
+impl From<AiStrategy> for Difficulty {
+    fn from(strategy: AiStrategy) -> Self {
+        match strategy {
+            AiStrategy::Random => Self::Easy,
+            AiStrategy::Mixed | AiStrategy::Humanlike => Self::Medium,
+            AiStrategy::Minimax => Self::Hard,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+        })
+    }
+}
+
+impl From<Difficulty> for AiStrategy {
+    fn from(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Easy => Self::Random,
+            Difficulty::Medium => Self::Mixed,
+            Difficulty::Hard => Self::Minimax,
+        }
+    }
+}
+
+impl AiStrategy {
+    /// Returns the index of an empty cell the given faction should mark next, playing for the
+    /// given win lines.
+    ///
+    /// # Panics
+    ///
+    /// If there is no empty cell left on the board.
+    pub fn pick(
+        self,
+        board: &[Cell],
+        faction: Faction,
+        lines: &Lines,
+        rng: &mut impl Rng,
+    ) -> usize {
+        assert!(
+            board.iter().copied().any(Cell::is_empty),
+            "no empty cell left to pick from"
+        );
+
+        match self {
+            Self::Random => loop {
+                let attempt = rng.gen_range(0..board.len());
+                // check if the field is empty at all
+                if board[attempt].is_empty() {
+                    break attempt;
+                }
+            },
+            Self::Mixed if rng.gen_bool(RANDOM_MOVE_CHANCE) => {
+                Self::Random.pick(board, faction, lines, rng)
+            }
+            Self::Mixed | Self::Minimax => best_move(board, faction, lines, None),
+            Self::Humanlike => {
+                let threats: Vec<usize> = threats(board, faction, lines)
+                    .into_iter()
+                    .chain(threats(board, faction.opposite(), lines))
+                    .collect();
+                let overlooked = rng
+                    .gen_bool(overlook_chance(threats.len()))
+                    .then(|| threats[rng.gen_range(0..threats.len())]);
+
+                best_move(board, faction, lines, overlooked)
+            }
+        }
+    }
+}
+
+impl FromStr for AiStrategy {
+    type Err = UnknownStrategyError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "random" => Ok(Self::Random),
+            "mixed" => Ok(Self::Mixed),
+            "minimax" => Ok(Self::Minimax),
+            "humanlike" => Ok(Self::Humanlike),
+            "easy" => Ok(Difficulty::Easy.into()),
+            "medium" => Ok(Difficulty::Medium.into()),
+            "hard" => Ok(Difficulty::Hard.into()),
+            _ => Err(UnknownStrategyError(source.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for AiStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Random => "random",
+            Self::Mixed => "mixed",
+            Self::Minimax => "minimax",
+            Self::Humanlike => "humanlike",
+        })
+    }
+}
+
+/// Scores every cell for the given faction, which is about to mark one. `None` for used cells.
+///
+/// Positive means the faction wins if it marks that cell and plays perfectly afterwards, negative
+/// means it loses against a perfect opponent, zero is a draw.
+pub fn evaluate_moves(board: &[Cell], faction: Faction, lines: &Lines) -> Vec<Option<i32>> {
+    let mut board = board.to_vec();
+    let mut scores = vec![None; board.len()];
+
+    for index in 0..board.len() {
+        if !board[index].is_empty() {
+            continue;
+        }
+
+        board[index] = faction.into();
+        scores[index] = Some(-minimax(&mut board, faction.opposite(), lines, 1));
+        board[index] = Cell::Empty;
+    }
+
+    scores
+}
+
+/// Scores the board for the faction about to mark a cell, assuming perfect play from both sides.
+/// Positive means it wins, negative means it loses, zero is a draw.
+pub fn forced_result(board: &[Cell], to_move: Faction, lines: &Lines) -> i32 {
+    minimax(&mut board.to_vec(), to_move, lines, 0)
+}
+
+/// Replays the moves of the given game and returns the first cell the given faction marked which
+/// turned a drawn or won position into a lost one. `None` if there was no such move.
+pub fn find_blunder(game: &Game, faction: Faction) -> Option<usize> {
+    let lines = game.lines();
+    let mut board = game.start().to_vec();
+    let mut to_move = game.opener();
+
+    for &index in game.moves() {
+        if to_move == faction {
+            let before = forced_result(&board, faction, lines);
+            board[index] = faction.into();
+            let after = -forced_result(&board, faction.opposite(), lines);
+
+            if before >= 0 && after < 0 {
+                return Some(index);
+            }
+        } else {
+            board[index] = to_move.into();
+        }
+        to_move = to_move.opposite();
+    }
+
+    None
+}
+
+/// How a single move compares to the best one available at that point.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MoveQuality {
+    /// No other cell would have been better.
+    Optimal,
+    /// Still ends the same with perfect play, but slower than possible if winning, or quicker
+    /// than necessary if losing.
+    Suboptimal,
+    /// Turned a won position into a drawn or lost one, or a drawn one into a lost one.
+    Blunder,
+}
+
+impl fmt::Display for MoveQuality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Optimal => "optimal",
+            Self::Suboptimal => "suboptimal",
+            Self::Blunder => "blunder",
+        })
+    }
+}
+
+/// Replays the moves of the given game and rates each one against the best move which was
+/// available at that point.
+pub fn analyze(game: &Game) -> Vec<MoveQuality> {
+    let lines = game.lines();
+    let mut board = game.start().to_vec();
+    let mut to_move = game.opener();
+
+    game.moves()
+        .iter()
+        .map(|&index| {
+            let scores = evaluate_moves(&board, to_move, lines);
+            let best = scores.iter().flatten().max().copied().unwrap_or(0);
+            let played = scores[index].unwrap_or(best);
+
+            board[index] = to_move.into();
+            to_move = to_move.opposite();
+
+            if played.signum() < best.signum() {
+                MoveQuality::Blunder
+            } else if played < best {
+                MoveQuality::Suboptimal
+            } else {
+                MoveQuality::Optimal
+            }
+        })
+        .collect()
+}
+
+/// Returns all empty cells which would complete a line for the given faction if it marked them.
+pub fn threats(board: &[Cell], faction: Faction, lines: &Lines) -> Vec<usize> {
+    let mut marked = board.to_vec();
+
+    game::legal_moves(board)
+        .filter(|&index| {
+            marked[index] = faction.into();
+            let completes = game::completed_by(&marked, lines) == Some(faction);
+            marked[index] = Cell::Empty;
+            completes
+        })
+        .collect()
+}
+
+/// How likely the humanlike AI overlooks one of the given number of threats.
+fn overlook_chance(threats: usize) -> f64 {
+    1.0 - (1.0 - OVERLOOK_CHANCE).powi(threats as i32)
+}
+
+/// Returns the empty cell with the best minimax score for the given faction. Ties are broken by
+/// taking the lowest index, so the result is deterministic.
+///
+/// An `overlooked` cell is only picked if it's the last empty one.
+fn best_move(board: &[Cell], faction: Faction, lines: &Lines, overlooked: Option<usize>) -> usize {
+    let scores = evaluate_moves(board, faction, lines);
+
+    // max_by_key returns the last maximum, so going in reverse makes the lowest index win ties
+    (0..board.len())
+        .rev()
+        .filter(|&index| Some(index) != overlooked)
+        .filter_map(|index| scores[index].map(|score| (index, score)))
+        .max_by_key(|&(_, score)| score)
+        .map(|(index, _)| index)
+        .or(overlooked)
+        .expect("at least one empty cell")
+}
+
+/// Scores the board from the perspective of `to_move`, which is the faction about to mark a cell.
+///
+/// Positive means `to_move` wins, negative means it loses, zero is a draw. Quicker wins and slower
+/// losses are scored higher, so the AI doesn't dawdle. Boards larger than the classic one are
+/// only searched up to the lookahead, and count as a draw beyond it.
+fn minimax(board: &mut [Cell], to_move: Faction, lines: &Lines, depth: i32) -> i32 {
+    if let Some(winner) = game::winner(board, lines) {
+        let score = board.len() as i32 + 1 - depth;
+        return if winner == to_move { score } else { -score };
+    }
+    if board.len() > BOARD_SIZE * BOARD_SIZE && depth > LOOKAHEAD {
+        return 0;
+    }
+
+    let mut best = None;
+    for index in 0..board.len() {
+        if !board[index].is_empty() {
+            continue;
+        }
+
+        board[index] = to_move.into();
+        let score = -minimax(board, to_move.opposite(), lines, depth + 1);
+        board[index] = Cell::Empty;
+
+        best = Some(best.map_or(score, |best: i32| best.max(score)));
+    }
+
+    // no empty cell left and no winner, so it's a draw
+    best.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blunder_is_the_move_turning_a_draw_into_a_loss() {
+        // ring opens in a corner, so cross has to take the center, but takes an edge instead and
+        // walks into a fork
+        let lost = Game::replay_from_moves(&[0, 1, 4, 8, 6, 3, 2], Faction::Ring).unwrap();
+        assert_eq!(lost.winner(), Some(Faction::Ring));
+        assert_eq!(find_blunder(&lost, Faction::Cross), Some(1));
+        assert_eq!(find_blunder(&lost, Faction::Ring), None);
+
+        let drawn = Game::replay_from_moves(&[4, 0, 8, 2, 1, 7, 6, 3, 5], Faction::Ring).unwrap();
+        assert_eq!(find_blunder(&drawn, Faction::Cross), None);
+        assert_eq!(find_blunder(&drawn, Faction::Ring), None);
+    }
+
+    #[test]
+    fn more_threats_are_overlooked_more_likely() {
+        assert_eq!(overlook_chance(0), 0.0);
+        assert!((overlook_chance(1) - OVERLOOK_CHANCE).abs() < 1e-9);
+        for threats in 1..8 {
+            assert!(overlook_chance(threats + 1) > overlook_chance(threats));
+            assert!(overlook_chance(threats + 1) < 1.0);
+        }
+
+        // cross can complete the bottom row and the left column, each missing one corner
+        use Cell::{Cross as X, Empty as E, Ring as O};
+        let board = [X, X, E, X, O, O, E, O, E];
+        assert_eq!(
+            threats(&board, Faction::Cross, &Lines::default()),
+            vec![2, 6]
+        );
+    }
+
+    #[test]
+    fn analysis_rates_each_move() {
+        use MoveQuality::{Blunder as B, Optimal as O, Suboptimal as S};
+        // cross gives away the draw with its first move, and then doesn't even block 6, losing
+        // sooner than needed. Ring doesn't take the win on 6 right away either, but still wins a
+        // move later.
+        let game = Game::replay_from_moves(&[0, 1, 3, 2, 4, 5, 6], Faction::Ring).unwrap();
+        assert_eq!(game.winner(), Some(Faction::Ring));
+        assert_eq!(analyze(&game), vec![O, B, O, S, S, O, O]);
+    }
+
+    #[test]
+    fn easy_plays_like_the_old_random_ai() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        for seed in 0..16 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut old_rng = StdRng::seed_from_u64(seed);
+            let mut board = [Cell::Empty; 9];
+            let mut faction = Faction::Ring;
+            for _ in 0..board.len() {
+                let easy = AiStrategy::from(Difficulty::Easy);
+                let picked = easy.pick(&board, faction, &Lines::default(), &mut rng);
+                // what ai_turn did before there were any strategies
+                let old = loop {
+                    let attempt = old_rng.gen_range(0..9);
+                    if board[attempt].is_empty() {
+                        break attempt;
+                    }
+                };
+                assert_eq!(picked, old, "seed {}, board {:?}", seed, board);
+
+                board[picked] = faction.into();
+                faction = faction.opposite();
+            }
+        }
+    }
+
+    #[test]
+    fn difficulties_map_to_strategies() {
+        for (name, strategy) in [
+            ("easy", AiStrategy::Random),
+            ("medium", AiStrategy::Mixed),
+            ("hard", AiStrategy::Minimax),
+        ] {
+            assert_eq!(name.parse::<AiStrategy>().unwrap(), strategy);
+        }
+        assert!("impossible".parse::<AiStrategy>().is_err());
+
+        assert_eq!(Difficulty::from(AiStrategy::Humanlike), Difficulty::Medium);
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            assert_eq!(Difficulty::from(AiStrategy::from(difficulty)), difficulty);
+        }
+    }
+
+    #[test]
+    fn minimax_takes_wins_and_blocks_threats() {
+        // every line on the classic board, with each of its cells left open once
+        let lines = Lines::default();
+        let classic = [
+            [0, 1, 2],
+            [3, 4, 5],
+            [6, 7, 8],
+            [0, 3, 6],
+            [1, 4, 7],
+            [2, 5, 8],
+            [0, 4, 8],
+            [2, 4, 6],
+        ];
+        let mut rng = rand::thread_rng();
+        for line in classic {
+            let elsewhere: Vec<usize> = (0..9).filter(|cell| !line.contains(cell)).collect();
+            for open in line {
+                let mut board = [Cell::Empty; 9];
+                for &cell in line.iter().filter(|&&cell| cell != open) {
+                    board[cell] = Cell::Cross;
+                }
+
+                // ring has two marks of its own, but can't complete anything with one more
+                let mut threatened = board;
+                threatened[elsewhere[0]] = Cell::Ring;
+                assert_eq!(
+                    AiStrategy::Minimax.pick(&threatened, Faction::Ring, &lines, &mut rng),
+                    open,
+                    "ring doesn't block {:?}",
+                    threatened
+                );
+
+                threatened[elsewhere[1]] = Cell::Ring;
+                assert_eq!(game::winner(&threatened, &lines), None);
+                assert_eq!(
+                    AiStrategy::Minimax.pick(&threatened, Faction::Cross, &lines, &mut rng),
+                    open,
+                    "cross doesn't win on {:?}",
+                    threatened
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn minimax_avoids_completing_lines_in_misere() {
+        use Cell::{Cross as X, Empty as E, Ring as O};
+        // cross would complete a line with 2
+        let board = [X, X, E, O, O, E, E, E, E];
+        let mut rng = rand::thread_rng();
+        let normal = Lines::default();
+        assert_eq!(
+            AiStrategy::Minimax.pick(&board, Faction::Cross, &normal, &mut rng),
+            2
+        );
+
+        let misere = Lines::default().with_win_mode(game::WinMode::Misere);
+        assert_ne!(
+            AiStrategy::Minimax.pick(&board, Faction::Cross, &misere, &mut rng),
+            2
+        );
+        assert!(evaluate_moves(&board, Faction::Cross, &misere)[2].unwrap() < 0);
+    }
+}
@@ -0,0 +1,116 @@
+use super::game::{Faction, BOARD_SIZE};
+
+/// Names each cell by where it is on a board with the given number of cells per side, as seen on
+/// the screen. Cells of larger boards are named by column and row, counted from the top left.
+pub fn cell_name(index: usize, board_size: usize) -> String {
+    if board_size != BOARD_SIZE {
+        let (x, y) = (index / board_size, index % board_size);
+        return format!("column {}, row {}", x + 1, board_size - y);
+    }
+
+    // indexed by x * 3 + y, with y+ up
+    const NAMES: [&str; 9] = [
+        "bottom-left",
+        "left",
+        "top-left",
+        "bottom",
+        "center",
+        "top",
+        "bottom-right",
+        "right",
+        "top-right",
+    ];
+    NAMES[index].to_string()
+}
+
+/// Describes a move on a board with the given number of cells per side in a sentence. If the game
+/// is against an AI, `user` is the faction of the user, so it's told from their point of view.
+/// Between two humans, factions are named instead.
+pub fn move_announcement(
+    index: usize,
+    board_size: usize,
+    faction: Faction,
+    user: Option<Faction>,
+) -> String {
+    let player = match user {
+        Some(user) if user == faction => "You",
+        Some(_) => "Opponent",
+        None => faction_name(faction),
+    };
+    format!("{} played {}", player, cell_name(index, board_size))
+}
+
+/// Describes how a finished game ended, from the same point of view as `move_announcement`.
+pub fn result_announcement(winner: Option<Faction>, user: Option<Faction>) -> String {
+    match (winner, user) {
+        (None, _) => "Draw".to_string(),
+        (Some(winner), Some(user)) if winner == user => "You win".to_string(),
+        (Some(_), Some(_)) => "You lose".to_string(),
+        (Some(winner), None) => format!("{} wins", faction_name(winner)),
+    }
+}
+
+/// Describes whose turn it is in a running game, from the same point of view as
+/// `move_announcement`.
+pub fn turn_announcement(turn: Faction, user: Option<Faction>) -> String {
+    match user {
+        Some(user) if user == turn => format!("Your turn ({})", faction_symbol(turn)),
+        Some(_) => "Thinking…".to_string(),
+        None => format!("{} to move", faction_name(turn)),
+    }
+}
+
+fn faction_name(faction: Faction) -> &'static str {
+    match faction {
+        Faction::Cross => "Cross",
+        Faction::Ring => "Ring",
+    }
+}
+
+fn faction_symbol(faction: Faction) -> char {
+    match faction {
+        Faction::Cross => 'X',
+        Faction::Ring => 'O',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_named_as_seen() {
+        assert_eq!(cell_name(0, 3), "bottom-left");
+        assert_eq!(cell_name(4, 3), "center");
+        assert_eq!(cell_name(8, 3), "top-right");
+        // the bottom right cell of a 4x4 board
+        assert_eq!(cell_name(12, 4), "column 4, row 4");
+        assert_eq!(cell_name(3, 4), "column 1, row 1");
+    }
+
+    #[test]
+    fn announcements_take_the_users_side() {
+        let (ring, cross) = (Faction::Ring, Faction::Cross);
+        assert_eq!(
+            move_announcement(4, 3, ring, Some(ring)),
+            "You played center"
+        );
+        assert_eq!(
+            move_announcement(2, 3, cross, Some(ring)),
+            "Opponent played top-left"
+        );
+        assert_eq!(
+            move_announcement(6, 3, cross, None),
+            "Cross played bottom-right"
+        );
+
+        assert_eq!(result_announcement(Some(ring), Some(ring)), "You win");
+        assert_eq!(result_announcement(Some(cross), Some(ring)), "You lose");
+        assert_eq!(result_announcement(Some(ring), None), "Ring wins");
+        assert_eq!(result_announcement(None, Some(ring)), "Draw");
+
+        assert_eq!(turn_announcement(ring, Some(ring)), "Your turn (O)");
+        assert_eq!(turn_announcement(cross, Some(ring)), "Thinking…");
+        assert_eq!(turn_announcement(cross, None), "Cross to move");
+    }
+}
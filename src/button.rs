@@ -0,0 +1,82 @@
+use {std::str::FromStr, thiserror::Error};
+
+#[derive(Debug, Error)]
+#[error("Unknown mouse button state {0:?}, expected one of `press` or `release`")]
+pub struct UnknownPressOrReleaseError(String);
+
+/// When a click places a mark.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PressOrRelease {
+    /// As soon as the mouse button goes down. Moving off the cell before letting go takes the
+    /// mark back again.
+    Press,
+    /// Once the mouse button goes up again.
+    Release,
+}
+
+impl FromStr for PressOrRelease {
+    type Err = UnknownPressOrReleaseError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "press" => Ok(Self::Press),
+            "release" => Ok(Self::Release),
+            _ => Err(UnknownPressOrReleaseError(source.to_string())),
+        }
+    }
+}
+
+/// How a single cell looks when cells are shown as buttons.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ButtonState {
+    Idle,
+    /// The cursor is above it.
+    Hovered,
+    /// The left mouse button went down on it and hasn't been released yet.
+    Pressed,
+}
+
+/// Tracks which cell is hovered and pressed, so cells can behave like buttons: A mark is only
+/// placed if the mouse button is released on the same cell it was pressed on.
+#[derive(Debug, Default)]
+pub struct Buttons {
+    hovered: Option<usize>,
+    pressed: Option<usize>,
+}
+
+impl Buttons {
+    /// Moves the hover to the given cell, or away from all of them for `None`. Returns whether
+    /// that changed anything.
+    pub fn hover(&mut self, cell: Option<usize>) -> bool {
+        let changed = self.hovered != cell;
+        self.hovered = cell;
+        changed
+    }
+
+    /// The left mouse button went down, pressing the hovered cell if there is one.
+    pub fn press(&mut self) {
+        self.pressed = self.hovered;
+    }
+
+    /// The left mouse button went up. Returns the cell which has been clicked, if it's still the
+    /// one the button went down on.
+    pub fn release(&mut self) -> Option<usize> {
+        self.pressed
+            .take()
+            .filter(|&cell| Some(cell) == self.hovered)
+    }
+
+    /// The state of each of the given number of cells.
+    pub fn states(&self, cells: usize) -> Vec<ButtonState> {
+        (0..cells)
+            .map(|cell| match (self.pressed, self.hovered) {
+                // dragging off a pressed button lets it pop back up until the cursor returns
+                (Some(pressed), Some(hovered)) if pressed == cell && hovered == cell => {
+                    ButtonState::Pressed
+                }
+                (_, Some(hovered)) if hovered == cell => ButtonState::Hovered,
+                _ => ButtonState::Idle,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,265 @@
+use {
+    super::{ai::Difficulty, game::Faction},
+    std::str::FromStr,
+    thiserror::Error,
+    winit::event::VirtualKeyCode,
+};
+
+#[derive(Debug, Error)]
+#[error("Unknown chooser mode {0:?}, expected one of `off`, `always` or `once`")]
+pub struct UnknownChooserModeError(String);
+
+/// When the user gets to pick their faction and opponent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChooserMode {
+    /// Never, the faction is decided by the faction choice and the AI difficulty by the settings.
+    Off,
+    /// Before every game.
+    Always,
+    /// Before the first game only, later games remember the choices.
+    Once,
+}
+
+impl FromStr for ChooserMode {
+    type Err = UnknownChooserModeError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "off" => Ok(Self::Off),
+            "always" => Ok(Self::Always),
+            "once" => Ok(Self::Once),
+            _ => Err(UnknownChooserModeError(source.to_string())),
+        }
+    }
+}
+
+/// Who plays the faction the user didn't pick.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Opponent {
+    /// The AI, at whatever difficulty the app is set to.
+    Ai,
+    /// Another human on the same machine, taking turns with the mouse.
+    Human,
+}
+
+/// Where the app is, between games or during one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameState {
+    /// Waiting for the user to press X or O.
+    ChoosingFaction,
+    /// The user picked their faction, and is now picking their opponent with 1 to 4.
+    ChoosingOpponent(Faction),
+    Playing,
+    /// The pause menu is open, nothing else can be done until it's closed again.
+    Paused,
+}
+
+/// Walks the user through picking their faction and opponent before a game.
+#[derive(Debug)]
+pub struct Chooser {
+    mode: ChooserMode,
+    // whether there's an opponent to pick at all, it's fixed in challenge mode
+    pick_opponent: bool,
+    state: GameState,
+    // where to go back to once the pause menu is closed
+    paused_from: Option<GameState>,
+    // the last complete choices
+    faction: Option<Faction>,
+    opponent: Option<Opponent>,
+    difficulty: Option<Difficulty>,
+}
+
+impl Chooser {
+    pub fn new(mode: ChooserMode, pick_opponent: bool) -> Self {
+        Self {
+            mode,
+            pick_opponent,
+            state: GameState::Playing,
+            paused_from: None,
+            faction: None,
+            opponent: None,
+            difficulty: None,
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    /// The faction picked last, if any.
+    pub fn faction(&self) -> Option<Faction> {
+        self.faction
+    }
+
+    /// The opponent picked last, if any.
+    pub fn opponent(&self) -> Option<Opponent> {
+        self.opponent
+    }
+
+    /// The AI difficulty picked last, if any. Stays picked while playing against a human.
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        self.difficulty
+    }
+
+    /// To be called before each game. Enters the chooser if the mode asks for it, and returns
+    /// whether it did so.
+    pub fn begin(&mut self) -> bool {
+        let choose = match self.mode {
+            ChooserMode::Off => false,
+            ChooserMode::Always => true,
+            ChooserMode::Once => self.faction.is_none(),
+        };
+
+        self.state = if choose {
+            GameState::ChoosingFaction
+        } else {
+            GameState::Playing
+        };
+        choose
+    }
+
+    /// Opens the pause menu, remembering what was going on before. Does nothing if it's open
+    /// already.
+    pub fn pause(&mut self) {
+        if self.state != GameState::Paused {
+            self.paused_from = Some(self.state);
+            self.state = GameState::Paused;
+        }
+    }
+
+    /// Closes the pause menu again, going back to what was going on before it was opened.
+    pub fn resume(&mut self) {
+        if let Some(state) = self.paused_from.take() {
+            self.state = state;
+        }
+    }
+
+    /// Feeds a pressed key into the chooser. Returns true if that completed the choices, so the
+    /// game can start. Keys not meaning anything in the current state are ignored.
+    pub fn press(&mut self, key: VirtualKeyCode) -> bool {
+        match (self.state, key) {
+            (GameState::ChoosingFaction, VirtualKeyCode::X | VirtualKeyCode::O) => {
+                let faction = match key {
+                    VirtualKeyCode::X => Faction::Cross,
+                    _ => Faction::Ring,
+                };
+
+                if self.pick_opponent {
+                    self.state = GameState::ChoosingOpponent(faction);
+                    false
+                } else {
+                    self.faction = Some(faction);
+                    self.state = GameState::Playing;
+                    true
+                }
+            }
+            (
+                GameState::ChoosingOpponent(faction),
+                VirtualKeyCode::Key1
+                | VirtualKeyCode::Key2
+                | VirtualKeyCode::Key3
+                | VirtualKeyCode::Key4,
+            ) => {
+                let difficulty = match key {
+                    VirtualKeyCode::Key1 => Some(Difficulty::Easy),
+                    VirtualKeyCode::Key2 => Some(Difficulty::Medium),
+                    VirtualKeyCode::Key3 => Some(Difficulty::Hard),
+                    _ => None,
+                };
+                match difficulty {
+                    Some(difficulty) => {
+                        self.opponent = Some(Opponent::Ai);
+                        self.difficulty = Some(difficulty);
+                    }
+                    None => self.opponent = Some(Opponent::Human),
+                }
+                self.faction = Some(faction);
+                self.state = GameState::Playing;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_faction_then_opponent() {
+        let mut chooser = Chooser::new(ChooserMode::Always, true);
+        assert_eq!(chooser.state(), GameState::Playing);
+        assert!(chooser.begin());
+        assert_eq!(chooser.state(), GameState::ChoosingFaction);
+
+        // opponents can't be picked before the faction
+        assert!(!chooser.press(VirtualKeyCode::Key2));
+        assert_eq!(chooser.state(), GameState::ChoosingFaction);
+        assert!(!chooser.press(VirtualKeyCode::O));
+        assert_eq!(chooser.state(), GameState::ChoosingOpponent(Faction::Ring));
+        assert!(!chooser.press(VirtualKeyCode::X));
+        assert!(chooser.press(VirtualKeyCode::Key3));
+        assert_eq!(chooser.state(), GameState::Playing);
+        assert_eq!(chooser.faction(), Some(Faction::Ring));
+        assert_eq!(chooser.opponent(), Some(Opponent::Ai));
+        assert_eq!(chooser.difficulty(), Some(Difficulty::Hard));
+
+        // always means before every game, with the last choices kept until new ones are made
+        assert!(chooser.begin());
+        assert_eq!(chooser.state(), GameState::ChoosingFaction);
+        chooser.press(VirtualKeyCode::X);
+        assert!(chooser.press(VirtualKeyCode::Key4));
+        assert_eq!(chooser.faction(), Some(Faction::Cross));
+        assert_eq!(chooser.opponent(), Some(Opponent::Human));
+        assert_eq!(chooser.difficulty(), Some(Difficulty::Hard));
+
+        chooser.begin();
+        chooser.press(VirtualKeyCode::X);
+        assert!(chooser.press(VirtualKeyCode::Key2));
+        assert_eq!(chooser.opponent(), Some(Opponent::Ai));
+        assert_eq!(chooser.difficulty(), Some(Difficulty::Medium));
+    }
+
+    #[test]
+    fn once_only_asks_before_first_game() {
+        let mut chooser = Chooser::new(ChooserMode::Once, true);
+        assert!(chooser.begin());
+        chooser.press(VirtualKeyCode::X);
+        assert!(chooser.press(VirtualKeyCode::Key1));
+        assert!(!chooser.begin());
+        assert_eq!(chooser.state(), GameState::Playing);
+        assert_eq!(chooser.opponent(), Some(Opponent::Ai));
+        assert_eq!(chooser.difficulty(), Some(Difficulty::Easy));
+    }
+
+    #[test]
+    fn fixed_opponent_skips_picking_one() {
+        let mut chooser = Chooser::new(ChooserMode::Always, false);
+        chooser.begin();
+        assert!(chooser.press(VirtualKeyCode::O));
+        assert_eq!(chooser.state(), GameState::Playing);
+        assert_eq!(chooser.opponent(), None);
+    }
+
+    #[test]
+    fn off_never_asks() {
+        let mut chooser = Chooser::new(ChooserMode::Off, true);
+        assert!(!chooser.begin());
+        assert!(!chooser.press(VirtualKeyCode::X));
+        assert_eq!(chooser.state(), GameState::Playing);
+    }
+
+    #[test]
+    fn pausing_returns_to_the_same_step() {
+        let mut chooser = Chooser::new(ChooserMode::Always, true);
+        chooser.begin();
+        chooser.press(VirtualKeyCode::O);
+        chooser.pause();
+        assert_eq!(chooser.state(), GameState::Paused);
+        // the chooser doesn't react while paused
+        assert!(!chooser.press(VirtualKeyCode::Key1));
+        chooser.resume();
+        assert_eq!(chooser.state(), GameState::ChoosingOpponent(Faction::Ring));
+    }
+}
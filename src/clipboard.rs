@@ -0,0 +1,152 @@
+use {
+    super::render::Frame,
+    std::{
+        io::{self, Write},
+        process::{Command, Stdio},
+    },
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+pub enum ClipboardError {
+    #[error("Could not encode the frame as PNG: {0}")]
+    Encoding(#[from] png::EncodingError),
+    #[error("Neither of {0:?} could take the frame, is one of them installed?")]
+    NoProgram(Vec<String>),
+    #[error("Could not pass the frame to {0}: {1}")]
+    Io(String, io::Error),
+}
+
+/// A program which puts whatever it's given on stdin into the clipboard, along with its
+/// arguments.
+#[derive(Debug, Clone, Copy)]
+pub struct Program<'a> {
+    pub name: &'a str,
+    pub args: &'a [&'a str],
+}
+
+/// What's tried in order to put frames into the clipboard, first for Wayland, then for X11.
+pub const PROGRAMS: [Program; 2] = [
+    Program {
+        name: "wl-copy",
+        args: &["--type", "image/png"],
+    },
+    Program {
+        name: "xclip",
+        args: &["-selection", "clipboard", "-target", "image/png", "-in"],
+    },
+];
+
+/// Copies the given frame into the clipboard as PNG, through the first of [`PROGRAMS`] which is
+/// installed. Returns the name of that one.
+pub fn copy_frame(frame: &Frame) -> Result<&'static str, ClipboardError> {
+    copy_with(&PROGRAMS, &to_png(frame)?)
+}
+
+/// Passes the given data to the first of the given programs which can be started. Returns the
+/// name of that one.
+pub fn copy_with<'a>(programs: &[Program<'a>], data: &[u8]) -> Result<&'a str, ClipboardError> {
+    for program in programs {
+        let mut child = match Command::new(program.name)
+            .args(program.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(ClipboardError::Io(program.name.to_string(), e)),
+        };
+
+        // the stdin handle has to be dropped before waiting, otherwise the program waits for more
+        let written = child
+            .stdin
+            .take()
+            .expect("stdin to be piped")
+            .write_all(data);
+        let status = child.wait();
+        let fail = |e| ClipboardError::Io(program.name.to_string(), e);
+        written.map_err(fail)?;
+        let status = status.map_err(fail)?;
+        if !status.success() {
+            return Err(fail(io::Error::other(format!("exited with {}", status))));
+        }
+        return Ok(program.name);
+    }
+
+    Err(ClipboardError::NoProgram(
+        programs
+            .iter()
+            .map(|program| program.name.to_string())
+            .collect(),
+    ))
+}
+
+/// Encodes the given frame as PNG, which unlike PPM is what clipboards usually expect images as.
+pub fn to_png(frame: &Frame) -> Result<Vec<u8>, png::EncodingError> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, frame.width, frame.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&frame.rgba)?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::fs};
+
+    fn frame() -> Frame {
+        Frame {
+            width: 2,
+            height: 1,
+            rgba: vec![255, 0, 0, 255, 0, 0, 255, 255],
+        }
+    }
+
+    #[test]
+    fn frame_is_passed_to_the_first_installed_program() {
+        let path =
+            std::env::temp_dir().join(format!("tic-tac-gpu-clipboard-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let programs = [
+            Program {
+                name: "tic-tac-gpu-not-installed",
+                args: &[],
+            },
+            // stands in for the clipboard, writing to a file instead
+            Program {
+                name: "sh",
+                args: &["-c", "cat > \"$0\"", path],
+            },
+        ];
+
+        let png = to_png(&frame()).unwrap();
+        assert_eq!(copy_with(&programs, &png).unwrap(), "sh");
+        let copied = fs::read(path).unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(copied, png);
+
+        let decoder = png::Decoder::new(copied.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut rgba = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut rgba).unwrap();
+        assert_eq!(rgba, frame().rgba);
+    }
+
+    #[test]
+    fn missing_programs_are_reported() {
+        let programs = [Program {
+            name: "tic-tac-gpu-not-installed",
+            args: &[],
+        }];
+        assert!(matches!(
+            copy_with(&programs, &[]),
+            Err(ClipboardError::NoProgram(names)) if names == ["tic-tac-gpu-not-installed"]
+        ));
+        assert!(matches!(
+            copy_with(&[Program { name: "false", args: &[] }], &[]),
+            Err(ClipboardError::Io(name, _)) if name == "false"
+        ));
+    }
+}
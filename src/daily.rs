@@ -0,0 +1,104 @@
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A day in the Gregorian calendar, in UTC so everyone around the world shares the same one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Date {
+    year: u64,
+    month: u64,
+    day: u64,
+}
+
+impl Date {
+    /// Today, in UTC. A system clock set before 1970 counts as 1970-01-01.
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        Self::from_days_since_epoch(seconds / (24 * 60 * 60))
+    }
+
+    /// The seed everyone playing on this day gets, which is just the date written as YYYYMMDD.
+    /// So passing it as a seed later on replays an earlier day.
+    pub fn seed(self) -> u64 {
+        self.year * 10_000 + self.month * 100 + self.day
+    }
+
+    // Counts the days like the Gregorian calendar does, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    fn from_days_since_epoch(days: u64) -> Self {
+        // shifted to start at 0000-03-01, so the leap day is the last one of each year
+        let days = days + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // starting with march as 0
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+
+        Self {
+            year: era * 400 + year_of_era + u64::from(month <= 2),
+            month,
+            day: day_of_year - (153 * shifted_month + 2) / 5 + 1,
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            ai::AiStrategy,
+            game::{FactionChoice, Game, Handicap, Lines},
+        },
+        rand::{rngs::StdRng, SeedableRng},
+    };
+
+    #[test]
+    fn days_are_counted_like_the_calendar() {
+        let date = |days| Date::from_days_since_epoch(days).to_string();
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11_016), "2000-02-29");
+        assert_eq!(date(20_741), "2026-10-15");
+        // 2100 isn't a leap year
+        assert_eq!(date(47_541), "2100-03-01");
+        assert_eq!(Date::from_days_since_epoch(20_741).seed(), 20_261_015);
+    }
+
+    #[test]
+    fn same_day_plays_the_same() {
+        let first_moves = |days| {
+            let seed = Date::from_days_since_epoch(days).seed();
+            let mut game = Game::new(
+                StdRng::seed_from_u64(seed),
+                FactionChoice::Random,
+                Lines::default(),
+                Handicap::default(),
+            );
+            for _ in 0..4 {
+                game.ai_turn(AiStrategy::Random);
+            }
+            (game.user_faction(), game.moves().to_vec())
+        };
+
+        assert_eq!(first_moves(20_741), first_moves(20_741));
+        // with only a few moves to pick from, a couple of days might still happen to agree
+        let other_days: Vec<_> = (20_742..20_752).map(first_moves).collect();
+        assert!(other_days.iter().any(|moves| *moves != first_moves(20_741)));
+    }
+}
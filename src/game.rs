@@ -0,0 +1,1423 @@
+use {
+    super::ai::AiStrategy,
+    rand::{distributions::Standard, prelude::*, rngs::StdRng},
+    std::{fmt, fs, io, path::Path, str::FromStr},
+    thiserror::Error,
+};
+
+/// How many cells there are on each side of the classic board.
+pub const BOARD_SIZE: usize = 3;
+
+/// The most cells per side a board can have. Beyond that, the marks get tiny and the move list
+/// huge.
+pub const MAX_BOARD_SIZE: usize = 5;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Cross,
+    Ring,
+    Empty,
+}
+
+impl Cell {
+    // Returns whether this cell is empty, false if it is used by any faction.
+    pub fn is_empty(self) -> bool {
+        matches!(self, Self::Empty)
+    }
+
+    // Returns the faction occupying this cell, if any.
+    pub fn faction(self) -> Option<Faction> {
+        match self {
+            Self::Cross => Some(Faction::Cross),
+            Self::Ring => Some(Faction::Ring),
+            Self::Empty => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Faction {
+    Cross,
+    Ring,
+}
+
+impl Faction {
+    // Determines whether this faction makes the first turn. Ring is the one for that.
+    pub fn goes_first(self) -> bool {
+        match self {
+            Self::Cross => false,
+            Self::Ring => true,
+        }
+    }
+
+    // Returns the opposite faction, e.g. cross for ring and ring for cross.
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Cross => Self::Ring,
+            Self::Ring => Self::Cross,
+        }
+    }
+}
+
+impl Distribution<Faction> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Faction {
+        // exact mapping doesn't matter
+        match rng.gen() {
+            false => Faction::Cross,
+            true => Faction::Ring,
+        }
+    }
+}
+
+impl From<Faction> for Cell {
+    fn from(faction: Faction) -> Self {
+        match faction {
+            Faction::Cross => Cell::Cross,
+            Faction::Ring => Cell::Ring,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+pub enum MoveError {
+    #[error("Cell {0} is out of range, there are only {1} cells")]
+    OutOfRange(usize, usize),
+    #[error("Cell {0} is already occupied")]
+    Occupied(usize),
+    #[error("The game is already over")]
+    GameOver,
+    #[error("It's not {0:?}'s turn")]
+    WrongTurn(Faction),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+pub enum DecodeError {
+    #[error("Encoded game is truncated, it needs at least a faction and an outcome byte")]
+    Truncated,
+    #[error("Invalid faction byte {0}, expected 0 for cross or 1 for ring")]
+    InvalidFaction(u8),
+    #[error("Invalid cell {0}, it's not on the board")]
+    InvalidCell(u8),
+    #[error("Invalid outcome byte {0}, expected 0 to 3")]
+    InvalidOutcome(u8),
+    #[error("Encoded moves are not a valid game: {0}")]
+    IllegalMove(MoveError),
+    #[error("Encoded outcome doesn't match the outcome of the encoded moves")]
+    OutcomeMismatch,
+}
+
+#[derive(Debug, Error)]
+pub enum LinesError {
+    #[error("Could not read win lines: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid cell {0:?} in win line {1}, expected 0 to {2}")]
+    InvalidCell(String, usize, usize),
+    #[error("There has to be at least one win line")]
+    Empty,
+}
+
+#[derive(Debug, Error)]
+#[error("Unknown win mode {0:?}, expected one of `normal` or `misere`")]
+pub struct UnknownWinModeError(String);
+
+/// What completing a line means for the faction doing so.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum WinMode {
+    /// It wins, as usual.
+    #[default]
+    Normal,
+    /// It loses instead, which is also known as misère or reverse tic tac toe.
+    Misere,
+}
+
+impl FromStr for WinMode {
+    type Err = UnknownWinModeError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "normal" => Ok(Self::Normal),
+            "misere" => Ok(Self::Misere),
+            _ => Err(UnknownWinModeError(source.to_string())),
+        }
+    }
+}
+
+/// All lines which, if completely filled by one faction, end the game. Each is a set of cell
+/// indices, not necessarily in a straight line or as long as a side. Whether that's a win or a
+/// loss for the faction filling it is up to the win mode.
+///
+/// They also decide how large the board is, since they only make sense on one size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lines {
+    cells: Vec<Vec<usize>>,
+    win_mode: WinMode,
+    // cells per side of the board
+    size: usize,
+}
+
+impl Default for Lines {
+    fn default() -> Self {
+        Self::full(BOARD_SIZE)
+    }
+}
+
+impl Lines {
+    /// Every column, row and both diagonals of a board with the given number of cells per side,
+    /// in that order. Cells are indexed by `x * size + y`, with y+ up.
+    pub fn full(size: usize) -> Self {
+        Self::in_a_row(size, size)
+    }
+
+    /// Every run of `length` cells in a column, row or diagonal of a board with the given number
+    /// of cells per side, in that order. Same as `full` if the runs span the whole board.
+    pub fn in_a_row(size: usize, length: usize) -> Self {
+        let index = |x: usize, y: usize| x * size + y;
+        // where runs can start along one axis, so they still end on the board
+        let starts = 0..=size - length;
+        let mut cells: Vec<Vec<usize>> = Vec::new();
+
+        for x in 0..size {
+            for y in starts.clone() {
+                cells.push((0..length).map(|i| index(x, y + i)).collect());
+            }
+        }
+        for y in 0..size {
+            for x in starts.clone() {
+                cells.push((0..length).map(|i| index(x + i, y)).collect());
+            }
+        }
+        for x in starts.clone() {
+            for y in starts.clone() {
+                cells.push((0..length).map(|i| index(x + i, y + i)).collect());
+            }
+        }
+        for x in starts.clone() {
+            for y in starts.clone() {
+                cells.push(
+                    (0..length)
+                        .map(|i| index(x + i, y + length - 1 - i))
+                        .collect(),
+                );
+            }
+        }
+
+        Self {
+            cells,
+            win_mode: WinMode::default(),
+            size,
+        }
+    }
+
+    /// Parses one win line per line, each as cell indices separated by whitespace or commas,
+    /// for a board with the given number of cells per side. Empty lines and everything after a
+    /// `#` are ignored.
+    pub fn parse(source: &str, size: usize) -> Result<Self, LinesError> {
+        let last = size * size - 1;
+        let mut lines = Vec::new();
+
+        for text in source.lines() {
+            let text = text.split('#').next().unwrap_or_default();
+            let line = text
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|index| !index.is_empty())
+                .map(|index| match index.parse() {
+                    Ok(index) if index <= last => Ok(index),
+                    _ => Err(LinesError::InvalidCell(
+                        index.to_string(),
+                        lines.len() + 1,
+                        last,
+                    )),
+                })
+                .collect::<Result<Vec<usize>, _>>()?;
+
+            if !line.is_empty() {
+                lines.push(line);
+            }
+        }
+
+        if lines.is_empty() {
+            return Err(LinesError::Empty);
+        }
+        Ok(Self {
+            cells: lines,
+            win_mode: WinMode::default(),
+            size,
+        })
+    }
+
+    /// Reads win lines from the given file, in the format `parse` accepts.
+    pub fn from_file(path: impl AsRef<Path>, size: usize) -> Result<Self, LinesError> {
+        Self::parse(&fs::read_to_string(path)?, size)
+    }
+
+    /// The same lines, but with completing one meaning what the given win mode says.
+    pub fn with_win_mode(self, win_mode: WinMode) -> Self {
+        Self { win_mode, ..self }
+    }
+
+    pub fn win_mode(&self) -> WinMode {
+        self.win_mode
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// How many cells there are on each side of the board these lines are for.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum HandicapError {
+    #[error("Invalid handicap mark {0:?}, expected `x` or `o` followed by a cell index")]
+    InvalidMark(String),
+    #[error("Cell {0} is marked more than once by the handicap")]
+    Duplicate(usize),
+    #[error("Cell {0} of the handicap is not on the board")]
+    OffBoard(usize),
+    #[error("The handicap already ends the game")]
+    GameOver,
+}
+
+/// Marks placed before each game starts, e.g. to give the weaker player a free center. They count
+/// like any other mark, but aren't moves, so they can't be taken back.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Handicap {
+    // the cell index and mark of each, ordered by index
+    marks: Vec<(usize, Cell)>,
+}
+
+impl Handicap {
+    /// A board with the given number of cells per side and nothing on it but the handicap.
+    pub fn board(&self, size: usize) -> Vec<Cell> {
+        let mut board = vec![Cell::Empty; size * size];
+        for &(index, cell) in &self.marks {
+            board[index] = cell;
+        }
+        board
+    }
+
+    /// Makes sure the handicap fits onto the board of the given lines, and that there's still a
+    /// game to play after placing it.
+    pub fn validate(self, lines: &Lines) -> Result<Self, HandicapError> {
+        let cells = lines.size() * lines.size();
+        if let Some(&(index, _)) = self.marks.iter().find(|&&(index, _)| index >= cells) {
+            return Err(HandicapError::OffBoard(index));
+        }
+        if is_finished(&self.board(lines.size()), lines) {
+            return Err(HandicapError::GameOver);
+        }
+        Ok(self)
+    }
+}
+
+impl fmt::Display for Handicap {
+    /// Lists the marks in the format `from_str` accepts, or `none` if there aren't any.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let marks: Vec<_> = self
+            .marks
+            .iter()
+            .map(|&(index, cell)| match cell {
+                Cell::Cross => format!("x{}", index),
+                _ => format!("o{}", index),
+            })
+            .collect();
+
+        if marks.is_empty() {
+            f.write_str("none")
+        } else {
+            f.write_str(&marks.join(" "))
+        }
+    }
+}
+
+impl FromStr for Handicap {
+    type Err = HandicapError;
+
+    /// Parses marks separated by whitespace or commas, each `x` for cross or `o` for ring,
+    /// followed by the index of the cell to place it on. For example, `x4` gives cross the center.
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut marks: Vec<(usize, Cell)> = Vec::new();
+
+        for mark in source
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|mark| !mark.is_empty())
+        {
+            let invalid = || HandicapError::InvalidMark(mark.to_string());
+            let (cell, index) = if let Some(index) = mark.strip_prefix('x') {
+                (Cell::Cross, index)
+            } else if let Some(index) = mark.strip_prefix('o') {
+                (Cell::Ring, index)
+            } else {
+                return Err(invalid());
+            };
+            let index = index.parse().map_err(|_| invalid())?;
+
+            if marks.iter().any(|&(marked, _)| marked == index) {
+                return Err(HandicapError::Duplicate(index));
+            }
+            marks.push((index, cell));
+        }
+
+        marks.sort_unstable_by_key(|&(index, _)| index);
+        Ok(Self { marks })
+    }
+}
+
+#[derive(Debug, Error)]
+#[error(
+    "Unknown faction choice {0:?}, expected one of `cross`, `ring`, `random`, `alternate`, \
+     `loser-opens` or `loser-opens-swap`"
+)]
+pub struct UnknownFactionChoiceError(String);
+
+/// How the user's faction is decided on at the start of each game.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FactionChoice {
+    /// The same faction every game.
+    Fixed(Faction),
+    /// Flip a coin every game.
+    Random,
+    /// Cross in odd games, ring in even games, so both sides get played equally often.
+    Alternate,
+    /// Whoever lost the last game opens the next one. After a draw, or if the last game wasn't
+    /// finished, the user swaps factions if `swap_on_draw` is set, otherwise a coin is flipped.
+    LoserOpens { swap_on_draw: bool },
+}
+
+/// How the previous game went, as far as picking the faction for the next one is concerned.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct LastGame {
+    user_faction: Faction,
+    // None for a draw or a game which was abandoned before it was over
+    loser: Option<Faction>,
+}
+
+impl FactionChoice {
+    // Picks the user's faction for the game with the given number, counting from 1, which is
+    // opened by the given faction.
+    fn pick(
+        self,
+        game_number: u32,
+        last: Option<LastGame>,
+        opener: Faction,
+        rng: &mut impl Rng,
+    ) -> Faction {
+        match (self, last) {
+            (Self::Fixed(faction), _) => faction,
+            (Self::Random, _) | (Self::LoserOpens { .. }, None) => rng.gen(),
+            (Self::Alternate, _) if game_number % 2 == 1 => Faction::Cross,
+            (Self::Alternate, _) => Faction::Ring,
+            (Self::LoserOpens { swap_on_draw }, Some(last)) => match last.loser {
+                Some(loser) if loser == last.user_faction => opener,
+                Some(_) => opener.opposite(),
+                None if swap_on_draw => last.user_faction.opposite(),
+                None => rng.gen(),
+            },
+        }
+    }
+}
+
+impl FromStr for FactionChoice {
+    type Err = UnknownFactionChoiceError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "cross" => Ok(Self::Fixed(Faction::Cross)),
+            "ring" => Ok(Self::Fixed(Faction::Ring)),
+            "random" => Ok(Self::Random),
+            "alternate" => Ok(Self::Alternate),
+            "loser-opens" => Ok(Self::LoserOpens {
+                swap_on_draw: false,
+            }),
+            "loser-opens-swap" => Ok(Self::LoserOpens { swap_on_draw: true }),
+            _ => Err(UnknownFactionChoiceError(source.to_string())),
+        }
+    }
+}
+
+/// How a finished game ended.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Win(Faction),
+    Draw,
+}
+
+/// Returns how the game on the given board ended, or `None` if it's still going on.
+pub fn outcome(board: &[Cell], lines: &Lines) -> Option<Outcome> {
+    match winner(board, lines) {
+        Some(faction) => Some(Outcome::Win(faction)),
+        None if is_finished(board, lines) => Some(Outcome::Draw),
+        None => None,
+    }
+}
+
+/// Returns the faction which won on the given board, if any. That's the one which completed a
+/// line, or in misère, the other one.
+pub fn winner(board: &[Cell], lines: &Lines) -> Option<Faction> {
+    let completer = completed_by(board, lines)?;
+    Some(match lines.win_mode {
+        WinMode::Normal => completer,
+        WinMode::Misere => completer.opposite(),
+    })
+}
+
+/// Returns the faction which completed a line on the given board, if any.
+pub fn completed_by(board: &[Cell], lines: &Lines) -> Option<Faction> {
+    completed_line(board, lines).and_then(|line| board[line[0]].faction())
+}
+
+/// Returns the first line marked completely by one faction on the given board, if any.
+pub fn completed_line<'a>(board: &[Cell], lines: &'a Lines) -> Option<&'a [usize]> {
+    lines.cells.iter().map(Vec::as_slice).find(|line| {
+        let first = board[line[0]];
+        !first.is_empty() && line.iter().all(|&index| board[index] == first)
+    })
+}
+
+/// Returns whose turn it is on the given board, as if ring opened and both sides marked cells in
+/// turns. Handicap marks shift this just like moves do.
+pub fn turn_on(board: &[Cell]) -> Faction {
+    let count = |cell| board.iter().filter(|&&other| other == cell).count();
+    if count(Cell::Ring) > count(Cell::Cross) {
+        Faction::Cross
+    } else {
+        Faction::Ring
+    }
+}
+
+/// Returns the indices of all cells which can still be marked.
+pub fn legal_moves(board: &[Cell]) -> impl Iterator<Item = usize> + '_ {
+    (0..board.len()).filter(|&index| board[index].is_empty())
+}
+
+/// Returns whether the game on the given board is finished, either by a win or a full board.
+pub fn is_finished(board: &[Cell], lines: &Lines) -> bool {
+    winner(board, lines).is_some() || !board.iter().copied().any(Cell::is_empty)
+}
+
+// Fills the low nibble of the last move byte if there's an odd number of moves.
+const PADDING_NIBBLE: u8 = 0xf;
+
+// 0 for a running game, 1 for a draw, 2 if cross won, 3 if ring won.
+fn outcome_byte(board: &[Cell], lines: &Lines) -> u8 {
+    match outcome(board, lines) {
+        None => 0,
+        Some(Outcome::Draw) => 1,
+        Some(Outcome::Win(Faction::Cross)) => 2,
+        Some(Outcome::Win(Faction::Ring)) => 3,
+    }
+}
+
+/// Returns a hash of the given moves, played starting with the given faction. The same game always
+/// has the same fingerprint, regardless of run or platform, so it can be used to find duplicates.
+///
+/// This is 64 bit FNV-1a over the opener followed by the moves, one byte each.
+pub fn game_fingerprint(moves: &[usize], first: Faction) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let opener = match first {
+        Faction::Cross => 0,
+        Faction::Ring => 1,
+    };
+    // cells fit into a byte easily, so there's no need to care about the width of usize
+    std::iter::once(opener)
+        .chain(moves.iter().map(|&index| index as u8))
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+}
+
+/// A single move, made by marking one cell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub index: usize,
+    pub faction: Faction,
+}
+
+/// Pure game state, without any knowledge about windows or rendering.
+#[derive(Debug)]
+pub struct Game {
+    board: Vec<Cell>,
+    // whose turn it is right now
+    turn: Faction,
+    game_over: bool,
+    // we need only one side to hold which faction it belongs to, the AI will then just be the
+    // other one
+    user_faction: Faction,
+    faction_choice: FactionChoice,
+    // counting from 1, survives resets
+    game_number: u32,
+    // what the board looks like before the first move, only not empty with a handicap
+    start: Vec<Cell>,
+    // who makes the first move on the start board
+    opener: Faction,
+    // all marked cells in order, not including the handicap
+    moves: Vec<usize>,
+    lines: Lines,
+    rng: StdRng,
+}
+
+impl Game {
+    /// Creates a new game with an empty board and the given win lines, assigning the user a
+    /// faction as per the given choice.
+    pub fn new(
+        mut rng: StdRng,
+        faction_choice: FactionChoice,
+        lines: Lines,
+        handicap: Handicap,
+    ) -> Self {
+        let start = handicap.board(lines.size());
+        let opener = turn_on(&start);
+        let user_faction = faction_choice.pick(1, None, opener, &mut rng);
+
+        Self {
+            board: start.clone(),
+            turn: opener,
+            game_over: false,
+            user_faction,
+            faction_choice,
+            game_number: 1,
+            start,
+            opener,
+            moves: Vec::new(),
+            lines,
+            rng,
+        }
+    }
+
+    /// Replays the given moves in a fresh game with the classic lines and no handicap, the first
+    /// one by the given faction. Fails on the first move which isn't legal at that point, like
+    /// marking a used cell or continuing after the game is over.
+    pub fn replay_from_moves(moves: &[usize], first: Faction) -> Result<Self, MoveError> {
+        let mut game = Self::new(
+            StdRng::seed_from_u64(0),
+            FactionChoice::Random,
+            Lines::default(),
+            Handicap::default(),
+        );
+        game.opener = first;
+        game.turn = first;
+
+        for &index in moves {
+            game.mark(index)?;
+        }
+        Ok(game)
+    }
+
+    pub fn board(&self) -> &[Cell] {
+        &self.board
+    }
+
+    pub fn turn(&self) -> Faction {
+        self.turn
+    }
+
+    pub fn user_faction(&self) -> Faction {
+        self.user_faction
+    }
+
+    /// Whether the game is finished, either by a win or a full board.
+    pub fn is_over(&self) -> bool {
+        self.game_over
+    }
+
+    /// The faction which won the game. `None` for draws and games still running.
+    ///
+    /// A move filling the last free cell while completing a line is a win, not a draw. Anything
+    /// tallying outcomes (the streak, self-play) relies on that, so it has to ask this instead of
+    /// looking at whether the board is full.
+    pub fn winner(&self) -> Option<Faction> {
+        winner(&self.board, &self.lines)
+    }
+
+    /// The line which ended the game, if it was won. `None` for draws and games still running.
+    pub fn completed_line(&self) -> Option<&[usize]> {
+        completed_line(&self.board, &self.lines)
+    }
+
+    /// How the game ended, telling draws apart from wins. `None` while it's still going on.
+    pub fn outcome(&self) -> Option<Outcome> {
+        outcome(&self.board, &self.lines)
+    }
+
+    pub fn lines(&self) -> &Lines {
+        &self.lines
+    }
+
+    /// The board before the first move, which has the handicap on it if there is one.
+    pub fn start(&self) -> &[Cell] {
+        &self.start
+    }
+
+    /// The faction making the first move on the start board.
+    pub fn opener(&self) -> Faction {
+        self.opener
+    }
+
+    /// All cells marked so far, in order, not including the handicap. The first one was marked by
+    /// the opener.
+    pub fn moves(&self) -> &[usize] {
+        &self.moves
+    }
+
+    /// Same as `moves`, but with the faction which made each move, since they take turns starting
+    /// with the opener.
+    pub fn history(&self) -> impl Iterator<Item = Move> + '_ {
+        let factions = std::iter::successors(Some(self.opener), |faction| Some(faction.opposite()));
+        self.moves
+            .iter()
+            .zip(factions)
+            .map(|(&index, faction)| Move { index, faction })
+    }
+
+    /// Fingerprint of the moves so far, see [`game_fingerprint`].
+    pub fn fingerprint(&self) -> u64 {
+        game_fingerprint(&self.moves, self.opener)
+    }
+
+    /// Overrides the user's faction for the current game. Only makes sense before any move.
+    pub fn choose_user_faction(&mut self, faction: Faction) {
+        self.user_faction = faction;
+    }
+
+    /// Marks the given cell for the faction whose turn it is, and passes the turn on.
+    ///
+    /// Leaves the game untouched and returns an error if the move isn't possible.
+    pub fn mark(&mut self, index: usize) -> Result<(), MoveError> {
+        if self.game_over {
+            return Err(MoveError::GameOver);
+        }
+        match self.board.get(index) {
+            None => return Err(MoveError::OutOfRange(index, self.board.len())),
+            Some(cell) if !cell.is_empty() => return Err(MoveError::Occupied(index)),
+            Some(_) => (),
+        }
+
+        self.board[index] = self.turn.into();
+        self.moves.push(index);
+        self.turn = self.turn.opposite();
+        self.check_over();
+        Ok(())
+    }
+
+    /// Makes the given move, as long as it's the turn of its faction. Same as `mark` otherwise.
+    pub fn play(&mut self, played: Move) -> Result<(), MoveError> {
+        if self.game_over {
+            return Err(MoveError::GameOver);
+        }
+        if played.faction != self.turn {
+            return Err(MoveError::WrongTurn(played.faction));
+        }
+        self.mark(played.index)
+    }
+
+    /// Lets the given strategy decide on a cell for the faction whose turn it is, and marks it.
+    pub fn ai_turn(&mut self, strategy: AiStrategy) {
+        if self.game_over {
+            return;
+        }
+
+        let index = strategy.pick(&self.board, self.turn, &self.lines, &mut self.rng);
+        let index = self.legal_or_random(index, strategy, cfg!(debug_assertions));
+        self.mark(index)
+            .expect("only free cells are picked on unfinished boards");
+    }
+
+    // Returns the cell `picker` picked if it's free, otherwise a random free one. A strategy
+    // picking an used cell is a bug in the strategy, but not something the user should suffer
+    // from in release builds. With `strict`, it panics instead, so the bug gets noticed.
+    fn legal_or_random(&mut self, index: usize, picker: impl fmt::Display, strict: bool) -> usize {
+        if legal_moves(&self.board).any(|legal| legal == index) {
+            return index;
+        }
+
+        let message = format!(
+            "{} strategy picked cell {}, which isn't free on {:?}",
+            picker, index, self.board
+        );
+        if strict {
+            panic!("{}", message);
+        }
+        log::error!("{}, picking a random one instead", message);
+        AiStrategy::Random.pick(&self.board, self.turn, &self.lines, &mut self.rng)
+    }
+
+    /// Returns the cell the given strategy would answer with, if the faction whose turn it is
+    /// marked `index` now. `None` if that move isn't possible or ends the game.
+    ///
+    /// As long as the game isn't modified in between, this is exactly what `ai_turn` will pick
+    /// afterwards, even for random strategies.
+    pub fn predict_response(&self, index: usize, strategy: AiStrategy) -> Option<usize> {
+        if self.game_over || !self.board.get(index).is_some_and(|cell| cell.is_empty()) {
+            return None;
+        }
+
+        let mut board = self.board.clone();
+        board[index] = self.turn.into();
+        if is_finished(&board, &self.lines) {
+            return None;
+        }
+
+        // cloning the RNG lets the prediction see the same "random" numbers as the real turn
+        Some(strategy.pick(
+            &board,
+            self.turn.opposite(),
+            &self.lines,
+            &mut self.rng.clone(),
+        ))
+    }
+
+    /// Takes back the last `n` moves, or all of them if there are fewer, passing the turn back
+    /// accordingly. Returns how many moves were taken back.
+    pub fn undo_n(&mut self, n: usize) -> usize {
+        let n = n.min(self.moves.len());
+        for _ in 0..n {
+            let index = self
+                .moves
+                .pop()
+                .expect("n to be limited by the number of moves");
+            self.turn = self.board[index]
+                .faction()
+                .expect("cells in the move history to be marked");
+            self.board[index] = Cell::Empty;
+        }
+
+        self.check_over();
+        n
+    }
+
+    fn check_over(&mut self) {
+        self.game_over = is_finished(&self.board, &self.lines);
+    }
+
+    /// Clears the board down to the handicap and assigns the user a new faction as per the faction
+    /// choice.
+    pub fn reset(&mut self) {
+        let last = LastGame {
+            user_faction: self.user_faction,
+            loser: self.winner().map(Faction::opposite),
+        };
+
+        self.board.clone_from(&self.start);
+        self.turn = self.opener;
+        self.game_over = false;
+        self.moves.clear();
+        self.game_number += 1;
+        self.user_faction =
+            self.faction_choice
+                .pick(self.game_number, Some(last), self.opener, &mut self.rng);
+    }
+
+    /// Encodes the game compactly: One byte for the user's faction, then the cells marked so far
+    /// as nibbles, two per byte and padded with `0xf`, and finally one byte for the outcome. Even
+    /// a full game fits into 7 bytes.
+    ///
+    /// Boards with more cells than nibbles can tell apart from the padding use a whole byte per
+    /// move instead.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(2 + self.moves.len());
+
+        bytes.push(match self.user_faction {
+            Faction::Cross => 0,
+            Faction::Ring => 1,
+        });
+        // even the largest board has few enough cells for them to fit into a byte
+        if self.packs_nibbles() {
+            for pair in self.moves.chunks(2) {
+                let high = pair[0] as u8;
+                let low = pair.get(1).map_or(PADDING_NIBBLE, |&index| index as u8);
+                bytes.push(high << 4 | low);
+            }
+        } else {
+            bytes.extend(self.moves.iter().map(|&index| index as u8));
+        }
+        bytes.push(outcome_byte(&self.board, &self.lines));
+
+        bytes
+    }
+
+    /// Decodes a game encoded by `to_bytes`, on the classic board without any handicap.
+    ///
+    /// Returns an error if the bytes are truncated or don't encode a valid game.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let fresh = Self::new(
+            StdRng::seed_from_u64(0),
+            FactionChoice::Random,
+            Lines::default(),
+            Handicap::default(),
+        );
+        fresh.with_bytes(bytes)
+    }
+
+    /// Same as `from_bytes`, but with the lines and handicap of this game, and its RNG and faction
+    /// choice for later games. The moves are replayed on top of the handicap.
+    pub fn with_bytes(&self, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (&faction, rest) = bytes.split_first().ok_or(DecodeError::Truncated)?;
+        let (&outcome, packed) = rest.split_last().ok_or(DecodeError::Truncated)?;
+
+        let user_faction = match faction {
+            0 => Faction::Cross,
+            1 => Faction::Ring,
+            _ => return Err(DecodeError::InvalidFaction(faction)),
+        };
+        if outcome > 3 {
+            return Err(DecodeError::InvalidOutcome(outcome));
+        }
+
+        let mut cells: Vec<_> = if self.packs_nibbles() {
+            packed
+                .iter()
+                .flat_map(|byte| [byte >> 4, byte & 0xf])
+                .collect()
+        } else {
+            packed.to_vec()
+        };
+        if self.packs_nibbles() && cells.last() == Some(&PADDING_NIBBLE) {
+            cells.pop();
+        }
+
+        let mut replayed = Self {
+            board: self.start.clone(),
+            turn: self.opener,
+            game_over: false,
+            user_faction,
+            faction_choice: self.faction_choice,
+            game_number: self.game_number,
+            start: self.start.clone(),
+            opener: self.opener,
+            moves: Vec::with_capacity(cells.len()),
+            lines: self.lines.clone(),
+            rng: self.rng.clone(),
+        };
+        for cell in cells {
+            if usize::from(cell) >= self.board.len() {
+                return Err(DecodeError::InvalidCell(cell));
+            }
+            replayed
+                .mark(usize::from(cell))
+                .map_err(DecodeError::IllegalMove)?;
+        }
+        if outcome_byte(&replayed.board, &replayed.lines) != outcome {
+            return Err(DecodeError::OutcomeMismatch);
+        }
+
+        Ok(replayed)
+    }
+
+    // Whether all cell indices fit into a nibble without being mistaken for padding.
+    fn packs_nibbles(&self) -> bool {
+        self.board.len() <= usize::from(PADDING_NIBBLE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A game on the classic board, with the user playing cross.
+    fn new_game(seed: u64) -> Game {
+        Game::new(
+            StdRng::seed_from_u64(seed),
+            FactionChoice::Fixed(Faction::Cross),
+            Lines::default(),
+            Handicap::default(),
+        )
+    }
+
+    #[test]
+    fn new_games_start_empty() {
+        let game = new_game(0);
+        assert_eq!(game.board(), [Cell::Empty; 9]);
+        assert_eq!(game.turn(), game.opener());
+        assert_eq!(game.user_faction(), Faction::Cross);
+        assert!(game.moves().is_empty());
+        assert!(!game.is_over());
+        assert_eq!(game.outcome(), None);
+    }
+
+    #[test]
+    fn marking_passes_the_turn_on() {
+        let mut game = new_game(0);
+        let first = game.turn();
+        game.mark(4).unwrap();
+        assert_eq!(game.board()[4], Cell::from(first));
+        assert_eq!(game.turn(), first.opposite());
+
+        game.mark(0).unwrap();
+        assert_eq!(game.board()[0], Cell::from(first.opposite()));
+        assert_eq!(game.turn(), first);
+        assert_eq!(game.moves(), [4, 0]);
+    }
+
+    #[test]
+    fn ai_marks_one_free_cell() {
+        for strategy in [
+            AiStrategy::Random,
+            AiStrategy::Mixed,
+            AiStrategy::Minimax,
+            AiStrategy::Humanlike,
+        ] {
+            let mut game = new_game(0);
+            game.mark(4).unwrap();
+            let faction = game.turn();
+            game.ai_turn(strategy);
+
+            let &[_, index] = game.moves() else {
+                panic!("{} marked {:?}", strategy, game.moves());
+            };
+            assert_ne!(index, 4);
+            assert_eq!(game.board()[index], Cell::from(faction));
+            assert_eq!(game.turn(), faction.opposite());
+        }
+
+        // nothing left to do once the game is over
+        let mut game = Game::replay_from_moves(&[0, 3, 1, 4, 2], Faction::Ring).unwrap();
+        game.ai_turn(AiStrategy::Minimax);
+        assert_eq!(game.moves().len(), 5);
+    }
+
+    #[test]
+    fn reset_clears_the_board() {
+        let mut game = new_game(0);
+        for index in [0, 3, 1, 4, 2] {
+            game.mark(index).unwrap();
+        }
+        assert!(game.is_over());
+
+        game.reset();
+        assert_eq!(game.board(), [Cell::Empty; 9]);
+        assert_eq!(game.turn(), game.opener());
+        assert!(game.moves().is_empty());
+        assert!(!game.is_over());
+        // the choice is fixed, so the user keeps their faction
+        assert_eq!(game.user_faction(), Faction::Cross);
+        game.mark(0).unwrap();
+    }
+
+    #[test]
+    fn predicted_response_is_what_the_ai_plays() {
+        for strategy in [
+            AiStrategy::Random,
+            AiStrategy::Mixed,
+            AiStrategy::Minimax,
+            AiStrategy::Humanlike,
+        ] {
+            for seed in 0..10 {
+                let mut game = new_game(seed);
+                game.mark(0).unwrap();
+                let predicted = game.predict_response(4, strategy);
+
+                game.mark(4).unwrap();
+                game.ai_turn(strategy);
+                assert_eq!(predicted, game.moves().last().copied(), "{}", strategy);
+            }
+        }
+    }
+
+    #[test]
+    fn no_response_is_predicted_for_impossible_or_final_moves() {
+        // ring has 0 and 1 already, so 2 finishes the game
+        let game = Game::replay_from_moves(&[0, 3, 1, 4], Faction::Ring).unwrap();
+        assert_eq!(game.predict_response(0, AiStrategy::Minimax), None);
+        assert_eq!(game.predict_response(9, AiStrategy::Minimax), None);
+        assert_eq!(game.predict_response(2, AiStrategy::Minimax), None);
+    }
+
+    #[test]
+    fn impossible_moves_are_rejected() {
+        let mut game = new_game(0);
+        let ring = |index| Move {
+            index,
+            faction: Faction::Ring,
+        };
+        let cross = |index| Move {
+            index,
+            faction: Faction::Cross,
+        };
+
+        assert_eq!(
+            game.play(cross(4)),
+            Err(MoveError::WrongTurn(Faction::Cross))
+        );
+        assert_eq!(game.play(ring(9)), Err(MoveError::OutOfRange(9, 9)));
+        game.play(ring(4)).unwrap();
+        assert_eq!(game.play(cross(4)), Err(MoveError::Occupied(4)));
+        // none of the errors changed anything
+        assert_eq!(game.moves(), [4]);
+        assert_eq!(game.turn(), Faction::Cross);
+
+        for index in [0, 3, 1, 5] {
+            game.mark(index).unwrap();
+        }
+        assert_eq!(game.play(cross(6)), Err(MoveError::GameOver));
+        assert_eq!(game.play(ring(6)), Err(MoveError::GameOver));
+    }
+
+    // Stands in for a strategy with a bug, which always picks the center.
+    const BUGGY: &str = "Center-obsessed";
+
+    #[test]
+    fn illegal_pick_falls_back_to_free_cell() {
+        let mut game = new_game(0);
+        game.mark(4).unwrap();
+        for seed in 0..20 {
+            game.rng = StdRng::seed_from_u64(seed);
+            let index = game.legal_or_random(4, BUGGY, false);
+            assert!(game.board()[index].is_empty());
+        }
+        // legal picks are left alone
+        assert_eq!(game.legal_or_random(0, BUGGY, false), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Center-obsessed strategy picked cell 4")]
+    fn illegal_pick_panics_when_strict() {
+        let mut game = new_game(0);
+        game.mark(4).unwrap();
+        game.legal_or_random(4, BUGGY, true);
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        for moves in [&[][..], &[4], &[4, 0, 8], &[0, 3, 1, 4, 2]] {
+            let game = Game::replay_from_moves(moves, Faction::Ring).unwrap();
+            let bytes = game.to_bytes();
+            assert!(bytes.len() <= 7);
+
+            let decoded = Game::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded.moves(), moves);
+            assert_eq!(decoded.board(), game.board());
+            assert_eq!(decoded.user_faction(), game.user_faction());
+            assert_eq!(decoded.outcome(), game.outcome());
+        }
+    }
+
+    #[test]
+    fn malformed_bytes_are_rejected() {
+        assert_eq!(Game::from_bytes(&[]).unwrap_err(), DecodeError::Truncated);
+        assert_eq!(Game::from_bytes(&[1]).unwrap_err(), DecodeError::Truncated);
+        assert_eq!(
+            Game::from_bytes(&[2, 0]).unwrap_err(),
+            DecodeError::InvalidFaction(2)
+        );
+        assert_eq!(
+            Game::from_bytes(&[1, 4]).unwrap_err(),
+            DecodeError::InvalidOutcome(4)
+        );
+        assert_eq!(
+            Game::from_bytes(&[1, 0x9f, 0]).unwrap_err(),
+            DecodeError::InvalidCell(9)
+        );
+        assert_eq!(
+            Game::from_bytes(&[1, 0x44, 0]).unwrap_err(),
+            DecodeError::IllegalMove(MoveError::Occupied(4))
+        );
+        // a single move can't have ended the game
+        assert_eq!(
+            Game::from_bytes(&[1, 0x4f, 1]).unwrap_err(),
+            DecodeError::OutcomeMismatch
+        );
+    }
+
+    #[test]
+    fn winner_only_for_won_games() {
+        let replay = |moves: &[usize]| Game::replay_from_moves(moves, Faction::Ring).unwrap();
+
+        let running = replay(&[4, 0]);
+        assert_eq!((running.is_over(), running.winner()), (false, None));
+        // ring completes the middle column
+        let ring_won = replay(&[3, 0, 4, 1, 5]);
+        assert_eq!(
+            (ring_won.is_over(), ring_won.winner()),
+            (true, Some(Faction::Ring))
+        );
+        // cross completes the left column
+        let cross_won = replay(&[4, 0, 8, 1, 3, 2]);
+        assert_eq!(
+            (cross_won.is_over(), cross_won.winner()),
+            (true, Some(Faction::Cross))
+        );
+        let draw = replay(&[4, 0, 8, 2, 1, 7, 6, 3, 5]);
+        assert_eq!((draw.is_over(), draw.winner()), (true, None));
+        assert_eq!(draw.outcome(), Some(Outcome::Draw));
+    }
+
+    #[test]
+    fn outcome_tells_who_won() {
+        let replay = |moves: &[usize]| Game::replay_from_moves(moves, Faction::Ring).unwrap();
+
+        assert_eq!(replay(&[1, 0, 2, 4, 3]).outcome(), None);
+        // cross completes the diagonal from the bottom left to the top right
+        let diagonal = replay(&[1, 0, 2, 4, 3, 8]);
+        assert_eq!(diagonal.outcome(), Some(Outcome::Win(Faction::Cross)));
+        assert_eq!(diagonal.completed_line(), Some(&[0, 4, 8][..]));
+        // ring completes the right column
+        let column = replay(&[6, 0, 7, 4, 8]);
+        assert_eq!(column.outcome(), Some(Outcome::Win(Faction::Ring)));
+        assert_eq!(column.completed_line(), Some(&[6, 7, 8][..]));
+        // every cell is marked, but no line by a single faction
+        let draw = replay(&[4, 0, 8, 2, 1, 7, 6, 3, 5]);
+        assert!(!draw.board().iter().copied().any(Cell::is_empty));
+        assert_eq!(draw.outcome(), Some(Outcome::Draw));
+        assert_eq!(draw.completed_line(), None);
+    }
+
+    #[test]
+    fn undoing_a_mark_restores_the_board() {
+        let mut game = new_game(0);
+        game.mark(4).unwrap();
+        let before = game.board().to_vec();
+        let turn = game.turn();
+
+        game.mark(0).unwrap();
+        assert_eq!(game.undo_n(1), 1);
+        assert_eq!(game.board(), before);
+        assert_eq!(game.turn(), turn);
+        assert_eq!(game.moves(), [4]);
+
+        // nothing to take back at the start
+        assert_eq!(game.undo_n(1), 1);
+        assert_eq!(game.undo_n(1), 0);
+        assert_eq!(game.board(), [Cell::Empty; 9]);
+    }
+
+    #[test]
+    fn filled_board_without_a_line_is_a_draw() {
+        use Cell::{Cross as X, Ring as O};
+
+        // no row, column or diagonal has three of the same
+        let board = [X, O, X, X, O, O, O, X, X];
+        assert_eq!(outcome(&board, &Lines::default()), Some(Outcome::Draw));
+        assert_eq!(winner(&board, &Lines::default()), None);
+    }
+
+    #[test]
+    fn full_lines_win_on_larger_boards() {
+        let lines = Lines::full(4);
+        assert_eq!(lines.len(), 4 + 4 + 2);
+        assert_eq!(lines.size(), 4);
+
+        let mut board = [Cell::Empty; 16];
+        // three of four in the second column aren't enough anymore
+        for index in [4, 5, 6] {
+            board[index] = Cell::Cross;
+        }
+        assert_eq!(winner(&board, &lines), None);
+        board[7] = Cell::Cross;
+        assert_eq!(winner(&board, &lines), Some(Faction::Cross));
+        assert_eq!(completed_line(&board, &lines), Some(&[4, 5, 6, 7][..]));
+
+        // the diagonal from the top left to the bottom right
+        let mut board = [Cell::Empty; 16];
+        for index in [3, 6, 9, 12] {
+            board[index] = Cell::Ring;
+        }
+        assert_eq!(winner(&board, &lines), Some(Faction::Ring));
+        // the bottom row
+        let mut board = [Cell::Empty; 16];
+        for index in [0, 4, 8, 12] {
+            board[index] = Cell::Ring;
+        }
+        assert_eq!(winner(&board, &lines), Some(Faction::Ring));
+    }
+
+    #[test]
+    fn runs_of_four_win_on_a_five_by_five_board() {
+        let lines = Lines::in_a_row(5, 4);
+        // two runs fit along each column and row, four along each diagonal direction
+        assert_eq!(lines.len(), 10 + 10 + 4 + 4);
+        assert_eq!(Lines::in_a_row(3, 3), Lines::default());
+
+        let won_by = |cells: &[usize], cell| {
+            let mut board = [Cell::Empty; 25];
+            for &index in cells {
+                board[index] = cell;
+            }
+            winner(&board, &lines)
+        };
+        // three and then four of the left column
+        assert_eq!(won_by(&[1, 2, 3], Cell::Cross), None);
+        assert_eq!(won_by(&[1, 2, 3, 4], Cell::Cross), Some(Faction::Cross));
+        // four of the bottom row, but with a gap
+        assert_eq!(won_by(&[0, 5, 15, 20], Cell::Ring), None);
+        assert_eq!(won_by(&[5, 10, 15, 20], Cell::Ring), Some(Faction::Ring));
+        // a diagonal going up and one going down, neither through a corner
+        assert_eq!(won_by(&[5, 11, 17, 23], Cell::Ring), Some(Faction::Ring));
+        assert_eq!(won_by(&[9, 13, 17, 21], Cell::Cross), Some(Faction::Cross));
+    }
+
+    #[test]
+    fn history_lists_every_move_in_order() {
+        let mut game = new_game(5);
+        let opener = game.turn();
+        while !game.is_over() {
+            game.ai_turn(AiStrategy::Minimax);
+        }
+        // minimax against itself can only end in a draw, with every cell marked
+        assert_eq!(game.outcome(), Some(Outcome::Draw));
+
+        let history: Vec<Move> = game.history().collect();
+        assert_eq!(history.len(), 9);
+        let indices: Vec<usize> = history.iter().map(|played| played.index).collect();
+        assert_eq!(indices, game.moves());
+        for (i, played) in history.iter().enumerate() {
+            let faction = if i % 2 == 0 {
+                opener
+            } else {
+                opener.opposite()
+            };
+            assert_eq!(played.faction, faction);
+            assert_eq!(game.board()[played.index], faction.into());
+        }
+
+        // replaying the history ends up with the very same game
+        let replayed = Game::replay_from_moves(&indices, opener).unwrap();
+        assert_eq!(replayed.board(), game.board());
+
+        game.reset();
+        assert_eq!(game.history().count(), 0);
+    }
+
+    #[test]
+    fn custom_lines_decide_the_winner() {
+        // the four corners, and the middle column
+        let lines = Lines::parse("0, 2, 6, 8 # corners\n\n3 4 5\n", 3).unwrap();
+        let board = |marked: &[usize]| {
+            let mut board = [Cell::Empty; 9];
+            for &index in marked {
+                board[index] = Cell::Cross;
+            }
+            board
+        };
+
+        assert_eq!(winner(&board(&[0, 2, 6, 8]), &lines), Some(Faction::Cross));
+        assert_eq!(winner(&board(&[3, 4, 5]), &lines), Some(Faction::Cross));
+        // a usual line isn't one here
+        assert_eq!(winner(&board(&[0, 1, 2]), &lines), None);
+        assert_eq!(winner(&board(&[0, 2, 6]), &lines), None);
+        assert_eq!(
+            winner(&board(&[0, 2, 6, 8]), &lines.with_win_mode(WinMode::Misere)),
+            Some(Faction::Ring)
+        );
+
+        assert!(matches!(
+            Lines::parse("0 1 9", 3),
+            Err(LinesError::InvalidCell(cell, 1, 8)) if cell == "9"
+        ));
+        assert!(matches!(
+            Lines::parse("0 1 2\nthree", 3),
+            Err(LinesError::InvalidCell(cell, 2, 8)) if cell == "three"
+        ));
+        assert!(matches!(
+            Lines::parse("# nothing\n\n", 3),
+            Err(LinesError::Empty)
+        ));
+    }
+
+    #[test]
+    fn fingerprints_tell_games_apart() {
+        let replay = |moves: &[usize], first| Game::replay_from_moves(moves, first).unwrap();
+
+        let game = replay(&[4, 0, 8], Faction::Ring);
+        assert_eq!(
+            game.fingerprint(),
+            replay(&[4, 0, 8], Faction::Ring).fingerprint()
+        );
+        // has to stay the same across versions and platforms
+        assert_eq!(game.fingerprint(), 0x8a84_d677_33f8_4f20);
+
+        assert_ne!(
+            game.fingerprint(),
+            replay(&[8, 0, 4], Faction::Ring).fingerprint()
+        );
+        assert_ne!(
+            game.fingerprint(),
+            replay(&[4, 0], Faction::Ring).fingerprint()
+        );
+        assert_ne!(
+            game.fingerprint(),
+            replay(&[4, 0, 8], Faction::Cross).fingerprint()
+        );
+    }
+
+    #[test]
+    fn replays_follow_the_moves() {
+        let game = Game::replay_from_moves(&[4, 0, 8], Faction::Cross).unwrap();
+        assert_eq!(game.moves(), [4, 0, 8]);
+        assert_eq!(
+            (game.board()[4], game.board()[0]),
+            (Cell::Cross, Cell::Ring)
+        );
+        assert_eq!(game.turn(), Faction::Ring);
+
+        assert_eq!(
+            Game::replay_from_moves(&[4, 0, 4], Faction::Cross).unwrap_err(),
+            MoveError::Occupied(4)
+        );
+        assert_eq!(
+            Game::replay_from_moves(&[4, 9], Faction::Cross).unwrap_err(),
+            MoveError::OutOfRange(9, 9)
+        );
+        // ring completed the bottom row with the fifth move already
+        assert_eq!(
+            Game::replay_from_moves(&[0, 1, 3, 2, 6, 5], Faction::Ring).unwrap_err(),
+            MoveError::GameOver
+        );
+    }
+
+    #[test]
+    fn loser_opens_the_next_game() {
+        let choice = FactionChoice::LoserOpens { swap_on_draw: true };
+        let mut seen = Vec::new();
+        for seed in 0..8 {
+            let mut game = Game::new(
+                StdRng::seed_from_u64(seed),
+                choice,
+                Lines::default(),
+                Handicap::default(),
+            );
+            // the opener completes the left column
+            for index in [0, 3, 1, 4, 2] {
+                game.mark(index).unwrap();
+            }
+            let user_lost = game.winner() != Some(game.user_faction());
+            seen.push(user_lost);
+
+            game.reset();
+            assert_eq!(game.turn(), game.opener());
+            assert_eq!(game.user_faction() == game.opener(), user_lost);
+
+            // a draw makes the user swap
+            let before = game.user_faction();
+            for index in [0, 1, 2, 4, 3, 5, 7, 6, 8] {
+                game.mark(index).unwrap();
+            }
+            assert_eq!(game.outcome(), Some(Outcome::Draw));
+            game.reset();
+            assert_eq!(game.user_faction(), before.opposite());
+        }
+        assert!(seen.contains(&true) && seen.contains(&false));
+    }
+
+    #[test]
+    fn completing_a_line_loses_in_misere() {
+        let mut game = Game::new(
+            StdRng::seed_from_u64(0),
+            FactionChoice::Fixed(Faction::Cross),
+            Lines::default().with_win_mode(WinMode::Misere),
+            Handicap::default(),
+        );
+        let completer = game.turn();
+        for index in [0, 3, 1, 4, 2] {
+            game.mark(index).unwrap();
+        }
+        assert!(game.is_over());
+        assert_eq!(game.completed_line(), Some(&[0, 1, 2][..]));
+        assert_eq!(game.winner(), Some(completer.opposite()));
+        assert_eq!(game.outcome(), Some(Outcome::Win(completer.opposite())));
+    }
+}
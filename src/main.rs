@@ -1,286 +1,3117 @@
+mod ai;
+mod announce;
+mod button;
+mod chooser;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod daily;
+mod game;
+mod menu;
+mod move_list;
+mod record;
 mod render;
+mod replay;
+mod score;
+mod script;
+mod settings;
+mod simulate;
+#[cfg(feature = "sound")]
+mod sound;
+mod svg;
+mod theme;
 
 use {
-    rand::{distributions::Standard, prelude::*},
-    render::Backend,
+    ai::{AiStrategy, Difficulty},
+    button::{Buttons, PressOrRelease},
+    chooser::{Chooser, GameState, Opponent},
+    game::{Cell, Faction, Game, Move, MoveError, Outcome},
+    menu::{MenuOption, PauseMenu},
+    move_list::MoveListWindow,
+    rand::{rngs::StdRng, SeedableRng},
+    record::Recording,
+    render::{Backend, Jitter, Layout, LimitsMode, NullRenderer, Renderer, View},
+    replay::Replay,
+    score::{Score, Streak, Tally},
+    settings::Settings,
+    simulate::Simulation,
+    std::{
+        collections::HashSet,
+        fs,
+        time::{Duration, Instant},
+    },
+    theme::Theme,
     thiserror::Error,
     winit::{
         dpi,
-        event::{ElementState, Event, MouseButton, WindowEvent},
-        event_loop::{ControlFlow, EventLoop},
-        window::{Window, WindowBuilder},
+        event::{
+            ElementState, Event, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+            VirtualKeyCode, WindowEvent,
+        },
+        event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopProxy},
+        window::{self, Window, WindowBuilder, WindowId},
     },
 };
 
+/// How many pixels width and height may differ before the window is snapped back to a square.
+/// Some window managers round sizes a bit, fighting them over that would end in a resize loop.
+const SQUARE_SNAP_THRESHOLD: u32 = 2;
+
+/// How long after one tick for the cursor moving onto another cell the next one can sound at the
+/// earliest, so sweeping across the board doesn't rattle.
+const HOVER_TICK_INTERVAL: Duration = Duration::from_millis(80);
+
+/// How far the board can be zoomed out and in.
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 4.0;
+
+/// Where S saves the current game to and L loads it from, relative to the working directory.
+const SAVE_PATH: &str = "tic-tac-gpu.save";
+const SCREENSHOT_PATH: &str = "tic-tac-gpu.ppm";
+const SVG_PATH: &str = "tic-tac-gpu.svg";
+
 pub trait HandleEvent {
-    fn handle(&mut self, event: Event<()>, flow: &mut ControlFlow);
+    fn handle(&mut self, event: Event<AppEvent>, flow: &mut ControlFlow);
 }
 
-#[derive(Debug, Error)]
-enum AppError {
-    #[error("Unable to create window: {0}")]
-    WindowError(#[from] winit::error::OsError),
-    #[error("Could not create backend: {0}")]
-    BackendError(#[from] render::BackendError),
+/// Everything the app needs from the window it's shown in, so it can be driven without one, just
+/// like [`Renderer`] allows for drawing.
+pub trait AppWindow {
+    fn id(&self) -> WindowId;
+    fn inner_size(&self) -> dpi::PhysicalSize<u32>;
+    fn set_inner_size(&self, size: dpi::PhysicalSize<u32>);
+    fn scale_factor(&self) -> f64;
+    fn theme(&self) -> Option<window::Theme>;
+    fn set_title(&self, title: &str);
+    fn request_redraw(&self);
+    /// The actual window, for creating a backend drawing onto it. `None` if there's none.
+    fn winit(&self) -> Option<&Window>;
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum Cell {
-    Cross,
-    Ring,
-    Empty,
-}
+impl AppWindow for Window {
+    fn id(&self) -> WindowId {
+        self.id()
+    }
 
-impl Cell {
-    // Returns whether this cell is empty, false if it is used by any faction.
-    fn is_empty(self) -> bool {
-        matches!(self, Self::Empty)
+    fn inner_size(&self) -> dpi::PhysicalSize<u32> {
+        self.inner_size()
     }
-}
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Faction {
-    Cross,
-    Ring,
-}
+    fn set_inner_size(&self, size: dpi::PhysicalSize<u32>) {
+        self.set_inner_size(size);
+    }
 
-impl Faction {
-    // Determines whether this faction makes the first turn. Ring is the one for that.
-    fn goes_first(self) -> bool {
-        match self {
-            Self::Cross => false,
-            Self::Ring => true,
-        }
+    fn scale_factor(&self) -> f64 {
+        self.scale_factor()
     }
 
-    // Returns the opposite faction, e.g. cross for ring and ring for cross.
-    fn opposite(self) -> Self {
-        match self {
-            Self::Cross => Self::Ring,
-            Self::Ring => Self::Cross,
-        }
+    fn theme(&self) -> Option<window::Theme> {
+        self.theme()
     }
-}
 
-impl Distribution<Faction> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Faction {
-        // exact mapping doesn't matter
-        match rng.gen() {
-            false => Faction::Cross,
-            true => Faction::Ring,
-        }
+    fn set_title(&self, title: &str) {
+        self.set_title(title);
+    }
+
+    fn request_redraw(&self) {
+        self.request_redraw();
+    }
+
+    fn winit(&self) -> Option<&Window> {
+        Some(self)
     }
 }
 
-impl From<Faction> for Cell {
-    fn from(faction: Faction) -> Self {
-        match faction {
-            Faction::Cross => Cell::Cross,
-            Faction::Ring => Cell::Ring,
-        }
+/// Where the app sends events to itself, which are handled once the current one is done.
+pub trait Schedule {
+    fn schedule(&self, event: AppEvent);
+}
+
+impl Schedule for EventLoopProxy<AppEvent> {
+    fn schedule(&self, event: AppEvent) {
+        // can only fail if the event loop is gone already, then no one cares about the event
+        // anyways
+        let _ = self.send_event(event);
     }
 }
 
+/// Where the app plays its sounds, so it can be driven without hearing anything.
+pub trait Sound {
+    /// Plays a short and soft tick.
+    fn tick(&self);
+}
+
+/// Plays nothing at all, for when sound is muted or the game was built without it.
+pub struct Silence;
+
+impl Sound for Silence {
+    fn tick(&self) {}
+}
+
+/// Events not coming from the window system, but from the application itself.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AppEvent {
+    /// Places the user's mark on the cell with the given index, as if it had been clicked.
+    ApplyMove(usize),
+    /// Throws away the current game and starts a new one.
+    ResetGame,
+    /// The AI should make its move now, if it's its turn.
+    AiMoveDue,
+}
+
+#[derive(Debug, Error)]
+enum AppError {
+    #[error("Unable to create window: {0}")]
+    WindowError(#[from] winit::error::OsError),
+    #[error("Could not create backend: {0}")]
+    BackendError(#[from] render::BackendError),
+    #[error("Could not start recording: {0}")]
+    Recording(#[from] std::io::Error),
+}
+
 struct App {
     selected_field: (u8, u8),
-    board: [Cell; 9],
-    game_over: bool,
-    // we need only one side to hold which faction it belongs to, the AI will then just be the
-    // other one
-    user_faction: Faction,
+    game: Game,
+    opponent: Opponent,
+    // how hard the AI is to beat, if it's the opponent
+    difficulty: Difficulty,
+    // whether the AI plays humanlike instead of mixed on medium, as asked for by the settings
+    humanlike: bool,
+    chooser: Chooser,
+    // only shown while paused, see GameState::Paused
+    menu: PauseMenu,
+    // whether to preview the hovered move and the AI's likely response to it
+    show_ghosts: bool,
+    show_grid: bool,
+    // debug overlay, see Renderer::set_instance_dots_visible
+    show_instance_dots: bool,
+    // whether to color cells by how good they'd be for the user
+    show_evaluation: bool,
+    // whether to rate each move once the game is over
+    show_analysis: bool,
+    // follows whether the system prefers light or dark windows
+    theme: Theme,
+    // whether to show the hovered cell in the title
+    show_coordinates: bool,
+    // whether the selected cell is outlined while it could be marked
+    show_cursor: bool,
+    // only tracked if cells are shown as buttons
+    buttons: Option<Buttons>,
+    place_on: PressOrRelease,
+    // placed on press, but only final once the mouse button goes up on the same cell again
+    pending_move: Option<usize>,
+    // clicks are ignored for this long after the user placed a mark
+    debounce: Duration,
+    last_placement: Option<Instant>,
+    // only tracked in challenge mode
+    streak: Option<Streak>,
+    // only counted if it's shown
+    score: Option<Score>,
+    // how the games against the AI ended, shown in the title
+    tally: Tally,
+    // fingerprints of all games finished so far, to point out repeated ones
+    finished_games: HashSet<u64>,
+    // which modifiers are held right now, so shortcuts with them don't trigger the plain keys
+    modifiers: ModifiersState,
+    // last known cursor position, physical like everything else here
+    cursor: Option<dpi::PhysicalPosition<f64>>,
+    // needed to follow the cursor position through scale factor changes
+    scale_factor: f64,
+    view: View,
+    // where the cursor was last seen while panning with the right mouse button
+    panning_from: Option<dpi::PhysicalPosition<f64>>,
+    // whether to keep the window square on resizes
+    keep_square: bool,
+    // whether to print moves and results to stdout, for screen reader bridges
+    announce: bool,
+    // whether the grid may breathe while waiting for the user, and whether it currently does
+    idle_animation: bool,
+    idle: bool,
+    // whether the AI's marks are drawn stroke by stroke
+    strokes: bool,
+    // only rolling if marks are jittered, and the jitter of each marked cell
+    jitter_rng: Option<StdRng>,
+    jitter: Vec<Option<Jitter>>,
+    // how many turns can be taken back, and how many moves are out of reach for that already
+    undo_levels: Option<usize>,
+    undo_floor: usize,
+    // how long the AI waits before answering, and since when it's been waiting
+    thinking_delay: Duration,
+    thinking_since: Option<Instant>,
+    // whether clicks while the AI thinks are remembered, and the last one if so
+    queue_clicks: bool,
+    queued_move: Option<usize>,
+    // used for scheduling events for ourselves, such as the AI's response
+    proxy: Box<dyn Schedule>,
+    // ticks when the cursor moves onto another cell, at most once per interval
+    sound: Box<dyn Sound>,
+    tick_interval: Duration,
+    last_tick: Option<Instant>,
+    // needed again whenever the backend is recreated
+    transparent: bool,
+    adapter: Option<usize>,
+    limits: LimitsMode,
+    // only open if asked for, and until it's closed
+    move_list: Option<MoveListWindow>,
+    // only going on if asked for, and until a frame can't be saved
+    recording: Option<Recording>,
 
-    backend: Backend,
+    backend: Box<dyn Renderer>,
     // DO NOT REORDER THIS -- Safety of Backend::new depends on it
-    window: Window,
+    window: Box<dyn AppWindow>,
 }
 
 impl App {
-    async fn new(event_loop: &EventLoop<()>) -> Result<Self, AppError> {
+    async fn new(event_loop: &EventLoop<AppEvent>, settings: Settings) -> Result<Self, AppError> {
         let window = WindowBuilder::new()
             .with_title("Tic Tac GPU")
-            .with_resizable(false)
             .with_inner_size(dpi::LogicalSize::new(400, 400))
+            .with_transparent(settings.transparent)
             .build(event_loop)?;
+        // might still change once the window is shown on its monitor, see ScaleFactorChanged
+        log::info!(
+            "Created window at scale factor {}, {}x{} physical pixels",
+            window.scale_factor(),
+            window.inner_size().width,
+            window.inner_size().height
+        );
         // SAFETY: window is in the same struct as the backend and the window gets dropped after
         // the backend
-        let backend = unsafe { Backend::new(&window) }.await?;
+        let board_size = settings.lines.size();
+        let backend = unsafe {
+            Backend::new(
+                &window,
+                settings.transparent,
+                settings.adapter,
+                settings.limits,
+                Layout::Board(board_size),
+            )
+        }
+        .await?;
+        let move_list = if settings.move_list {
+            Some(
+                MoveListWindow::new(
+                    event_loop,
+                    board_size,
+                    settings.transparent,
+                    settings.adapter,
+                    settings.limits,
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+        let recording = match &settings.record {
+            Some(dir) => {
+                log::info!(
+                    "Recording frames into {} at {} fps",
+                    dir.display(),
+                    settings.record_fps
+                );
+                Some(Recording::new(dir, settings.record_fps)?)
+            }
+            None => None,
+        };
+
+        #[cfg(feature = "sound")]
+        let sound: Box<dyn Sound> = if settings.mute {
+            Box::new(Silence)
+        } else {
+            Box::<sound::Speaker>::default()
+        };
+        #[cfg(not(feature = "sound"))]
+        let sound = Box::new(Silence);
+
+        Ok(Self::with_parts(
+            settings,
+            Box::new(window),
+            Box::new(backend),
+            Box::new(event_loop.create_proxy()),
+            sound,
+            move_list,
+            recording,
+        ))
+    }
 
-        let user_faction = thread_rng().gen();
+    // Puts the app together from the given parts, which don't have to be real ones, so it can
+    // also be driven without a window or GPU.
+    fn with_parts(
+        settings: Settings,
+        window: Box<dyn AppWindow>,
+        backend: Box<dyn Renderer>,
+        proxy: Box<dyn Schedule>,
+        sound: Box<dyn Sound>,
+        move_list: Option<MoveListWindow>,
+        recording: Option<Recording>,
+    ) -> Self {
+        let board_size = settings.lines.size();
+        let rng = match settings.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
 
         let mut app = Self {
-            selected_field: (1, 1),
-            board: [Cell::Empty; 9],
-            game_over: false,
-            user_faction,
+            // the center, or the cell right above and left of it on even sizes
+            selected_field: ((board_size / 2) as u8, (board_size / 2) as u8),
+            game: Game::new(rng, settings.faction, settings.lines, settings.handicap),
+            // the challenge is about surviving against the unbeatable AI
+            opponent: Opponent::Ai,
+            difficulty: if settings.challenge {
+                Difficulty::Hard
+            } else {
+                settings.strategy.into()
+            },
+            humanlike: settings.strategy == AiStrategy::Humanlike,
+            // picking a different opponent would defeat the point of the challenge
+            chooser: Chooser::new(settings.chooser, !settings.challenge),
+            menu: PauseMenu::default(),
+            show_ghosts: false,
+            show_grid: true,
+            show_instance_dots: false,
+            show_evaluation: false,
+            show_analysis: false,
+            theme: Theme::DARK,
+            show_coordinates: false,
+            show_cursor: settings.cursor,
+            buttons: settings.buttons.then(Buttons::default),
+            place_on: settings.place_on,
+            pending_move: None,
+            debounce: settings.debounce,
+            last_placement: None,
+            streak: settings.challenge.then(Streak::default),
+            score: settings.score.then(Score::default),
+            tally: Tally::default(),
+            finished_games: HashSet::new(),
+            modifiers: ModifiersState::empty(),
+            cursor: None,
+            scale_factor: window.scale_factor(),
+            view: View::default(),
+            panning_from: None,
+            keep_square: settings.square,
+            announce: settings.announce,
+            idle_animation: settings.idle_animation,
+            idle: false,
+            strokes: settings.strokes,
+            jitter_rng: settings.jitter.then(|| match settings.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            }),
+            jitter: vec![None; board_size * board_size],
+            undo_levels: settings.undo_levels,
+            undo_floor: 0,
+            thinking_delay: settings.thinking_delay,
+            thinking_since: None,
+            queue_clicks: settings.queue_clicks,
+            queued_move: None,
+            proxy,
+            sound,
+            tick_interval: HOVER_TICK_INTERVAL,
+            last_tick: None,
+            transparent: settings.transparent,
+            adapter: settings.adapter,
+            limits: settings.limits,
+            move_list,
+            recording,
             backend,
             window,
         };
 
-        if !user_faction.goes_first() {
-            app.ai_turn();
-        }
+        app.set_theme(Theme::for_system(app.window.theme()));
+        app.update_handicap();
+        app.update_buttons();
+        app.begin_game();
 
-        Ok(app)
+        app
     }
 
-    fn mark_field(&mut self, index: usize, with: Cell) {
-        self.board[index] = with;
-        // Don't forget to tell the backend! It has to update it's internal structure then
-        self.backend.update_instances(&self.board);
+    // Either shows the chooser or starts the game right away, depending on the chooser mode.
+    fn begin_game(&mut self) {
+        if self.chooser.begin() {
+            self.update_title();
+            self.window.request_redraw();
+        } else {
+            self.start_game();
+        }
     }
 
-    fn ai_turn(&mut self) {
-        let selected_field = loop {
-            let attempt = thread_rng().gen_range(0..9);
-            // check if the field is empty at all
-            if self.board[attempt].is_empty() {
-                break attempt;
+    // Recreates the backend on the next adapter, wrapping around after the last one.
+    fn switch_adapter(&mut self) {
+        let Some(window) = self.window.winit() else {
+            log::info!("There's no window to draw onto with another adapter");
+            return;
+        };
+        let adapters = Backend::list_adapters();
+        if adapters.len() <= 1 {
+            log::info!("There's no other adapter to switch to");
+            return;
+        }
+        // wgpu might have picked any of them if none was asked for
+        let current = self.adapter.or_else(|| {
+            let info = self.backend.adapter()?;
+            adapters.iter().position(|other| *other == info)
+        });
+        let index = current.map_or(0, |index| index + 1) % adapters.len();
+
+        // The old backend has to be gone before the new one creates its surface.
+        self.backend = Box::new(NullRenderer);
+        match self.create_backend(window, Some(index)) {
+            Ok(backend) => {
+                self.backend = Box::new(backend);
+                self.adapter = Some(index);
+                log::info!("Switched to adapter {}", index);
+            }
+            Err(e) => {
+                log::error!("Could not switch to adapter {}: {}", index, e);
+                match self.create_backend(window, self.adapter) {
+                    Ok(backend) => self.backend = Box::new(backend),
+                    Err(e) => {
+                        log::error!("Could not go back to the previous adapter either: {}", e);
+                        std::process::exit(1);
+                    }
+                }
             }
+        }
+        self.sync_backend();
+    }
+
+    fn create_backend(
+        &self,
+        window: &Window,
+        adapter: Option<usize>,
+    ) -> Result<Backend, render::BackendError> {
+        // SAFETY: the window is self.window, and the backend is only ever stored in self.backend,
+        // next to it, just like in App::new
+        let backend = unsafe {
+            Backend::new(
+                window,
+                self.transparent,
+                adapter,
+                self.limits,
+                Layout::Board(self.board_size()),
+            )
         };
-        self.mark_field(selected_field, self.user_faction.opposite().into());
+        pollster::block_on(backend)
     }
 
-    fn check_game_over(&mut self) {
-        let mut game_over = false;
-
-        // check first if there is any empty field left, else the game is over anyways
-        if self.board.iter().copied().any(Cell::is_empty) {
-            for i in 0..3 {
-                if (
-                    // horizontal
-                    !self.board[3 * i].is_empty()
-                        && self.board[3 * i] == self.board[3 * i + 1]
-                        && self.board[3 * i] == self.board[3 * i + 2]
-                ) || (
-                    // vertical
-                    !self.board[i].is_empty()
-                        && self.board[i] == self.board[i + 3]
-                        && self.board[i] == self.board[i + 6]
-                ) {
-                    game_over = true;
-                }
+    // Shows the current board, and the moves leading to it in the move list if it's open.
+    fn update_board(&mut self) {
+        if let Some(levels) = self.undo_levels {
+            // like in any editor, the oldest moves drop out of reach as new ones come in
+            let moves_per_turn = match self.opponent {
+                Opponent::Ai => 2,
+                Opponent::Human => 1,
+            };
+            let in_reach = levels.saturating_mul(moves_per_turn);
+            self.undo_floor = self
+                .undo_floor
+                .max(self.game.moves().len().saturating_sub(in_reach));
+        }
+
+        self.backend.update_instances(self.game.board());
+        self.update_jitter();
+        self.update_cursor();
+        self.backend
+            .update_win_line(self.game.completed_line().unwrap_or_default());
+        if let Some(move_list) = &mut self.move_list {
+            move_list.update(self.game.board(), self.game.moves());
+        }
+    }
+
+    // Rolls a jitter for each new mark, forgets the ones of cleared cells and shows the rest. The
+    // jitter of a mark stays the same as long as it's on the board.
+    fn update_jitter(&mut self) {
+        let Some(rng) = &mut self.jitter_rng else {
+            return;
+        };
+        for (jitter, cell) in self.jitter.iter_mut().zip(self.game.board()) {
+            if *cell == Cell::Empty {
+                *jitter = None;
+            } else {
+                jitter.get_or_insert_with(|| Jitter::random(rng));
             }
+        }
+        let jitter: Vec<_> = self
+            .jitter
+            .iter()
+            .map(|jitter| jitter.unwrap_or_default())
+            .collect();
+        self.backend.jitter_marks(&jitter);
+    }
 
-            // crossed
-            if (!self.board[0].is_empty()
-                && self.board[0] == self.board[4]
-                && self.board[0] == self.board[8])
-                || (!self.board[2].is_empty()
-                    && self.board[2] == self.board[4]
-                    && self.board[2] == self.board[6])
-            {
-                game_over = true;
+    // Tells a freshly created backend everything the old one knew.
+    fn sync_backend(&mut self) {
+        self.backend.update_instances(self.game.board());
+        self.update_jitter();
+        self.update_cursor();
+        self.backend
+            .update_win_line(self.game.completed_line().unwrap_or_default());
+        self.backend.set_grid_visible(self.show_grid);
+        self.backend
+            .set_instance_dots_visible(self.show_instance_dots);
+        self.backend.set_view(self.view);
+        self.backend.set_theme(&self.theme);
+        self.backend.set_idle(self.idle);
+        self.backend.update_score(self.score);
+        self.update_handicap();
+        self.update_buttons();
+        self.update_background();
+        self.update_highlight();
+        self.update_analysis();
+        self.update_ghosts();
+        self.update_evaluation();
+        self.window.request_redraw();
+    }
+
+    // Lets the AI open if it goes first, and makes sure whatever is on the board ends up on the
+    // next frame.
+    fn start_game(&mut self) {
+        // the remembered choices win over whatever the game decided on by itself
+        if let Some(faction) = self.chooser.faction() {
+            self.game.choose_user_faction(faction);
+        }
+        if let Some(opponent) = self.chooser.opponent() {
+            self.opponent = opponent;
+        }
+        if let Some(difficulty) = self.chooser.difficulty() {
+            self.difficulty = difficulty;
+        }
+        if self.human_to_move().is_none() {
+            self.ai_turn();
+            // a handicap might leave the AI a line to complete right away
+            self.check_game_over();
+        }
+        // only now it's clear whose turn it is
+        self.update_title();
+        self.update_ghosts();
+        self.update_evaluation();
+
+        // The very first frame would be drawn anyways as the window appears, but after a reset
+        // nothing else would trigger one.
+        self.window.request_redraw();
+    }
+
+    // How many cells there are on each side of the board.
+    fn board_size(&self) -> usize {
+        self.game.lines().size()
+    }
+
+    // basically 2d to 1d index conversion, with the width of one line being the board size
+    fn selected_index(&self) -> usize {
+        self.index_of(self.selected_field)
+    }
+
+    fn index_of(&self, (x, y): (u8, u8)) -> usize {
+        usize::from(x) * self.board_size() + usize::from(y)
+    }
+
+    fn set_view(&mut self, view: View) {
+        self.view = view;
+        self.backend.set_view(view);
+        // the cursor stays, but the board moves below it
+        self.update_selection(self.window.inner_size());
+        self.window.request_redraw();
+    }
+
+    // Moves the board along with the cursor while the right mouse button is held.
+    fn pan(&mut self, to: dpi::PhysicalPosition<f64>) {
+        if let Some(from) = self.panning_from.replace(to) {
+            let (_, side) = render::letterbox(self.window.inner_size());
+            let mut view = self.view;
+            // pixels to clip space, which is 2 wide and has y+ up
+            view.offset[0] += (to.x - from.x) as f32 * 2.0 / side;
+            view.offset[1] -= (to.y - from.y) as f32 * 2.0 / side;
+            self.set_view(view);
+        }
+    }
+
+    // Zooms by the given number of scroll wheel steps, positive for zooming in.
+    fn zoom(&mut self, steps: f32) {
+        let mut view = self.view;
+        view.scale = (view.scale * 1.1_f32.powf(steps)).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.set_view(view);
+    }
+
+    // The faction a human is about to mark a cell for, `None` while the AI is on the move or no
+    // game is running.
+    fn human_to_move(&self) -> Option<Faction> {
+        let turn = self.game.turn();
+        let humans_turn = self.opponent == Opponent::Human || turn == self.game.user_faction();
+
+        (self.chooser.state() == GameState::Playing && !self.game.is_over() && humans_turn)
+            .then_some(turn)
+    }
+
+    // Selects the cell below the last known cursor position, for the given window size. Has to be
+    // called whenever either of them changes.
+    fn update_selection(&mut self, window_size: dpi::PhysicalSize<u32>) {
+        let hovered = self
+            .cursor
+            .and_then(|position| cell_at(position, window_size, self.view, self.board_size()));
+        let hovered_index = hovered.map(|cell| self.index_of(cell));
+        if let Some(buttons) = &mut self.buttons {
+            if buttons.hover(hovered_index) {
+                self.update_buttons();
             }
-        } else {
-            game_over = true;
         }
 
-        if game_over {
-            self.game_over = true;
-            self.backend.set_background(wgpu::Color {
-                r: 0.3,
-                g: 0.35,
-                b: 0.35,
-                a: 1.0,
-            });
+        if let Some(cell) = hovered {
+            // only actually moving onto another cell ticks, not every pixel the cursor moves
+            if cell != self.selected_field {
+                self.hover_tick();
+            }
+            self.select(cell);
         }
     }
 
-    fn reset(&mut self) {
-        // TODO eventually the app should be more self-contained and all the game stuff into it's
-        // own struct which is resettable by ::new()ing and the app more like a manager, but it is
-        // what it is
-        self.board = [Cell::Empty; 9];
-        self.backend.update_instances(&self.board);
-        self.game_over = false;
-        self.backend.set_background(wgpu::Color {
-            r: 0.04,
-            g: 0.09,
-            b: 0.09,
-            a: 1.0,
-        });
+    // Ticks for the cursor having moved onto another cell, unless the last tick was just now.
+    fn hover_tick(&mut self) {
+        if self
+            .last_tick
+            .is_some_and(|ticked| ticked.elapsed() < self.tick_interval)
+        {
+            return;
+        }
+        self.last_tick = Some(Instant::now());
+        self.sound.tick();
+    }
 
-        self.user_faction = thread_rng().gen();
-        if !self.user_faction.goes_first() {
-            self.ai_turn();
+    // Moves the selection one cell in the given direction as seen on the screen, stopping at the
+    // edges of the board.
+    fn move_selection(&mut self, direction: (i8, i8)) {
+        let (mut dx, mut dy) = direction;
+        // undoes the turns of the view, so the selection goes where the arrow points
+        for _ in 0..self.view.quarter_turns {
+            (dx, dy) = (dy, -dx);
         }
+
+        let (x, y) = self.selected_field;
+        let last = (self.board_size() - 1) as u8;
+        self.select((
+            x.saturating_add_signed(dx).min(last),
+            y.saturating_add_signed(dy).min(last),
+        ));
     }
-}
 
-impl HandleEvent for App {
-    fn handle(&mut self, event: Event<()>, flow: &mut ControlFlow) {
-        if let Event::WindowEvent { ref event, .. } = event {
-             match event {
-                WindowEvent::CursorMoved { position, .. } => {
-                    let window_size = self.window.inner_size();
-
-                    // simple bounds checking, sometimes on X I've seen some mouse event coming
-                    // from out of the actual window size
-                    if !(position.x < 0.0
-                        || position.x >= f64::from(window_size.width)
-                        || position.y < 0.0
-                        || position.y >= f64::from(window_size.width))
-                    {
-                        // even though it's name might not make that clear, these components now range
-                        // from 0 to 3
-                        let grid_pos = (
-                            (position.x * 3.0 / f64::from(window_size.width)) as u8,
-                            (position.y * 3.0 / f64::from(window_size.height)) as u8,
-                        );
-                        // winit thinks in y+ down, but wgpu by default y+ up, so invert
-                        // (this causes our grid to be thought in the wgpu dimension)
-                        let inverted = (grid_pos.0, 2 - grid_pos.1);
-
-                        self.selected_field = inverted;
+    // Selects the given cell, updating whatever follows the selection. Both the cursor and the
+    // arrow keys end up here, so they always agree on the selected cell.
+    fn select(&mut self, cell: (u8, u8)) {
+        if cell != self.selected_field {
+            self.selected_field = cell;
+            self.update_cursor();
+            if self.show_ghosts {
+                self.update_ghosts();
+                self.window.request_redraw();
+            }
+            if self.show_coordinates {
+                self.update_title();
+            }
+        }
+    }
+
+    // Outlines the selected cell if it could be marked right now, so it's clear where a click
+    // or enter would place the mark.
+    fn update_cursor(&mut self) {
+        if !self.show_cursor {
+            return;
+        }
+        let index = self.selected_index();
+        let markable = self.human_to_move().is_some() && self.game.board()[index].is_empty();
+        self.backend.set_cursor(markable.then_some(index));
+        self.window.request_redraw();
+    }
+
+    fn update_handicap(&mut self) {
+        let handicap: Vec<_> = self
+            .game
+            .start()
+            .iter()
+            .map(|cell| !cell.is_empty())
+            .collect();
+        self.backend.dim_marks(&handicap);
+    }
+
+    fn update_buttons(&mut self) {
+        if let Some(buttons) = &self.buttons {
+            let cells = self.game.board().len();
+            self.backend.update_buttons(&buttons.states(cells));
+            self.window.request_redraw();
+        }
+    }
+
+    fn update_ghosts(&mut self) {
+        let mut ghosts = vec![Cell::Empty; self.game.board().len()];
+        let index = self.selected_index();
+
+        match self.human_to_move() {
+            Some(faction) if self.show_ghosts && self.game.board()[index].is_empty() => {
+                ghosts[index] = faction.into();
+                if self.opponent == Opponent::Ai {
+                    if let Some(response) = self.game.predict_response(index, self.ai_strategy()) {
+                        ghosts[response] = faction.opposite().into();
                     }
                 }
-                WindowEvent::MouseInput {
-                    button: MouseButton::Left,
-                    state: ElementState::Released,
-                    ..
-                } => {
-                    if self.game_over {
-                        self.reset();
-                        self.window.request_redraw();
-                    } else {
-                        // basically 2d to 1d index conversion, but we know already the width of one
-                        // line is 3
-                        let field_index = self.selected_field.0 * 3 + self.selected_field.1;
-
-                        // check first if the cell is free at all, we shouldn't overwrite an used one
-                        if self.board[usize::from(field_index)].is_empty() {
-                            self.mark_field(usize::from(field_index), self.user_faction.into());
-                            self.check_game_over();
-
-                            if !self.game_over {
-                                self.ai_turn();
-                                self.check_game_over();
-                            }
+            }
+            _ => (),
+        }
 
-                            // Not triggering would cause the backend not to know when it should redraw,
-                            // and so it would be drawn on the next required redraw, such as the window
-                            // being visible again or switching workspaces.
-                            self.window.request_redraw();
-                        }
+        self.backend.update_ghosts(&ghosts);
+    }
+
+    fn update_evaluation(&mut self) {
+        let scores = match self.human_to_move() {
+            Some(faction) if self.show_evaluation => {
+                ai::evaluate_moves(self.game.board(), faction, self.game.lines())
+            }
+            _ => vec![None; self.game.board().len()],
+        };
+
+        self.backend.update_evaluation(&scores);
+    }
+
+    // Rates every move of a finished game on the cell it marked, and lists the ratings in the log.
+    fn update_analysis(&mut self) {
+        let mut qualities = vec![None; self.game.board().len()];
+
+        if self.show_analysis && self.game.is_over() {
+            let analysis = ai::analyze(&self.game);
+            for (number, (played, quality)) in self.game.history().zip(analysis).enumerate() {
+                log::info!(
+                    "Move {}: {:?} on cell {}, {}",
+                    number + 1,
+                    played.faction,
+                    played.index,
+                    quality
+                );
+                qualities[played.index] = Some(quality);
+            }
+        }
+
+        self.backend.update_analysis(&qualities);
+    }
+
+    fn mark_field(&mut self, faction: Faction, index: usize) -> Result<(), MoveError> {
+        self.game.play(Move { index, faction })?;
+        // Don't forget to tell the backend! It has to update it's internal structure then
+        self.update_board();
+        Ok(())
+    }
+
+    // How the AI plays at the current difficulty.
+    fn ai_strategy(&self) -> AiStrategy {
+        match self.difficulty {
+            Difficulty::Easy => AiStrategy::Random,
+            Difficulty::Medium if self.humanlike => AiStrategy::Humanlike,
+            Difficulty::Medium => AiStrategy::Mixed,
+            Difficulty::Hard => AiStrategy::Minimax,
+        }
+    }
+
+    fn ai_turn(&mut self) {
+        if self.opponent == Opponent::Ai {
+            let moves_before = self.game.moves().len();
+            self.game.ai_turn(self.ai_strategy());
+            self.update_board();
+
+            if self.game.moves().len() > moves_before {
+                self.announce_last_move();
+                if self.strokes {
+                    if let Some(&cell) = self.game.moves().last() {
+                        self.backend.reveal_mark(cell);
                     }
                 }
-                _ => (),
-             }
+            }
         }
-        // Just forward, maybe it wants to do something with it as well (such as... re-rendering if
-        // needed)
-        self.backend.handle(event, flow);
     }
-}
 
-fn main() -> Result<(), flexi_logger::FlexiLoggerError> {
-    flexi_logger::Logger::try_with_env()?.start()?;
+    // The faction moves and results are announced for, if there's only one human playing.
+    fn announced_user(&self) -> Option<Faction> {
+        (self.opponent == Opponent::Ai).then(|| self.game.user_faction())
+    }
 
-    let event_loop = EventLoop::new();
+    fn announce_last_move(&self) {
+        if let Some(last) = self.game.history().last() {
+            self.announce(announce::move_announcement(
+                last.index,
+                self.board_size(),
+                last.faction,
+                self.announced_user(),
+            ));
+        }
+    }
 
-    let mut app = pollster::block_on(App::new(&event_loop)).unwrap_or_else(|e| {
-        log::error!("{}", e);
-        std::process::exit(1)
-    });
-    event_loop.run(move |event, _, flow| app.handle(event, flow));
+    // Prints the given sentence on its own line, if announcing is enabled at all.
+    fn announce(&self, sentence: String) {
+        if self.announce {
+            println!("{}", sentence);
+        }
+    }
+
+    // Places the mark of the human on the move on the given cell and schedules the AI's response,
+    // if there's an AI. Does nothing if it's no human's turn.
+    fn user_turn(&mut self, index: usize) -> Result<(), MoveError> {
+        if self.place_user_mark(index)? {
+            self.finish_user_turn();
+        }
+        Ok(())
+    }
+
+    // Only places the mark of the human on the move, nothing follows from it until
+    // finish_user_turn. Returns whether it was a human's turn at all.
+    fn place_user_mark(&mut self, index: usize) -> Result<bool, MoveError> {
+        let Some(faction) = self.human_to_move() else {
+            // the AI is only pretending to think, the click is probably meant for right after
+            if self.queue_clicks
+                && self.thinking_since.is_some()
+                && self.chooser.state() == GameState::Playing
+            {
+                log::debug!("Queueing move on cell {} until the AI has moved", index);
+                self.queued_move = Some(index);
+            }
+            return Ok(false);
+        };
+
+        self.mark_field(faction, index)?;
+        self.last_placement = Some(Instant::now());
+        self.update_ghosts();
+        self.update_evaluation();
+        self.window.request_redraw();
+        Ok(true)
+    }
+
+    // Lets everything follow from the mark the human just placed.
+    fn finish_user_turn(&mut self) {
+        self.announce_last_move();
+        self.check_game_over();
+
+        if self.human_to_move().is_none() && !self.game.is_over() {
+            if self.thinking_delay.is_zero() {
+                // can only fail if the event loop is gone already, then no one cares about the AI
+                // anyways
+                self.proxy.schedule(AppEvent::AiMoveDue);
+            } else {
+                self.thinking_since = Some(Instant::now());
+            }
+        }
+        self.update_ghosts();
+        self.update_evaluation();
+        self.update_title();
+
+        // Not triggering would cause the backend not to know when it should redraw, and so it
+        // would be drawn on the next required redraw, such as the window being visible again or
+        // switching workspaces.
+        self.window.request_redraw();
+    }
+
+    // Finishes or takes back the move placed on press, depending on whether the cursor is still
+    // on its cell.
+    fn release_pending_move(&mut self, index: usize) {
+        if self.hovered_index() == Some(index) {
+            self.finish_user_turn();
+            return;
+        }
+
+        log::info!(
+            "Cursor moved off cell {} before release, taking the move back",
+            index
+        );
+        self.game.undo_n(1);
+        self.update_board();
+        self.update_ghosts();
+        self.update_evaluation();
+        self.window.request_redraw();
+    }
+
+    // Whether a click now would most likely be the second half of a double click on a mark which
+    // was just placed.
+    fn debouncing(&self) -> bool {
+        self.last_placement
+            .is_some_and(|placed| placed.elapsed() < self.debounce)
+    }
+
+    // The index of the cell below the cursor, if there's one at all.
+    fn hovered_index(&self) -> Option<usize> {
+        cell_at(
+            self.cursor?,
+            self.window.inner_size(),
+            self.view,
+            self.board_size(),
+        )
+        .map(|cell| self.index_of(cell))
+    }
+
+    // Lets the AI make its move, if it's its turn at all.
+    fn ai_response(&mut self) {
+        if self.game.is_over()
+            || self.chooser.state() != GameState::Playing
+            || self.human_to_move().is_some()
+        {
+            return;
+        }
+
+        self.ai_turn();
+        self.check_game_over();
+        self.update_ghosts();
+        self.update_evaluation();
+        self.update_title();
+        self.window.request_redraw();
+
+        if let Some(index) = self.queued_move.take() {
+            if let Err(e) = self.user_turn(index) {
+                log::info!("Dropping queued move on cell {}: {}", index, e);
+            }
+        }
+    }
+
+    // Fills in the thinking dots as the delay elapses, and lets the AI move once it's over.
+    fn update_thinking(&mut self) {
+        // the AI can't think while paused, it'll start over once resumed
+        if self.chooser.state() == GameState::Paused {
+            return;
+        }
+
+        if let Some(since) = self.thinking_since {
+            let progress = since.elapsed().as_secs_f32() / self.thinking_delay.as_secs_f32();
+
+            if progress >= 1.0 {
+                self.stop_thinking();
+                self.ai_response();
+            } else {
+                self.backend.update_thinking(Some(progress));
+                self.window.request_redraw();
+            }
+        }
+    }
+
+    // Lets the grid breathe exactly while a human is to move, if wanted at all.
+    fn update_idle(&mut self) {
+        let idle = self.idle_animation && self.human_to_move().is_some();
+        if idle != self.idle {
+            self.idle = idle;
+            self.backend.set_idle(idle);
+            self.window.request_redraw();
+        }
+    }
+
+    // Captures the next frame of the recording, if one is going on, with animations advanced by
+    // exactly one step since the last one. Stops recording if the frame can't be saved.
+    fn record_frame(&mut self) {
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+        self.backend.advance_clock(recording.step());
+        let result = match self.backend.capture() {
+            Ok(frame) => recording.save(&frame).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = result {
+            log::error!("Stopping the recording, could not save a frame: {}", e);
+            self.recording = None;
+        }
+    }
+
+    // Whether anything is moving on its own right now, be it in one of the windows or the AI's
+    // thinking delay.
+    fn is_animating(&self) -> bool {
+        self.thinking_since.is_some()
+            || self.backend.is_animating()
+            || self
+                .move_list
+                .as_ref()
+                .is_some_and(MoveListWindow::is_animating)
+    }
+
+    fn stop_thinking(&mut self) {
+        self.thinking_since = None;
+        self.backend.update_thinking(None);
+    }
+
+    fn check_game_over(&mut self) {
+        if self.game.is_over() {
+            let fingerprint = self.game.fingerprint();
+            self.announce(announce::result_announcement(
+                self.game.winner(),
+                self.announced_user(),
+            ));
+
+            let repeated = !self.finished_games.insert(fingerprint);
+            log::info!(
+                "Game over, fingerprint {:016x}{}",
+                fingerprint,
+                if repeated { " (played before)" } else { "" }
+            );
+
+            if let Some(streak) = &mut self.streak {
+                streak.record(self.game.winner(), self.game.user_faction());
+                log::info!(
+                    "Challenge streak: {} (best {})",
+                    streak.current(),
+                    streak.best()
+                );
+            }
+            if let Some(score) = &mut self.score {
+                score.record(self.game.winner());
+                self.backend.update_score(self.score);
+            }
+            if let (Opponent::Ai, Some(outcome)) = (self.opponent, self.game.outcome()) {
+                self.tally.record(outcome, self.game.user_faction());
+            }
+
+            self.update_background();
+            self.update_highlight();
+            self.update_analysis();
+        }
+    }
+
+    // After losing against the AI, highlights the move which gave the game away.
+    fn update_highlight(&mut self) {
+        let user_faction = self.game.user_faction();
+        let lost_against_ai =
+            self.opponent == Opponent::Ai && self.game.winner() == Some(user_faction.opposite());
+
+        let blunder = lost_against_ai
+            .then(|| ai::find_blunder(&self.game, user_faction))
+            .flatten();
+        if let Some(index) = blunder {
+            log::info!("The game was lost by marking cell {}", index);
+        }
+
+        self.backend.update_highlight(blunder);
+    }
+
+    // The title doubles as the only place for text, so it tells what to press in the chooser.
+    fn update_title(&mut self) {
+        let mut title = match (self.chooser.state(), &self.streak) {
+            (GameState::ChoosingFaction, _) => {
+                "Tic Tac GPU — press X or O to pick your faction".to_string()
+            }
+            (GameState::ChoosingOpponent(_), _) => {
+                "Tic Tac GPU — press 1 for an easy AI, 2 for medium, 3 for hard or 4 for a human \
+                opponent"
+                    .to_string()
+            }
+            (GameState::Playing, Some(streak)) => format!(
+                "Tic Tac GPU — streak {} (best {})",
+                streak.current(),
+                streak.best()
+            ),
+            (GameState::Playing, None) => "Tic Tac GPU".to_string(),
+            (GameState::Paused, _) => format!(
+                "Tic Tac GPU — paused: {} (arrow keys and enter, or click)",
+                self.menu.describe()
+            ),
+        };
+
+        if self.chooser.state() == GameState::Playing {
+            let status = if self.game.is_over() {
+                announce::result_announcement(self.game.winner(), self.announced_user())
+            } else {
+                announce::turn_announcement(self.game.turn(), self.announced_user())
+            };
+            title += &format!(" — {}", status);
+
+            if self.opponent == Opponent::Ai && self.tally.games() > 0 {
+                title += &format!(
+                    " — You {} / AI {} / Draws {}",
+                    self.tally.wins, self.tally.losses, self.tally.draws
+                );
+            }
+        }
+        if self.show_coordinates && self.cursor.is_some() && self.human_to_move().is_some() {
+            // counted from the top left, like the window system does
+            let (x, y) = self.selected_field;
+            let row = self.board_size() - 1 - usize::from(y);
+            title += &format!(" — cell ({}, {})", x, row);
+        }
+
+        self.window.set_title(&title);
+    }
+
+    // A finished game is shown by a lighter background, a draw by a more neutral one than a win.
+    fn update_background(&mut self) {
+        self.backend.set_background(match self.game.outcome() {
+            Some(Outcome::Draw) => self.theme.background_drawn,
+            Some(Outcome::Win(_)) => self.theme.background_won,
+            None => self.theme.background,
+        });
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.backend.set_theme(&self.theme);
+        if let Some(move_list) = &mut self.move_list {
+            move_list.set_theme(&self.theme);
+        }
+        self.update_background();
+        self.window.request_redraw();
+    }
+
+    fn reset(&mut self) {
+        self.stop_thinking();
+        self.queued_move = None;
+        self.game.reset();
+        self.undo_floor = 0;
+        self.update_board();
+        self.update_background();
+        self.update_highlight();
+        self.update_analysis();
+
+        self.begin_game();
+    }
+
+    // Takes back the given number of turns, each being one move against a human, or the user's
+    // move along with the AI's answer against the AI. Stops at the oldest move still in reach. If
+    // the AI opens, it does so again once everything is taken back.
+    fn undo(&mut self, turns: usize) {
+        if self.chooser.state() != GameState::Playing {
+            return;
+        }
+        if self.streak.is_some() {
+            log::info!("Taking moves back isn't allowed in the challenge");
+            return;
+        }
+
+        let moves_before = self.game.moves().len();
+        'turns: for _ in 0..turns {
+            // a turn is only taken back completely once a human is on the move again
+            loop {
+                if self.game.moves().len() <= self.undo_floor {
+                    break 'turns;
+                }
+                self.game.undo_n(1);
+                if self.human_to_move().is_some() {
+                    break;
+                }
+            }
+        }
+        let undone = moves_before - self.game.moves().len();
+        if undone == 0 {
+            log::info!("There's nothing to take back");
+            return;
+        }
+        log::info!("Took back {} moves", undone);
+
+        self.stop_thinking();
+        self.queued_move = None;
+        if self.human_to_move().is_none() {
+            self.ai_turn();
+            self.check_game_over();
+        }
+        self.update_board();
+        self.update_background();
+        self.update_highlight();
+        self.update_analysis();
+        self.update_ghosts();
+        self.update_evaluation();
+        self.update_title();
+        self.window.request_redraw();
+    }
+
+    // Opens the pause menu, which takes all input until it's closed again.
+    fn pause(&mut self) {
+        self.chooser.pause();
+        self.menu = PauseMenu::default();
+        self.update_menu();
+        self.update_ghosts();
+    }
+
+    fn update_menu(&mut self) {
+        let selected = (self.chooser.state() == GameState::Paused).then(|| self.menu.selected());
+        self.backend.update_menu(selected);
+        self.update_title();
+        self.window.request_redraw();
+    }
+
+    // Closes the pause menu and does whatever was picked in it.
+    fn pick_menu_option(&mut self, option: MenuOption, flow: &mut ControlFlow) {
+        self.chooser.resume();
+        self.update_menu();
+
+        match option {
+            MenuOption::Resume => {
+                // whatever the AI was up to was interrupted, so it has to start over
+                if self.thinking_since.is_some() {
+                    self.thinking_since = Some(Instant::now());
+                } else {
+                    self.proxy.schedule(AppEvent::AiMoveDue);
+                }
+                self.update_ghosts();
+            }
+            MenuOption::Restart => self.reset(),
+            MenuOption::Quit => *flow = ControlFlow::Exit,
+        }
+    }
+
+    // The menu option below the cursor, if the cursor is on one.
+    fn hovered_menu_option(&self) -> Option<MenuOption> {
+        let screen = screen_position(self.cursor?, self.window.inner_size());
+        MenuOption::at(self.view.to_board(screen))
+    }
+
+    // Lets the AI play at the next harder difficulty from its next move on.
+    fn cycle_difficulty(&mut self) {
+        if self.streak.is_some() {
+            log::info!("The AI can't be made easier in the challenge");
+            return;
+        }
+        if self.opponent == Opponent::Ai {
+            self.difficulty = self.difficulty.next();
+            log::info!("The AI now plays on {}", self.difficulty);
+            self.update_ghosts();
+            self.window.request_redraw();
+        }
+    }
+
+    // Handles a key pressed while holding ctrl. Keys without such a shortcut do nothing, instead
+    // of what they'd do on their own.
+    fn press_shortcut(&mut self, key: VirtualKeyCode) {
+        match key {
+            VirtualKeyCode::Z => self.undo(1),
+            VirtualKeyCode::R => self.reset(),
+            VirtualKeyCode::S => self.save(),
+            VirtualKeyCode::L => self.load(),
+            _ => (),
+        }
+    }
+
+    fn save(&self) {
+        match fs::write(SAVE_PATH, self.game.to_bytes()) {
+            Ok(()) => log::info!("Saved game to {}", SAVE_PATH),
+            Err(e) => log::error!("Could not save game to {}: {}", SAVE_PATH, e),
+        }
+    }
+
+    fn screenshot(&mut self) {
+        let frame = match self.backend.capture() {
+            Ok(frame) => frame,
+            Err(e) => {
+                log::error!("Could not capture the current frame: {}", e);
+                return;
+            }
+        };
+        match fs::write(SCREENSHOT_PATH, frame.to_ppm()) {
+            Ok(()) => log::info!("Saved screenshot to {}", SCREENSHOT_PATH),
+            Err(e) => log::error!("Could not save screenshot to {}: {}", SCREENSHOT_PATH, e),
+        }
+        #[cfg(feature = "clipboard")]
+        match clipboard::copy_frame(&frame) {
+            Ok(program) => log::info!("Copied screenshot to the clipboard using {}", program),
+            Err(e) => log::error!("Could not copy screenshot to the clipboard: {}", e),
+        }
+    }
+
+    fn export_svg(&self) {
+        let svg = svg::board_to_svg(self.game.board(), self.board_size());
+        match fs::write(SVG_PATH, svg) {
+            Ok(()) => log::info!("Exported board to {}", SVG_PATH),
+            Err(e) => log::error!("Could not export board to {}: {}", SVG_PATH, e),
+        }
+    }
+
+    fn load(&mut self) {
+        if self.chooser.state() != GameState::Playing {
+            log::warn!("Finish picking your faction and opponent before loading a game");
+            return;
+        }
+
+        let bytes = match fs::read(SAVE_PATH) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                log::error!("Could not read saved game from {}: {}", SAVE_PATH, e);
+                return;
+            }
+        };
+        match self.game.with_bytes(&bytes) {
+            Ok(game) => self.game = game,
+            Err(e) => {
+                log::error!("Could not load game from {}: {}", SAVE_PATH, e);
+                return;
+            }
+        }
+        log::info!("Loaded game from {}", SAVE_PATH);
+        self.stop_thinking();
+        self.queued_move = None;
+
+        self.undo_floor = 0;
+        self.update_board();
+        self.update_background();
+        self.update_highlight();
+        self.update_analysis();
+        self.update_ghosts();
+        self.update_evaluation();
+        self.update_title();
+        // the game might have been saved right before the AI's move
+        self.proxy.schedule(AppEvent::AiMoveDue);
+        self.window.request_redraw();
+    }
+}
+
+impl HandleEvent for App {
+    fn handle(&mut self, event: Event<AppEvent>, flow: &mut ControlFlow) {
+        // The move list only draws and can be closed on its own, everything else is about the
+        // main window.
+        if let Some(move_list) = &mut self.move_list {
+            match event {
+                Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::CloseRequested,
+                } if window_id == move_list.id() => {
+                    self.move_list = None;
+                    return;
+                }
+                Event::WindowEvent { window_id, .. } | Event::RedrawRequested(window_id)
+                    if window_id == move_list.id() =>
+                {
+                    move_list.handle(event, flow);
+                    return;
+                }
+                // its backend has to be shut down just like the main one below
+                Event::LoopDestroyed => move_list.handle(Event::LoopDestroyed, flow),
+                _ => (),
+            }
+        }
+
+        if let Event::WindowEvent {
+            window_id,
+            ref event,
+        } = event
+        {
+            // anything else would be a window this app doesn't know about
+            if window_id != self.window.id() {
+                return;
+            }
+            match event {
+                WindowEvent::ThemeChanged(theme) => {
+                    log::info!("System theme changed to {:?}", theme);
+                    self.set_theme(Theme::for_system(Some(*theme)));
+                }
+                WindowEvent::Resized(new_size) => {
+                    if self.keep_square {
+                        if let Some(square) = snap_to_square(*new_size) {
+                            self.window.set_inner_size(square);
+                        }
+                    }
+                    self.update_selection(*new_size);
+                }
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size,
+                } => {
+                    // The cursor stays where it is logically, but its physical position changes
+                    // with the scale. Waiting for the next CursorMoved would leave the selection
+                    // stale until the cursor moves.
+                    if let Some(cursor) = &mut self.cursor {
+                        let ratio = scale_factor / self.scale_factor;
+                        cursor.x *= ratio;
+                        cursor.y *= ratio;
+                    }
+                    log::info!(
+                        "Scale factor changed to {}, now {}x{} physical pixels",
+                        scale_factor,
+                        new_inner_size.width,
+                        new_inner_size.height
+                    );
+                    self.scale_factor = *scale_factor;
+                    self.update_selection(**new_inner_size);
+                    self.window.request_redraw();
+                }
+                WindowEvent::CursorMoved { position, .. } => {
+                    self.cursor = Some(*position);
+                    if self.chooser.state() == GameState::Paused {
+                        if let Some(option) = self.hovered_menu_option() {
+                            self.menu.select(option);
+                            self.update_menu();
+                        }
+                    }
+                    if self.panning_from.is_some() {
+                        self.pan(*position);
+                    }
+                    self.update_selection(self.window.inner_size());
+                }
+                WindowEvent::CursorLeft { .. } => {
+                    self.cursor = None;
+                    if let Some(buttons) = &mut self.buttons {
+                        if buttons.hover(None) {
+                            self.update_buttons();
+                        }
+                    }
+                    if self.show_coordinates {
+                        self.update_title();
+                    }
+                }
+                // while paused, the menu takes all input
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(key),
+                            ..
+                        },
+                    ..
+                } if self.chooser.state() == GameState::Paused => match self.menu.press(*key) {
+                    Some(option) => self.pick_menu_option(option, flow),
+                    None => self.update_menu(),
+                },
+                WindowEvent::MouseInput {
+                    button: MouseButton::Left,
+                    state: ElementState::Released,
+                    ..
+                } if self.chooser.state() == GameState::Paused => {
+                    if let Some(option) = self.hovered_menu_option() {
+                        self.pick_menu_option(option, flow);
+                    }
+                }
+                WindowEvent::MouseInput { .. } | WindowEvent::MouseWheel { .. }
+                    if self.chooser.state() == GameState::Paused => {}
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Escape),
+                            ..
+                        },
+                    ..
+                } => self.pause(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(key),
+                            ..
+                        },
+                    ..
+                } if self.chooser.state() != GameState::Playing => {
+                    if self.chooser.press(*key) {
+                        self.start_game();
+                    } else {
+                        self.update_title();
+                    }
+                }
+                WindowEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(key),
+                            ..
+                        },
+                    ..
+                } if self.modifiers.ctrl() => self.press_shortcut(*key),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::G),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.show_ghosts = !self.show_ghosts;
+                    self.update_ghosts();
+                    self.window.request_redraw();
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::H),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.show_grid = !self.show_grid;
+                    self.backend.set_grid_visible(self.show_grid);
+                    self.window.request_redraw();
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::E),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.show_evaluation = !self.show_evaluation;
+                    self.update_evaluation();
+                    self.window.request_redraw();
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::V),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.show_analysis = !self.show_analysis;
+                    self.update_analysis();
+                    self.window.request_redraw();
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::T),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.show_coordinates = !self.show_coordinates;
+                    self.update_title();
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::A),
+                            ..
+                        },
+                    ..
+                } => self.switch_adapter(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::S),
+                            ..
+                        },
+                    ..
+                } => self.save(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::F12),
+                            ..
+                        },
+                    ..
+                } => self.screenshot(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::F3),
+                            ..
+                        },
+                    ..
+                } => log::info!("Render stats: {}", self.backend.stats()),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::F4),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.show_instance_dots = !self.show_instance_dots;
+                    self.backend
+                        .set_instance_dots_visible(self.show_instance_dots);
+                    self.window.request_redraw();
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Q),
+                            ..
+                        },
+                    ..
+                } => self.set_view(self.view.turned()),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode:
+                                Some(
+                                    key @ (VirtualKeyCode::Left
+                                    | VirtualKeyCode::Right
+                                    | VirtualKeyCode::Up
+                                    | VirtualKeyCode::Down),
+                                ),
+                            ..
+                        },
+                    ..
+                } => self.move_selection(match key {
+                    VirtualKeyCode::Left => (-1, 0),
+                    VirtualKeyCode::Right => (1, 0),
+                    VirtualKeyCode::Up => (0, 1),
+                    _ => (0, -1),
+                }),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Return | VirtualKeyCode::Space),
+                            ..
+                        },
+                    ..
+                } => {
+                    if self.game.is_over() {
+                        self.reset();
+                    } else {
+                        // an used cell just does nothing, like when clicking it
+                        let _ = self.user_turn(self.selected_index());
+                    }
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::D),
+                            ..
+                        },
+                    ..
+                } => self.cycle_difficulty(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::B),
+                            ..
+                        },
+                    ..
+                } => self.export_svg(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::L),
+                            ..
+                        },
+                    ..
+                } => self.load(),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::U),
+                            ..
+                        },
+                    ..
+                } => self.undo(1),
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Home),
+                            ..
+                        },
+                    ..
+                } => self.undo(usize::MAX),
+                WindowEvent::MouseInput {
+                    button: MouseButton::Left,
+                    state: ElementState::Pressed,
+                    ..
+                } => {
+                    if let Some(buttons) = &mut self.buttons {
+                        buttons.press();
+                        self.update_buttons();
+                    }
+
+                    if self.place_on == PressOrRelease::Press && !self.debouncing() {
+                        if let Some(index) = self.hovered_index() {
+                            // clicking an used cell just does nothing
+                            if let Ok(true) = self.place_user_mark(index) {
+                                self.pending_move = Some(index);
+                            }
+                        }
+                    }
+                }
+                WindowEvent::MouseInput {
+                    button: MouseButton::Left,
+                    state: ElementState::Released,
+                    ..
+                } => {
+                    // buttons only count as clicked if released where they were pressed
+                    let clicked = match &mut self.buttons {
+                        Some(buttons) => {
+                            let clicked = buttons.release();
+                            self.update_buttons();
+                            clicked
+                        }
+                        None => self.hovered_index(),
+                    };
+
+                    if let Some(index) = self.pending_move.take() {
+                        self.release_pending_move(index);
+                    } else if self.debouncing() {
+                        log::debug!("Ignoring click right after placing a mark");
+                    } else if self.game.is_over() {
+                        self.reset();
+                    } else if let (PressOrRelease::Release, Some(index)) = (self.place_on, clicked)
+                    {
+                        // clicking an used cell just does nothing
+                        let _ = self.user_turn(index);
+                    }
+                }
+                WindowEvent::MouseInput {
+                    button: MouseButton::Right,
+                    state,
+                    ..
+                } => {
+                    self.panning_from = match state {
+                        ElementState::Pressed => self.cursor,
+                        ElementState::Released => None,
+                    };
+                }
+                WindowEvent::MouseInput {
+                    button: MouseButton::Middle,
+                    state: ElementState::Released,
+                    ..
+                } => self.set_view(View {
+                    // the board is only put back in place, which way it's turned is up to taste
+                    quarter_turns: self.view.quarter_turns,
+                    ..View::default()
+                }),
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let steps = match delta {
+                        MouseScrollDelta::LineDelta(_, lines) => *lines,
+                        // roughly what one wheel step scrolls on most platforms
+                        MouseScrollDelta::PixelDelta(position) => (position.y / 40.0) as f32,
+                    };
+                    self.zoom(steps);
+                }
+                _ => (),
+            }
+        }
+        if let Event::RedrawEventsCleared = event {
+            self.update_thinking();
+            self.update_idle();
+            // keep drawing frames until all animations are done
+            if self.backend.is_animating() {
+                self.window.request_redraw();
+            }
+            if let Some(move_list) = &self.move_list {
+                move_list.redraw_if_animating();
+            }
+            // Polling is only needed while frames are drawn continuously, otherwise there's
+            // nothing to do until the next event arrives. Exiting is sticky, so this can't
+            // accidentally cancel it.
+            *flow = if self.is_animating() {
+                ControlFlow::Poll
+            } else {
+                ControlFlow::Wait
+            };
+            // A recording wants a frame every step, whether anything moves or not. Waiting for
+            // the step to pass keeps it roughly as fast as it was played.
+            if let Some(recording) = &self.recording {
+                *flow = ControlFlow::WaitUntil(Instant::now() + recording.step());
+                self.record_frame();
+            }
+        }
+        if let Event::UserEvent(app_event) = event {
+            match app_event {
+                AppEvent::ApplyMove(index) => {
+                    if let Err(e) = self.user_turn(index) {
+                        log::warn!("Ignoring move on cell {}: {}", index, e);
+                    }
+                }
+                AppEvent::ResetGame => self.reset(),
+                AppEvent::AiMoveDue => self.ai_response(),
+            }
+        }
+        // Just forward, maybe it wants to do something with it as well (such as... re-rendering if
+        // needed)
+        self.backend.handle(event, flow);
+    }
+}
+
+/// Maps a physical cursor position to the cell below it, as `(x, y)` with y+ up, taking the
+/// given view into account. Both position and size have to be physical, so this holds regardless
+/// of the scale factor. `None` if there's no cell below the position on a board with the given
+/// number of cells per side.
+fn cell_at(
+    position: dpi::PhysicalPosition<f64>,
+    window_size: dpi::PhysicalSize<u32>,
+    view: View,
+    board_size: usize,
+) -> Option<(u8, u8)> {
+    // simple bounds checking, sometimes on X I've seen some mouse event coming from out of the
+    // actual window size
+    if position.x < 0.0
+        || position.x >= f64::from(window_size.width)
+        || position.y < 0.0
+        || position.y >= f64::from(window_size.height)
+    {
+        return None;
+    }
+
+    // convert to clip space first (this causes our grid to be thought in the wgpu dimension)
+    let screen = screen_position(position, window_size);
+    // even though it's name might not make that clear, these components now range from 0 to the
+    // board size
+    let side = board_size as f32;
+    let grid_pos = view
+        .to_board(screen)
+        .map(|coordinate| (coordinate + 1.0) * side / 2.0);
+
+    // zoomed out or moved away, there might be no board below the cursor at all
+    // the range check already keeps them below the size, clamped anyway so no cell index can
+    // overflow
+    let cell = |coordinate: f32| (coordinate as u8).min(board_size as u8 - 1);
+    grid_pos
+        .iter()
+        .all(|coordinate| (0.0..side).contains(coordinate))
+        .then_some((cell(grid_pos[0]), cell(grid_pos[1])))
+}
+
+/// Converts a physical cursor position into clip space. winit thinks in pixels and y+ down, but
+/// wgpu in clip space and y+ up. Clip space only spans the letterboxed square in the center of
+/// the window, so positions beside it end up beyond -1 or 1.
+fn screen_position(
+    position: dpi::PhysicalPosition<f64>,
+    window_size: dpi::PhysicalSize<u32>,
+) -> [f32; 2] {
+    let ([left, top], side) = render::letterbox(window_size);
+    [
+        (position.x as f32 - left) * 2.0 / side - 1.0,
+        1.0 - (position.y as f32 - top) * 2.0 / side,
+    ]
+}
+
+/// Returns the largest square fitting into the given size, if width and height differ by more than
+/// [`SQUARE_SNAP_THRESHOLD`].
+fn snap_to_square(size: dpi::PhysicalSize<u32>) -> Option<dpi::PhysicalSize<u32>> {
+    let side = size.width.min(size.height);
+    (size.width.abs_diff(size.height) > SQUARE_SNAP_THRESHOLD)
+        .then_some(dpi::PhysicalSize::new(side, side))
+}
+
+fn main() -> Result<(), flexi_logger::FlexiLoggerError> {
+    flexi_logger::Logger::try_with_env()?.start()?;
+
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("--ai-both") => {
+            let simulation = Simulation::from_args(args).unwrap_or_else(|e| {
+                log::error!("{}", e);
+                std::process::exit(1)
+            });
+            simulation.run_and_print();
+            return Ok(());
+        }
+        Some("--replay") => {
+            let replay = Replay::from_args(args).unwrap_or_else(|e| {
+                log::error!("{}", e);
+                std::process::exit(1)
+            });
+            replay.print();
+            return Ok(());
+        }
+        Some("--list-adapters") => {
+            for (index, info) in Backend::list_adapters().iter().enumerate() {
+                println!(
+                    "{}: {} ({:?}, {:?})",
+                    index, info.name, info.backend, info.device_type
+                );
+            }
+            return Ok(());
+        }
+        _ => (),
+    }
+
+    let settings = Settings::from_env().unwrap_or_else(|e| {
+        log::error!("{}", e);
+        std::process::exit(1)
+    });
+    settings.log();
+
+    let event_loop = EventLoopBuilder::with_user_event().build();
+    if settings.stdin {
+        script::spawn_stdin_reader(event_loop.create_proxy());
+    }
+
+    let mut app = pollster::block_on(App::new(&event_loop, settings)).unwrap_or_else(|e| {
+        log::error!("{}", e);
+        std::process::exit(1)
+    });
+    event_loop.run(move |event, _, flow| app.handle(event, flow));
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        ai::MoveQuality,
+        button::ButtonState,
+        chooser::ChooserMode,
+        game::FactionChoice,
+        render::{CaptureError, Frame, RenderStats},
+        std::{cell::RefCell, collections::VecDeque, rc::Rc},
+        winit::event::DeviceId,
+    };
+
+    /// Everything the app told the renderer, in order.
+    #[derive(Debug, Clone, PartialEq)]
+    enum Call {
+        UpdateInstances(Vec<Cell>),
+        DimMarks(Vec<bool>),
+        JitterMarks(Vec<Jitter>),
+        UpdateGhosts(Vec<Cell>),
+        UpdateEvaluation(Vec<Option<i32>>),
+        UpdateAnalysis(Vec<Option<MoveQuality>>),
+        UpdateButtons(Vec<ButtonState>),
+        UpdateHighlight(Option<usize>),
+        UpdateWinLine(Vec<usize>),
+        SetCursor(Option<usize>),
+        RevealMark(usize),
+        UpdateThinking(Option<f32>),
+        UpdateScore(Option<Score>),
+        UpdateMenu(Option<MenuOption>),
+        SetGridVisible(bool),
+        SetInstanceDotsVisible(bool),
+        SetIdle(bool),
+        SetBackground(wgpu::Color),
+        SetTheme(Theme),
+        Capture,
+        AdvanceClock(Duration),
+        SetView(View),
+        /// The renderer was asked to draw a frame.
+        Draw,
+        /// The renderer was told the event loop is going away.
+        ShutDown,
+    }
+
+    /// Records every call instead of drawing anything.
+    struct MockRenderer {
+        calls: Rc<RefCell<Vec<Call>>>,
+        // what is_animating answers, nothing moves on its own here
+        animating: Rc<std::cell::Cell<bool>>,
+    }
+
+    impl MockRenderer {
+        fn record(&self, call: Call) {
+            self.calls.borrow_mut().push(call);
+        }
+    }
+
+    impl Renderer for MockRenderer {
+        fn update_instances(&mut self, board: &[Cell]) {
+            self.record(Call::UpdateInstances(board.to_vec()));
+        }
+        fn dim_marks(&mut self, dimmed: &[bool]) {
+            self.record(Call::DimMarks(dimmed.to_vec()));
+        }
+        fn jitter_marks(&mut self, jitter: &[Jitter]) {
+            self.record(Call::JitterMarks(jitter.to_vec()));
+        }
+        fn update_ghosts(&mut self, ghosts: &[Cell]) {
+            self.record(Call::UpdateGhosts(ghosts.to_vec()));
+        }
+        fn update_evaluation(&mut self, scores: &[Option<i32>]) {
+            self.record(Call::UpdateEvaluation(scores.to_vec()));
+        }
+        fn update_analysis(&mut self, qualities: &[Option<MoveQuality>]) {
+            self.record(Call::UpdateAnalysis(qualities.to_vec()));
+        }
+        fn update_buttons(&mut self, states: &[ButtonState]) {
+            self.record(Call::UpdateButtons(states.to_vec()));
+        }
+        fn is_animating(&self) -> bool {
+            self.animating.get()
+        }
+        fn update_highlight(&mut self, cell: Option<usize>) {
+            self.record(Call::UpdateHighlight(cell));
+        }
+        fn update_win_line(&mut self, cells: &[usize]) {
+            self.record(Call::UpdateWinLine(cells.to_vec()));
+        }
+        fn set_cursor(&mut self, cell: Option<usize>) {
+            self.record(Call::SetCursor(cell));
+        }
+        fn reveal_mark(&mut self, cell: usize) {
+            self.record(Call::RevealMark(cell));
+        }
+        fn update_thinking(&mut self, progress: Option<f32>) {
+            self.record(Call::UpdateThinking(progress));
+        }
+        fn update_score(&mut self, score: Option<Score>) {
+            self.record(Call::UpdateScore(score));
+        }
+        fn update_menu(&mut self, selected: Option<MenuOption>) {
+            self.record(Call::UpdateMenu(selected));
+        }
+        fn set_grid_visible(&mut self, visible: bool) {
+            self.record(Call::SetGridVisible(visible));
+        }
+        fn set_instance_dots_visible(&mut self, visible: bool) {
+            self.record(Call::SetInstanceDotsVisible(visible));
+        }
+        fn set_idle(&mut self, idle: bool) {
+            self.record(Call::SetIdle(idle));
+        }
+        fn set_background(&mut self, color: wgpu::Color) {
+            self.record(Call::SetBackground(color));
+        }
+        fn set_theme(&mut self, theme: &Theme) {
+            self.record(Call::SetTheme(*theme));
+        }
+        fn capture(&mut self) -> Result<Frame, CaptureError> {
+            self.record(Call::Capture);
+            Ok(Frame {
+                width: 1,
+                height: 1,
+                rgba: vec![0, 0, 0, 255],
+            })
+        }
+        fn stats(&self) -> RenderStats {
+            RenderStats::default()
+        }
+        fn adapter(&self) -> Option<wgpu::AdapterInfo> {
+            None
+        }
+        fn advance_clock(&mut self, step: Duration) {
+            self.record(Call::AdvanceClock(step));
+        }
+        fn set_view(&mut self, view: View) {
+            self.record(Call::SetView(view));
+        }
+    }
+
+    impl HandleEvent for MockRenderer {
+        fn handle(&mut self, event: Event<AppEvent>, _: &mut ControlFlow) {
+            match event {
+                Event::RedrawRequested(_) => self.record(Call::Draw),
+                Event::LoopDestroyed => self.record(Call::ShutDown),
+                _ => (),
+            }
+        }
+    }
+
+    /// What the app did to the window, and what the window tells about itself.
+    #[derive(Debug)]
+    struct WindowState {
+        size: dpi::PhysicalSize<u32>,
+        scale_factor: f64,
+        theme: Option<window::Theme>,
+        title: String,
+        redraws_requested: usize,
+    }
+
+    struct MockWindow(Rc<RefCell<WindowState>>);
+
+    impl AppWindow for MockWindow {
+        fn id(&self) -> WindowId {
+            window_id()
+        }
+        fn inner_size(&self) -> dpi::PhysicalSize<u32> {
+            self.0.borrow().size
+        }
+        fn set_inner_size(&self, size: dpi::PhysicalSize<u32>) {
+            self.0.borrow_mut().size = size;
+        }
+        fn scale_factor(&self) -> f64 {
+            self.0.borrow().scale_factor
+        }
+        fn theme(&self) -> Option<window::Theme> {
+            self.0.borrow().theme
+        }
+        fn set_title(&self, title: &str) {
+            self.0.borrow_mut().title = title.to_string();
+        }
+        fn request_redraw(&self) {
+            self.0.borrow_mut().redraws_requested += 1;
+        }
+        fn winit(&self) -> Option<&Window> {
+            None
+        }
+    }
+
+    /// Collects the events the app sends itself, so they can be handled after the current one
+    /// just like the event loop would.
+    struct MockProxy(Rc<RefCell<VecDeque<AppEvent>>>);
+
+    impl Schedule for MockProxy {
+        fn schedule(&self, event: AppEvent) {
+            self.0.borrow_mut().push_back(event);
+        }
+    }
+
+    /// Counts the ticks played instead of playing them.
+    struct MockSound(Rc<std::cell::Cell<u32>>);
+
+    impl Sound for MockSound {
+        fn tick(&self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    fn window_id() -> WindowId {
+        // SAFETY: the ID is only compared against other dummy IDs
+        unsafe { WindowId::dummy() }
+    }
+
+    fn device_id() -> DeviceId {
+        // SAFETY: the ID is never looked at
+        unsafe { DeviceId::dummy() }
+    }
+
+    /// Settings for a reproducible game against minimax, in which the user opens.
+    fn settings() -> Settings {
+        Settings {
+            seed: Some(7),
+            faction: FactionChoice::Fixed(Faction::Ring),
+            strategy: AiStrategy::Minimax,
+            debounce: Duration::ZERO,
+            ..Settings::default()
+        }
+    }
+
+    /// Drives an app with a mocked window, renderer and event loop.
+    struct Harness {
+        app: App,
+        calls: Rc<RefCell<Vec<Call>>>,
+        animating: Rc<std::cell::Cell<bool>>,
+        window: Rc<RefCell<WindowState>>,
+        scheduled: Rc<RefCell<VecDeque<AppEvent>>>,
+        ticks: Rc<std::cell::Cell<u32>>,
+    }
+
+    impl Harness {
+        fn new(settings: Settings) -> Self {
+            Self::with_scale_factor(settings, 1.0)
+        }
+
+        /// Starts with the window on a monitor with the given scale factor, so its 400 by 400
+        /// logical pixels take more or less physical ones.
+        fn with_scale_factor(settings: Settings, scale_factor: f64) -> Self {
+            let calls = Rc::default();
+            let animating = Rc::default();
+            let scheduled = Rc::default();
+            let ticks = Rc::default();
+            let side = (400.0 * scale_factor) as u32;
+            let window = Rc::new(RefCell::new(WindowState {
+                size: dpi::PhysicalSize::new(side, side),
+                scale_factor,
+                theme: None,
+                title: String::new(),
+                redraws_requested: 0,
+            }));
+            let app = App::with_parts(
+                settings,
+                Box::new(MockWindow(Rc::clone(&window))),
+                Box::new(MockRenderer {
+                    calls: Rc::clone(&calls),
+                    animating: Rc::clone(&animating),
+                }),
+                Box::new(MockProxy(Rc::clone(&scheduled))),
+                Box::new(MockSound(Rc::clone(&ticks))),
+                None,
+                None,
+            );
+
+            let mut harness = Self {
+                app,
+                calls,
+                animating,
+                window,
+                scheduled,
+                ticks,
+            };
+            harness.run_scheduled();
+            harness
+        }
+
+        /// Handles the given event, and then all events the app sent itself meanwhile.
+        fn handle(&mut self, event: Event<AppEvent>) -> ControlFlow {
+            let mut flow = ControlFlow::Wait;
+            self.app.handle(event, &mut flow);
+            self.run_scheduled();
+            flow
+        }
+
+        fn run_scheduled(&mut self) {
+            loop {
+                let next = self.scheduled.borrow_mut().pop_front();
+                let Some(event) = next else {
+                    break;
+                };
+                self.app
+                    .handle(Event::UserEvent(event), &mut ControlFlow::Wait);
+            }
+        }
+
+        fn window_event(&mut self, event: WindowEvent) -> ControlFlow {
+            self.handle(Event::WindowEvent {
+                window_id: window_id(),
+                event,
+            })
+        }
+
+        #[allow(deprecated)]
+        fn move_cursor(&mut self, x: f64, y: f64) {
+            self.window_event(WindowEvent::CursorMoved {
+                device_id: device_id(),
+                position: dpi::PhysicalPosition::new(x, y),
+                modifiers: ModifiersState::empty(),
+            });
+        }
+
+        /// Moves the cursor onto the center of the given cell, as long as the view wasn't changed.
+        fn hover(&mut self, index: usize) {
+            let size = self.app.board_size();
+            let ([left, top], side) = render::letterbox(self.window.borrow().size);
+            let cell = f64::from(side) / size as f64;
+            let (x, y) = (index / size, index % size);
+            self.move_cursor(
+                f64::from(left) + (x as f64 + 0.5) * cell,
+                // y+ is up on the board, but down on the screen
+                f64::from(top) + f64::from(side) - (y as f64 + 0.5) * cell,
+            );
+        }
+
+        #[allow(deprecated)]
+        fn mouse(&mut self, button: MouseButton, state: ElementState) -> ControlFlow {
+            self.window_event(WindowEvent::MouseInput {
+                device_id: device_id(),
+                state,
+                button,
+                modifiers: ModifiersState::empty(),
+            })
+        }
+
+        #[allow(deprecated)]
+        fn press_key(&mut self, key: VirtualKeyCode) -> ControlFlow {
+            self.window_event(WindowEvent::KeyboardInput {
+                device_id: device_id(),
+                input: KeyboardInput {
+                    scancode: 0,
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(key),
+                    modifiers: ModifiersState::empty(),
+                },
+                is_synthetic: false,
+            })
+        }
+
+        /// Moves onto the given cell and clicks it with the left mouse button.
+        fn click(&mut self, index: usize) {
+            self.hover(index);
+            self.mouse(MouseButton::Left, ElementState::Pressed);
+            self.mouse(MouseButton::Left, ElementState::Released);
+        }
+
+        /// The board as the renderer was last told to show it. A fresh renderer shows an empty one.
+        fn shown_board(&self) -> Vec<Cell> {
+            let size = self.app.board_size();
+            self.calls
+                .borrow()
+                .iter()
+                .rev()
+                .find_map(|call| match call {
+                    Call::UpdateInstances(board) => Some(board.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| vec![Cell::Empty; size * size])
+        }
+    }
+
+    fn count(board: &[Cell], cell: Cell) -> usize {
+        board.iter().filter(|&&other| other == cell).count()
+    }
+
+    #[test]
+    fn click_places_mark_and_ai_answers() {
+        let mut harness = Harness::new(settings());
+        assert_eq!(harness.shown_board(), vec![Cell::Empty; 9]);
+
+        harness.click(4);
+        let board = harness.shown_board();
+        assert_eq!(board[4], Cell::Ring);
+        assert_eq!(count(&board, Cell::Ring), 1);
+        assert_eq!(count(&board, Cell::Cross), 1);
+
+        // the user's mark is shown on its own first, then along with the AI's answer
+        let shown: Vec<_> = harness
+            .calls
+            .borrow()
+            .iter()
+            .filter_map(|call| match call {
+                Call::UpdateInstances(board) => Some(count(board, Cell::Empty)),
+                _ => None,
+            })
+            .collect();
+        assert!(shown.ends_with(&[8, 7]), "{:?}", shown);
+
+        harness.handle(Event::RedrawRequested(window_id()));
+        assert_eq!(harness.calls.borrow().last(), Some(&Call::Draw));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn events_of_unknown_windows_are_ignored() {
+        let mut harness = Harness::new(settings());
+        harness.hover(4);
+        let calls = harness.calls.borrow().len();
+
+        let unknown = WindowId::from(u64::MAX);
+        assert_ne!(unknown, window_id());
+        for state in [ElementState::Pressed, ElementState::Released] {
+            harness.handle(Event::WindowEvent {
+                window_id: unknown,
+                event: WindowEvent::MouseInput {
+                    device_id: device_id(),
+                    state,
+                    button: MouseButton::Left,
+                    modifiers: ModifiersState::empty(),
+                },
+            });
+        }
+        assert_eq!(harness.calls.borrow().len(), calls);
+        assert!(harness.app.game.moves().is_empty());
+
+        // the very same click in the app's window does place a mark
+        harness.mouse(MouseButton::Left, ElementState::Pressed);
+        harness.mouse(MouseButton::Left, ElementState::Released);
+        assert_eq!(harness.shown_board()[4], Cell::Ring);
+    }
+
+    #[test]
+    fn undo_takes_back_whole_turns() {
+        let mut harness = Harness::new(settings());
+        let play_two_turns = |harness: &mut Harness| {
+            for _ in 0..2 {
+                let free = harness
+                    .app
+                    .game
+                    .board()
+                    .iter()
+                    .position(|cell| cell.is_empty());
+                harness.click(free.unwrap());
+            }
+            assert_eq!(harness.app.game.moves().len(), 4);
+        };
+
+        play_two_turns(&mut harness);
+        let first_turn = harness.app.game.moves()[..2].to_vec();
+        harness.app.undo(1);
+        assert_eq!(harness.app.game.moves(), first_turn);
+        assert_eq!(count(&harness.shown_board(), Cell::Ring), 1);
+        harness.app.undo(1);
+        assert!(harness.app.game.moves().is_empty());
+        assert_eq!(harness.shown_board(), vec![Cell::Empty; 9]);
+        // nothing left to take back
+        harness.app.undo(1);
+        assert!(harness.app.game.moves().is_empty());
+
+        play_two_turns(&mut harness);
+        harness.app.undo(usize::MAX);
+        assert!(harness.app.game.moves().is_empty());
+        assert_eq!(harness.shown_board(), vec![Cell::Empty; 9]);
+    }
+
+    #[test]
+    fn u_undoes_as_well() {
+        let mut harness = Harness::new(settings());
+        harness.click(4);
+        assert_eq!(harness.app.game.moves().len(), 2);
+
+        harness.press_key(VirtualKeyCode::U);
+        assert!(harness.app.game.moves().is_empty());
+        assert_eq!(harness.shown_board(), vec![Cell::Empty; 9]);
+    }
+
+    #[test]
+    fn only_ctrl_z_undoes() {
+        let mut harness = Harness::new(settings());
+        harness.click(4);
+        assert_eq!(harness.app.game.moves().len(), 2);
+
+        harness.press_key(VirtualKeyCode::Z);
+        assert_eq!(harness.app.game.moves().len(), 2);
+
+        harness.window_event(WindowEvent::ModifiersChanged(ModifiersState::CTRL));
+        harness.press_key(VirtualKeyCode::Z);
+        assert!(harness.app.game.moves().is_empty());
+        assert_eq!(harness.shown_board(), vec![Cell::Empty; 9]);
+
+        // once ctrl is let go, Z is a plain key again
+        harness.click(4);
+        harness.window_event(WindowEvent::ModifiersChanged(ModifiersState::empty()));
+        harness.press_key(VirtualKeyCode::Z);
+        assert_eq!(harness.app.game.moves().len(), 2);
+    }
+
+    #[test]
+    fn buttons_follow_hover_and_press() {
+        let mut harness = Harness::new(Settings {
+            buttons: true,
+            place_on: PressOrRelease::Release,
+            ..settings()
+        });
+        let shown = |harness: &Harness| {
+            harness
+                .calls
+                .borrow()
+                .iter()
+                .rev()
+                .find_map(|call| match call {
+                    Call::UpdateButtons(states) => Some(states.clone()),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        let only = |cell: usize, state| {
+            let mut states = vec![ButtonState::Idle; 9];
+            states[cell] = state;
+            states
+        };
+        assert_eq!(shown(&harness), vec![ButtonState::Idle; 9]);
+
+        harness.hover(4);
+        assert_eq!(shown(&harness), only(4, ButtonState::Hovered));
+        harness.mouse(MouseButton::Left, ElementState::Pressed);
+        assert_eq!(shown(&harness), only(4, ButtonState::Pressed));
+        assert_eq!(harness.shown_board()[4], Cell::Empty);
+
+        harness.mouse(MouseButton::Left, ElementState::Released);
+        assert_eq!(shown(&harness), only(4, ButtonState::Hovered));
+        assert_eq!(harness.shown_board()[4], Cell::Ring);
+    }
+
+    #[test]
+    fn polls_only_while_animating() {
+        let mut harness = Harness::new(Settings {
+            thinking_delay: Duration::from_secs(3600),
+            ..settings()
+        });
+        assert_eq!(
+            harness.handle(Event::RedrawEventsCleared),
+            ControlFlow::Wait
+        );
+
+        harness.animating.set(true);
+        assert_eq!(
+            harness.handle(Event::RedrawEventsCleared),
+            ControlFlow::Poll
+        );
+        harness.animating.set(false);
+        assert_eq!(
+            harness.handle(Event::RedrawEventsCleared),
+            ControlFlow::Wait
+        );
+
+        // the thinking dots fill in on their own
+        harness.click(4);
+        assert!(harness.app.thinking_since.is_some());
+        assert_eq!(
+            harness.handle(Event::RedrawEventsCleared),
+            ControlFlow::Poll
+        );
+    }
+
+    #[test]
+    fn d_cycles_through_the_difficulties() {
+        let mut harness = Harness::new(Settings {
+            strategy: AiStrategy::Humanlike,
+            ..settings()
+        });
+        assert_eq!(harness.app.difficulty, Difficulty::Medium);
+        assert_eq!(harness.app.ai_strategy(), AiStrategy::Humanlike);
+
+        harness.press_key(VirtualKeyCode::D);
+        assert_eq!(harness.app.ai_strategy(), AiStrategy::Minimax);
+        harness.press_key(VirtualKeyCode::D);
+        assert_eq!(harness.app.ai_strategy(), AiStrategy::Random);
+        // humanlike is what medium means for this player
+        harness.press_key(VirtualKeyCode::D);
+        assert_eq!(harness.app.ai_strategy(), AiStrategy::Humanlike);
+    }
+
+    #[test]
+    fn chooser_picks_the_difficulty() {
+        let mut harness = Harness::new(Settings {
+            chooser: ChooserMode::Always,
+            ..settings()
+        });
+        assert_eq!(harness.app.chooser.state(), GameState::ChoosingFaction);
+        harness.press_key(VirtualKeyCode::O);
+        harness.press_key(VirtualKeyCode::Key2);
+        assert_eq!(harness.app.chooser.state(), GameState::Playing);
+        assert_eq!(harness.app.opponent, Opponent::Ai);
+        assert_eq!(harness.app.difficulty, Difficulty::Medium);
+        assert_eq!(harness.app.ai_strategy(), AiStrategy::Mixed);
+    }
+
+    #[test]
+    fn board_ignores_input_while_paused() {
+        let mut harness = Harness::new(settings());
+        harness.press_key(VirtualKeyCode::Escape);
+        assert_eq!(harness.app.chooser.state(), GameState::Paused);
+        // a corner is beside all options of the menu
+        harness.click(0);
+        harness.press_key(VirtualKeyCode::Key1);
+        assert!(harness.app.game.moves().is_empty());
+        assert_eq!(harness.app.chooser.state(), GameState::Paused);
+
+        // escape picks resume right away
+        harness.press_key(VirtualKeyCode::Escape);
+        assert_eq!(harness.app.chooser.state(), GameState::Playing);
+        harness.click(4);
+        assert_eq!(harness.shown_board()[4], Cell::Ring);
+
+        harness.press_key(VirtualKeyCode::Escape);
+        harness.press_key(VirtualKeyCode::Down);
+        harness.press_key(VirtualKeyCode::Down);
+        assert_eq!(
+            harness.calls.borrow().last(),
+            Some(&Call::UpdateMenu(Some(MenuOption::Quit)))
+        );
+        assert_eq!(harness.press_key(VirtualKeyCode::Return), ControlFlow::Exit);
+    }
+
+    #[test]
+    fn releasing_beside_the_board_marks_nothing() {
+        let mut harness = Harness::new(Settings {
+            place_on: PressOrRelease::Release,
+            ..settings()
+        });
+        // wider than tall, so there are bars left and right of the board
+        harness.window.borrow_mut().size = dpi::PhysicalSize::new(600, 400);
+        harness.hover(4);
+        assert_eq!(harness.app.selected_index(), 4);
+
+        harness.move_cursor(50.0, 200.0);
+        harness.mouse(MouseButton::Left, ElementState::Pressed);
+        harness.mouse(MouseButton::Left, ElementState::Released);
+        assert!(harness.app.game.moves().is_empty());
+        assert_eq!(harness.shown_board(), vec![Cell::Empty; 9]);
+
+        // the same for a board moved away from below the cursor
+        harness.hover(4);
+        harness.app.set_view(View {
+            offset: [1.5, 0.0],
+            ..View::default()
+        });
+        harness.mouse(MouseButton::Left, ElementState::Pressed);
+        harness.mouse(MouseButton::Left, ElementState::Released);
+        assert!(harness.app.game.moves().is_empty());
+    }
+
+    #[test]
+    fn marks_are_placed_on_press_or_release() {
+        let mut harness = Harness::new(Settings {
+            place_on: PressOrRelease::Release,
+            ..settings()
+        });
+        harness.hover(4);
+        harness.mouse(MouseButton::Left, ElementState::Pressed);
+        assert_eq!(harness.shown_board()[4], Cell::Empty);
+        harness.mouse(MouseButton::Left, ElementState::Released);
+        assert_eq!(harness.shown_board()[4], Cell::Ring);
+        assert_eq!(count(&harness.shown_board(), Cell::Cross), 1);
+
+        let mut harness = Harness::new(Settings {
+            place_on: PressOrRelease::Press,
+            ..settings()
+        });
+        harness.hover(4);
+        harness.mouse(MouseButton::Left, ElementState::Pressed);
+        assert_eq!(harness.shown_board()[4], Cell::Ring);
+        // the AI only answers once it's certain the mark stays
+        assert_eq!(count(&harness.shown_board(), Cell::Cross), 0);
+        harness.mouse(MouseButton::Left, ElementState::Released);
+        assert_eq!(count(&harness.shown_board(), Cell::Cross), 1);
+
+        // dragging off the cell before letting go takes the mark back
+        let free = harness
+            .app
+            .game
+            .board()
+            .iter()
+            .position(|cell| cell.is_empty());
+        let free = free.unwrap();
+        harness.hover(free);
+        harness.mouse(MouseButton::Left, ElementState::Pressed);
+        assert_eq!(harness.shown_board()[free], Cell::Ring);
+        harness.hover(4);
+        harness.mouse(MouseButton::Left, ElementState::Released);
+        assert_eq!(harness.shown_board()[free], Cell::Empty);
+        assert_eq!(harness.app.game.moves().len(), 2);
+    }
+
+    #[test]
+    fn theme_change_swaps_the_background() {
+        let mut harness = Harness::new(settings());
+        let background = |harness: &Harness| {
+            harness
+                .calls
+                .borrow()
+                .iter()
+                .rev()
+                .find_map(|call| match call {
+                    Call::SetBackground(color) => Some(*color),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        assert_eq!(background(&harness), Theme::DARK.background);
+
+        harness.window_event(WindowEvent::ThemeChanged(window::Theme::Light));
+        assert_eq!(background(&harness), Theme::LIGHT.background);
+        assert!(harness
+            .calls
+            .borrow()
+            .contains(&Call::SetTheme(Theme::LIGHT)));
+
+        // ring won by completing the bottom row
+        harness.app.game = Game::replay_from_moves(&[0, 1, 3, 2, 6], Faction::Ring).unwrap();
+        harness.app.update_background();
+        assert_eq!(background(&harness), Theme::LIGHT.background_won);
+        harness.window_event(WindowEvent::ThemeChanged(window::Theme::Dark));
+        assert_eq!(background(&harness), Theme::DARK.background_won);
+    }
+
+    #[test]
+    fn wins_are_tallied_across_games() {
+        // ring only has to complete the left column, and it's ring's turn
+        let mut harness = Harness::new(Settings {
+            handicap: "o0 o1 x3 x4".parse().unwrap(),
+            ..settings()
+        });
+        for wins in 1..=3 {
+            harness.click(2);
+            assert_eq!(harness.app.game.winner(), Some(Faction::Ring));
+            assert_eq!(harness.app.tally.wins, wins);
+            assert!(harness
+                .window
+                .borrow()
+                .title
+                .ends_with(&format!("You {} / AI 0 / Draws 0", wins)));
+
+            // the next click starts the next game, but keeps the tally
+            harness.click(2);
+            assert!(!harness.app.game.is_over());
+        }
+        assert_eq!(
+            harness.app.tally,
+            Tally {
+                wins: 3,
+                losses: 0,
+                draws: 0
+            }
+        );
+    }
+
+    #[test]
+    fn handicap_can_let_the_ai_win_right_away() {
+        // cross only has to complete the left column, and it's cross' turn
+        let harness = Harness::new(Settings {
+            handicap: "x0 x1 o4 o6 o8".parse().unwrap(),
+            ..settings()
+        });
+        assert_eq!(harness.app.game.winner(), Some(Faction::Cross));
+        assert_eq!(harness.app.tally.losses, 1);
+        assert!(harness
+            .calls
+            .borrow()
+            .contains(&Call::SetBackground(Theme::DARK.background_won)));
+    }
+
+    #[test]
+    fn script_plays_whole_game() {
+        let mut harness = Harness::new(settings());
+        // the AI takes some of these cells first, those are just skipped
+        let script = "4\n0\n2\n6\n8\n1\n3\n5\n7\n";
+        script::read_script(script.as_bytes(), |event| {
+            harness.handle(Event::UserEvent(event));
+            Ok::<_, ()>(())
+        });
+        assert_ne!(harness.app.game.outcome(), None);
+        assert_eq!(harness.shown_board(), harness.app.game.board());
+
+        script::read_script("reset\n".as_bytes(), |event| {
+            harness.handle(Event::UserEvent(event));
+            Ok::<_, ()>(())
+        });
+        assert_eq!(harness.app.game.outcome(), None);
+        assert_eq!(harness.shown_board(), vec![Cell::Empty; 9]);
+    }
+
+    #[test]
+    fn apply_move_event_plays_for_the_user() {
+        let mut harness = Harness::new(settings());
+        harness.handle(Event::UserEvent(AppEvent::ApplyMove(0)));
+        let board = harness.shown_board();
+        assert_eq!(board[0], Cell::Ring);
+        assert_eq!(count(&board, Cell::Cross), 1);
+
+        // neither cells off the board nor taken ones change anything
+        harness.handle(Event::UserEvent(AppEvent::ApplyMove(9)));
+        harness.handle(Event::UserEvent(AppEvent::ApplyMove(0)));
+        assert_eq!(harness.shown_board(), board);
+    }
+
+    #[test]
+    fn alternating_factions_let_the_ai_open_as_ring() {
+        let mut harness = Harness::new(Settings {
+            faction: FactionChoice::Alternate,
+            ..settings()
+        });
+        for game in 1..=4 {
+            if game > 1 {
+                harness.handle(Event::UserEvent(AppEvent::ResetGame));
+            }
+            let board = harness.shown_board();
+            if game % 2 == 1 {
+                assert_eq!(harness.app.game.user_faction(), Faction::Cross);
+                assert_eq!(count(&board, Cell::Ring), 1, "game {}", game);
+            } else {
+                assert_eq!(harness.app.game.user_faction(), Faction::Ring);
+                assert_eq!(board, vec![Cell::Empty; 9], "game {}", game);
+            }
+        }
+    }
+
+    #[test]
+    fn snaps_only_clearly_non_square_sizes() {
+        let size = dpi::PhysicalSize::new;
+        assert_eq!(snap_to_square(size(800, 600)), Some(size(600, 600)));
+        assert_eq!(snap_to_square(size(300, 500)), Some(size(300, 300)));
+        // off by a few pixels is what window managers do anyways, snapping would never end
+        assert_eq!(snap_to_square(size(600, 602)), None);
+        assert_eq!(snap_to_square(size(600, 600)), None);
+    }
+
+    #[test]
+    fn hit_testing_undoes_the_view() {
+        let window_size = dpi::PhysicalSize::new(300, 300);
+        let at = |x, y, view| cell_at(dpi::PhysicalPosition::new(x, y), window_size, view, 3);
+        // half as large and moved into the top right quarter of the window
+        let view = View {
+            scale: 0.5,
+            offset: [0.5, 0.5],
+            quarter_turns: 0,
+        };
+
+        assert_eq!(at(175.0, 125.0, view), Some((0, 0)));
+        assert_eq!(at(290.0, 10.0, view), Some((2, 2)));
+        assert_eq!(at(225.0, 75.0, view), Some((1, 1)));
+        // where the board would be without the view, but isn't anymore
+        assert_eq!(at(10.0, 10.0, view), None);
+        assert_eq!(at(10.0, 10.0, View::default()), Some((0, 2)));
+    }
+
+    #[test]
+    fn hit_testing_follows_quarter_turns() {
+        let window_size = dpi::PhysicalSize::new(300, 300);
+        let at = |x, y, view| cell_at(dpi::PhysicalPosition::new(x, y), window_size, view, 3);
+        let view = View::default().turned();
+
+        // turned counter-clockwise, the bottom left corner ends up in the bottom right
+        assert_eq!(at(250.0, 250.0, view), Some((0, 0)));
+        assert_eq!(at(50.0, 250.0, view), Some((0, 2)));
+        assert_eq!(at(250.0, 50.0, view), Some((2, 0)));
+        assert_eq!(at(50.0, 50.0, view), Some((2, 2)));
+        assert_eq!(at(150.0, 150.0, view), Some((1, 1)));
+        // the screen's left edge shows the top row
+        assert_eq!(at(50.0, 150.0, view), Some((1, 2)));
+
+        // four turns are back where they started
+        let full = view.turned().turned().turned();
+        assert_eq!(full, View::default());
+        assert_eq!(at(50.0, 250.0, full), Some((0, 0)));
+    }
+
+    #[test]
+    fn nothing_is_hit_past_the_bottom_edge() {
+        // wider than tall, so a y past the bottom is still within the width
+        let window_size = dpi::PhysicalSize::new(600, 400);
+        let at = |x, y| {
+            cell_at(
+                dpi::PhysicalPosition::new(x, y),
+                window_size,
+                View::default(),
+                3,
+            )
+        };
+        assert_eq!(at(300.0, 399.0), Some((1, 0)));
+        assert_eq!(at(300.0, 400.0), None);
+        assert_eq!(at(300.0, 401.0), None);
+        assert_eq!(at(300.0, 599.0), None);
+
+        let mut harness = Harness::new(settings());
+        harness.window.borrow_mut().size = window_size;
+        harness.move_cursor(300.0, 399.0);
+        assert_eq!(harness.app.selected_field, (1, 0));
+        harness.move_cursor(300.0, 400.5);
+        assert_eq!(harness.app.selected_field, (1, 0));
+        assert_eq!(harness.app.hovered_index(), None);
+    }
+
+    #[test]
+    fn turning_the_board_keeps_clicks_on_the_cell_below() {
+        let mut harness = Harness::new(settings());
+        harness.press_key(VirtualKeyCode::Q);
+        assert_eq!(harness.app.view.quarter_turns, 1);
+
+        // the bottom right corner of the window shows the bottom left cell now
+        harness.move_cursor(350.0, 350.0);
+        assert_eq!(harness.app.hovered_index(), Some(0));
+        harness.mouse(MouseButton::Left, ElementState::Pressed);
+        harness.mouse(MouseButton::Left, ElementState::Released);
+        assert_eq!(harness.shown_board()[0], Cell::Ring);
+    }
+
+    #[test]
+    fn resizing_snaps_back_to_square() {
+        let mut harness = Harness::new(Settings {
+            square: true,
+            ..settings()
+        });
+        harness.window_event(WindowEvent::Resized(dpi::PhysicalSize::new(800, 600)));
+        assert_eq!(
+            harness.window.borrow().size,
+            dpi::PhysicalSize::new(600, 600)
+        );
+    }
+
+    #[test]
+    fn opening_move_is_shown_on_first_frame() {
+        let mut harness = Harness::new(Settings {
+            faction: FactionChoice::Fixed(Faction::Cross),
+            ..settings()
+        });
+        assert!(harness.window.borrow().redraws_requested > 0);
+        assert_eq!(count(&harness.shown_board(), Cell::Ring), 1);
+
+        harness.handle(Event::RedrawRequested(window_id()));
+        let calls = harness.calls.borrow();
+        let opened = calls.iter().position(
+            |call| matches!(call, Call::UpdateInstances(board) if count(board, Cell::Ring) == 1),
+        );
+        let drawn = calls.iter().position(|call| call == &Call::Draw);
+        assert!(opened.is_some() && opened < drawn, "{:?}", calls);
+    }
+
+    #[test]
+    fn starts_on_a_scaled_monitor() {
+        let mut harness = Harness::with_scale_factor(settings(), 1.5);
+        assert_eq!(
+            harness.window.borrow().size,
+            dpi::PhysicalSize::new(600, 600)
+        );
+        // the top right corner in physical pixels, which would be off the window if taken as
+        // logical ones
+        harness.move_cursor(550.0, 50.0);
+        assert_eq!(harness.app.hovered_index(), Some(8));
+        harness.click(8);
+        assert_eq!(harness.shown_board()[8], Cell::Ring);
+    }
+
+    #[test]
+    fn hit_test_follows_scale_factor_change() {
+        let mut harness = Harness::new(settings());
+        harness.hover(0);
+        assert_eq!(harness.app.hovered_index(), Some(0));
+
+        // moved onto a monitor with twice the density, the window keeps its logical size
+        let mut new_inner_size = dpi::PhysicalSize::new(800, 800);
+        harness.window.borrow_mut().size = new_inner_size;
+        harness.window.borrow_mut().scale_factor = 2.0;
+        harness.window_event(WindowEvent::ScaleFactorChanged {
+            scale_factor: 2.0,
+            new_inner_size: &mut new_inner_size,
+        });
+        assert_eq!(harness.app.hovered_index(), Some(0));
+
+        // from now on, the cursor arrives in the doubled coordinates
+        harness.move_cursor(700.0, 100.0);
+        assert_eq!(harness.app.hovered_index(), Some(8));
+        harness.click(8);
+        assert_eq!(harness.shown_board()[8], Cell::Ring);
+    }
+
+    #[test]
+    fn quick_second_click_is_ignored() {
+        let mut harness = Harness::new(Settings {
+            debounce: Duration::from_secs(3600),
+            ..settings()
+        });
+        harness.click(4);
+        let board = harness.shown_board();
+        assert_eq!(count(&board, Cell::Empty), 7);
+
+        // plenty of cells are still free, but the second click comes too soon for any of them
+        let free = board.iter().position(|cell| cell.is_empty()).unwrap();
+        harness.click(free);
+        assert_eq!(harness.shown_board(), board);
+    }
+
+    #[test]
+    fn ticks_only_when_another_cell_is_hovered() {
+        let mut harness = Harness::new(settings());
+        harness.app.tick_interval = Duration::ZERO;
+        // the center is selected from the start
+        harness.hover(4);
+        assert_eq!(harness.ticks.get(), 0);
+
+        harness.hover(0);
+        assert_eq!(harness.ticks.get(), 1);
+        // still on the same cell, just a bit further right
+        harness.move_cursor(60.0, 350.0);
+        assert_eq!(harness.app.hovered_index(), Some(0));
+        assert_eq!(harness.ticks.get(), 1);
+        harness.hover(8);
+        assert_eq!(harness.ticks.get(), 2);
+
+        // sweeping across the board right after a tick doesn't rattle
+        harness.app.tick_interval = Duration::from_secs(3600);
+        harness.hover(0);
+        harness.hover(4);
+        assert_eq!(harness.ticks.get(), 2);
+        harness.app.tick_interval = Duration::ZERO;
+        harness.hover(8);
+        assert_eq!(harness.ticks.get(), 3);
+    }
+
+    #[test]
+    fn recording_saves_one_frame_per_step() {
+        let dir = std::env::temp_dir().join(format!("tic-tac-gpu-record-{}", std::process::id()));
+        let mut harness = Harness::new(settings());
+        harness.app.recording = Some(Recording::new(&dir, 25).unwrap());
+        let step = Duration::from_millis(40);
+
+        for _ in 0..5 {
+            assert!(matches!(
+                harness.handle(Event::RedrawEventsCleared),
+                ControlFlow::WaitUntil(_)
+            ));
+        }
+        let frames = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(frames, 5);
+
+        // the clock only moves by whole steps, no matter how long the frames took
+        let steps: Vec<_> = harness
+            .calls
+            .borrow()
+            .iter()
+            .filter_map(|call| match call {
+                Call::AdvanceClock(step) => Some(*step),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(steps, [step; 5]);
+    }
+
+    #[test]
+    fn clicks_while_the_ai_thinks_are_ignored_or_queued() {
+        for queue_clicks in [false, true] {
+            let mut harness = Harness::new(Settings {
+                thinking_delay: Duration::from_secs(3600),
+                queue_clicks,
+                ..settings()
+            });
+            harness.click(4);
+            harness.click(1);
+            let board = harness.shown_board();
+            assert_eq!(count(&board, Cell::Ring), 1);
+            assert_eq!(count(&board, Cell::Cross), 0);
+
+            // done thinking
+            harness.app.thinking_since = Some(Instant::now() - Duration::from_secs(3600));
+            harness.handle(Event::RedrawEventsCleared);
+            let board = harness.shown_board();
+            assert_eq!(count(&board, Cell::Cross), 1);
+            assert_eq!(board[1] == Cell::Ring, queue_clicks);
+        }
+    }
+
+    #[test]
+    fn renderer_is_told_about_the_shutdown() {
+        let mut harness = Harness::new(settings());
+        harness.handle(Event::LoopDestroyed);
+        assert_eq!(harness.calls.borrow().last(), Some(&Call::ShutDown));
+    }
+
+    #[test]
+    fn jitter_is_reproducible_and_stays_with_its_mark() {
+        let play = || {
+            let mut harness = Harness::new(Settings {
+                jitter: true,
+                ..settings()
+            });
+            harness.click(4);
+            let calls = harness.calls.borrow();
+            let jitters: Vec<_> = calls
+                .iter()
+                .filter_map(|call| match call {
+                    Call::JitterMarks(jitter) => Some(jitter.clone()),
+                    _ => None,
+                })
+                .collect();
+            jitters
+        };
+
+        let jitters = play();
+        assert_eq!(play(), jitters);
+        // once placed, the user's mark keeps its jitter while the AI's answer gets its own
+        let last = jitters.last().unwrap();
+        let placed: Vec<_> = jitters
+            .iter()
+            .filter(|jitter| jitter[4] != Jitter::default())
+            .collect();
+        assert!(placed.len() >= 2);
+        assert!(placed.iter().all(|jitter| jitter[4] == last[4]));
+        let jittered = last.iter().filter(|&&jitter| jitter != Jitter::default());
+        assert_eq!(jittered.count(), 2);
+
+        // without a fixed seed, it's all but impossible to roll the same twice
+        let mut unseeded = Harness::new(Settings {
+            jitter: true,
+            seed: None,
+            ..settings()
+        });
+        unseeded.click(4);
+        assert!(!unseeded
+            .calls
+            .borrow()
+            .contains(&Call::JitterMarks(last.clone())));
+    }
+
+    #[test]
+    fn hovered_cell_is_outlined_while_it_can_be_marked() {
+        let mut harness = Harness::new(settings());
+        let outlined = |harness: &Harness| {
+            harness
+                .calls
+                .borrow()
+                .iter()
+                .rev()
+                .find_map(|call| match call {
+                    Call::SetCursor(cell) => Some(*cell),
+                    _ => None,
+                })
+                .flatten()
+        };
+
+        harness.hover(0);
+        assert_eq!(outlined(&harness), Some(0));
+        harness.hover(8);
+        assert_eq!(outlined(&harness), Some(8));
+        // marked, so there's nothing to hint at anymore
+        harness.click(8);
+        assert_eq!(outlined(&harness), None);
+        // until the cursor moves on to a free cell again
+        let free = harness
+            .shown_board()
+            .iter()
+            .position(|cell| cell.is_empty());
+        harness.hover(free.unwrap());
+        assert_eq!(outlined(&harness), free);
+
+        let mut hidden = Harness::new(Settings {
+            cursor: false,
+            ..settings()
+        });
+        hidden.hover(0);
+        assert!(!hidden
+            .calls
+            .borrow()
+            .iter()
+            .any(|call| matches!(call, Call::SetCursor(_))));
+    }
 }
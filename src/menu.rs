@@ -0,0 +1,109 @@
+use winit::event::VirtualKeyCode;
+
+/// Half the width and height of the bar each option is shown as, in board coordinates.
+pub const BAR_HALF_SIZE: [f32; 2] = [0.6, 0.12];
+
+/// What can be picked in the pause menu, from top to bottom.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MenuOption {
+    Resume,
+    /// Throws away the current game and starts a new one.
+    Restart,
+    Quit,
+}
+
+impl MenuOption {
+    pub const ALL: [Self; 3] = [Self::Resume, Self::Restart, Self::Quit];
+
+    /// Where the bar of this option is centered vertically, in board coordinates.
+    pub fn center_y(self) -> f32 {
+        match self {
+            Self::Resume => 0.4,
+            Self::Restart => 0.0,
+            Self::Quit => -0.4,
+        }
+    }
+
+    /// The option whose bar is at the given position in board coordinates, if any.
+    pub fn at(position: [f32; 2]) -> Option<Self> {
+        Self::ALL.into_iter().find(|option| {
+            position[0].abs() <= BAR_HALF_SIZE[0]
+                && (position[1] - option.center_y()).abs() <= BAR_HALF_SIZE[1]
+        })
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Resume => "Resume",
+            Self::Restart => "Restart",
+            Self::Quit => "Quit",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::Resume => 0,
+            Self::Restart => 1,
+            Self::Quit => 2,
+        }
+    }
+}
+
+/// Which option of the pause menu is selected. Whether the menu is open at all is decided by
+/// [`GameState::Paused`](super::chooser::GameState::Paused).
+#[derive(Debug)]
+pub struct PauseMenu {
+    selected: MenuOption,
+}
+
+impl Default for PauseMenu {
+    fn default() -> Self {
+        Self {
+            selected: MenuOption::Resume,
+        }
+    }
+}
+
+impl PauseMenu {
+    pub fn selected(&self) -> MenuOption {
+        self.selected
+    }
+
+    pub fn select(&mut self, option: MenuOption) {
+        self.selected = option;
+    }
+
+    /// Feeds a pressed key into the menu. The arrow keys move the selection, enter and space pick
+    /// the selected option, escape resumes right away. Returns the picked option, if any.
+    pub fn press(&mut self, key: VirtualKeyCode) -> Option<MenuOption> {
+        let index = self.selected.index();
+        match key {
+            VirtualKeyCode::Up => {
+                self.selected = MenuOption::ALL[index.saturating_sub(1)];
+                None
+            }
+            VirtualKeyCode::Down => {
+                self.selected = MenuOption::ALL[(index + 1).min(MenuOption::ALL.len() - 1)];
+                None
+            }
+            VirtualKeyCode::Return | VirtualKeyCode::Space => Some(self.selected),
+            VirtualKeyCode::Escape => Some(MenuOption::Resume),
+            _ => None,
+        }
+    }
+
+    /// Lists all options, with the selected one in brackets.
+    pub fn describe(&self) -> String {
+        MenuOption::ALL
+            .iter()
+            .map(|&option| {
+                if option == self.selected {
+                    format!("[{}]", option.label())
+                } else {
+                    option.label().to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
@@ -0,0 +1,104 @@
+use {
+    super::{
+        game::Cell,
+        render::{Backend, Layout, LimitsMode, Renderer},
+        theme::Theme,
+        AppError, AppEvent, HandleEvent,
+    },
+    winit::{
+        dpi,
+        event::{Event, WindowEvent},
+        event_loop::{ControlFlow, EventLoop},
+        window::{Window, WindowBuilder, WindowId},
+    },
+};
+
+/// A second window listing all moves of the current game, e.g. for showing on a stream next to
+/// the board. Only shows things, all input apart from closing it is ignored.
+pub struct MoveListWindow {
+    backend: Backend,
+    // DO NOT REORDER THIS -- Safety of Backend::new depends on it
+    window: Window,
+}
+
+impl MoveListWindow {
+    /// Opens the window for listing the moves of games on a board with the given number of cells
+    /// per side.
+    pub async fn new(
+        event_loop: &EventLoop<AppEvent>,
+        board_size: usize,
+        transparent: bool,
+        adapter: Option<usize>,
+        limits: LimitsMode,
+    ) -> Result<Self, AppError> {
+        let window = WindowBuilder::new()
+            .with_title("Tic Tac GPU — moves")
+            .with_resizable(false)
+            .with_inner_size(dpi::LogicalSize::new(300, 300))
+            .with_transparent(transparent)
+            .build(event_loop)?;
+        // SAFETY: window is in the same struct as the backend and the window gets dropped after
+        // the backend
+        let layout = Layout::MoveList(board_size);
+        let backend =
+            unsafe { Backend::new(&window, transparent, adapter, limits, layout) }.await?;
+
+        Ok(Self { backend, window })
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    /// Shows the given moves, which have been placed on the given board.
+    pub fn update(&mut self, board: &[Cell], moves: &[usize]) {
+        self.backend.update_instances(&list_cells(board, moves));
+        self.window.request_redraw();
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.backend.set_theme(theme);
+        self.backend.set_background(theme.background);
+        self.window.request_redraw();
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.backend.is_animating()
+    }
+
+    /// Keeps drawing until all marks of the last game have faded out.
+    pub fn redraw_if_animating(&self) {
+        if self.is_animating() {
+            self.window.request_redraw();
+        }
+    }
+}
+
+impl HandleEvent for MoveListWindow {
+    fn handle(&mut self, event: Event<AppEvent>, flow: &mut ControlFlow) {
+        // closing this window is up to the app, the backend would quit everything
+        if let Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            ..
+        } = event
+        {
+            return;
+        }
+        self.backend.handle(event, flow);
+    }
+}
+
+/// Lays out the moves as expected by [`Layout::MoveList`]: Each move is placed in its row, in the
+/// column of the field it was placed on, with the mark it has on the board.
+fn list_cells(board: &[Cell], moves: &[usize]) -> Vec<Cell> {
+    // one column per field of the board
+    let side = board.len();
+    let mut cells = vec![Cell::Empty; side * side];
+
+    for (row, &field) in moves.iter().enumerate() {
+        // indexed like the board, so x * side + y with y+ up, but the first move is at the top
+        cells[field * side + (side - 1 - row)] = board[field];
+    }
+
+    cells
+}
@@ -0,0 +1,83 @@
+use {
+    super::render::Frame,
+    std::{
+        fs, io,
+        path::{Path, PathBuf},
+        time::Duration,
+    },
+};
+
+/// Saves every frame into a directory, with animations advancing by the same step between each
+/// pair of frames, so they can be put together into a smooth video or GIF afterwards.
+#[derive(Debug)]
+pub struct Recording {
+    dir: PathBuf,
+    step: Duration,
+    frames: usize,
+}
+
+impl Recording {
+    /// Starts recording into the given directory, creating it if needed, at the given frames per
+    /// second.
+    pub fn new(dir: &Path, fps: u32) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            step: Duration::from_secs(1) / fps.max(1),
+            frames: 0,
+        })
+    }
+
+    /// How much time passes between two frames.
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Writes the given frame as the next one, numbered so the files sort in order.
+    pub fn save(&mut self, frame: &Frame) -> io::Result<()> {
+        let path = self.dir.join(format!("frame-{:05}.ppm", self.frames));
+        fs::write(path, frame.to_ppm())?;
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_numbered_in_order() {
+        let dir = std::env::temp_dir().join(format!("tic-tac-gpu-frames-{}", std::process::id()));
+        let mut recording = Recording::new(&dir, 30).unwrap();
+        assert_eq!(recording.step(), Duration::from_secs(1) / 30);
+
+        let frame = Frame {
+            width: 1,
+            height: 1,
+            rgba: vec![255, 0, 0, 255],
+        };
+        for _ in 0..3 {
+            recording.save(&frame).unwrap();
+        }
+        let mut names: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        let ppm = fs::read(dir.join("frame-00002.ppm")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            names,
+            ["frame-00000.ppm", "frame-00001.ppm", "frame-00002.ppm"]
+        );
+        assert_eq!(ppm, frame.to_ppm());
+        // no frames per second at all would never advance
+        assert_eq!(
+            Recording::new(&dir, 0).unwrap().step(),
+            Duration::from_secs(1)
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,25 @@
 use {
-    super::Cell,
-    std::{f32::consts::PI, mem, ops::Range},
+    super::{
+        ai::MoveQuality,
+        button::ButtonState,
+        game::{Cell, MAX_BOARD_SIZE},
+        menu::{MenuOption, BAR_HALF_SIZE},
+        score::Score,
+        theme::Theme,
+        AppEvent,
+    },
+    rand::Rng,
+    std::{
+        array,
+        borrow::Cow,
+        f32::consts::PI,
+        fmt, mem,
+        num::NonZeroU64,
+        ops::Range,
+        str::FromStr,
+        sync::mpsc,
+        time::{Duration, Instant},
+    },
     thiserror::Error,
     ultraviolet::{rotor::Rotor2, vec::Vec2},
     wgpu::util::DeviceExt,
@@ -8,7 +27,7 @@ use {
         dpi,
         event::{Event, WindowEvent},
         event_loop::ControlFlow,
-        window::Window,
+        window::{Window, WindowId},
     },
 };
 
@@ -16,10 +35,46 @@ use {
 pub enum BackendError {
     #[error("Could not find any suitable GPU adapter")]
     NoSuitableAdapter,
+    #[error("There is no adapter {0} able to draw onto the window, see --list-adapters")]
+    NoSuchAdapter(usize),
+    #[error("The adapter doesn't support any format to draw onto the window with")]
+    NoSurfaceFormat,
     #[error("Could not request device: {0}")]
     RequestDeviceError(#[from] wgpu::RequestDeviceError),
     #[error("Unable to create WebGL/WebGPU surface: {0}")]
     CreateSurfaceError(#[from] wgpu::CreateSurfaceError),
+    #[error("Shader or render pipeline is invalid: {0}")]
+    ShaderError(String),
+}
+
+#[derive(Debug, Error)]
+#[error("Unknown limits mode {0:?}, expected one of `strict`, `adapter` or `balanced`")]
+pub struct UnknownLimitsModeError(String);
+
+/// Which limits the device is requested with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LimitsMode {
+    /// Exactly what the game needs and nothing more, as hand-tuned in `LIMITS`. Frames are only
+    /// drawn up to 2048 pixels wide and tall then, and stretched over larger windows.
+    Strict,
+    /// Everything the adapter reports it can do.
+    Adapter,
+    /// The downlevel defaults, raised wherever the game needs more, with textures as large as the
+    /// adapter allows so the window can be too.
+    Balanced,
+}
+
+impl FromStr for LimitsMode {
+    type Err = UnknownLimitsModeError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "strict" => Ok(Self::Strict),
+            "adapter" => Ok(Self::Adapter),
+            "balanced" => Ok(Self::Balanced),
+            _ => Err(UnknownLimitsModeError(source.to_string())),
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -30,6 +85,18 @@ enum BackendDrawError {
     SurfaceTextureError(wgpu::SurfaceError),
 }
 
+#[derive(Debug, Error)]
+pub enum CaptureError {
+    #[error("Nothing is being drawn right now")]
+    NothingDrawn,
+    #[error("Frames in {0:?} can't be captured")]
+    UnsupportedFormat(wgpu::TextureFormat),
+    #[error("A frame of {0}x{1} pixels is larger than the device can capture")]
+    TooLarge(u32, u32),
+    #[error("Could not read the frame back from the GPU: {0}")]
+    BufferAsyncError(#[from] wgpu::BufferAsyncError),
+}
+
 impl From<wgpu::SurfaceError> for BackendDrawError {
     fn from(source: wgpu::SurfaceError) -> Self {
         match source {
@@ -39,15 +106,100 @@ impl From<wgpu::SurfaceError> for BackendDrawError {
     }
 }
 
+/// A single frame as it was drawn, read back from the GPU.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    /// 4 bytes per pixel, row by row from the top left. Transparency is lost, so the alpha
+    /// channel is always opaque.
+    pub rgba: Vec<u8>,
+}
+
+impl Frame {
+    /// Encodes the frame as binary PPM, which pretty much any image viewer can open.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        ppm.extend(self.rgba.chunks_exact(4).flat_map(|pixel| &pixel[..3]));
+        ppm
+    }
+}
+
+/// How much is drawn of each shape, for finding out why something doesn't show up.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// In the order they're drawn in.
+    pub shapes: Vec<ShapeStats>,
+    /// How many draw calls the last frame drawn onto the window took.
+    pub draw_calls: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ShapeStats {
+    pub name: &'static str,
+    pub vertices: u32,
+    pub indices: u32,
+    /// Instances which are drawn, including ones still fading out or being revealed.
+    pub active_instances: u32,
+}
+
+impl fmt::Display for RenderStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} draw calls in the last frame", self.draw_calls)?;
+        for shape in &self.shapes {
+            write!(
+                f,
+                "\n{}: {} vertices, {} indices, {} active instances",
+                shape.name, shape.vertices, shape.indices, shape.active_instances
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Opacity of the marks previewing a move which isn't placed yet.
+const GHOST_ALPHA: f32 = 0.25;
+
+/// How many wins are tallied per faction at most. There's no room for more in the corner.
+const TALLY_CAP: u32 = 10;
+
+/// How opaque the background is when rendering onto a transparent window.
+const TRANSPARENT_BACKGROUND_ALPHA: f64 = 0.5;
+
+/// Size of the push constants, if they're supported. Only holds the tint of the shape being drawn.
+const PUSH_CONSTANT_SIZE: u32 = mem::size_of::<[f32; 4]>() as u32;
+
+/// How many samples each pixel gets, so the edges of the marks aren't jagged. Adapters which
+/// can't do that for the surface format get only one, see `sample_count`.
+const SAMPLE_COUNT: u32 = 4;
+
+/// How long removed marks take to shrink until they vanish.
+const FADE_OUT_DURATION: Duration = Duration::from_millis(150);
+
+/// How long placed marks take to grow from nothing to their full size.
+const FADE_IN_DURATION: Duration = Duration::from_millis(150);
+
+/// How long it takes to draw a mark stroke by stroke, see [`Renderer::reveal_mark`].
+const REVEAL_DURATION: Duration = Duration::from_millis(200);
+
+/// How long one breath of the grid takes while waiting for the user, see [`Renderer::set_idle`].
+const BREATH_PERIOD: Duration = Duration::from_secs(4);
+
+/// How much the grid grows and shrinks while breathing, relative to its size.
+const BREATH_AMPLITUDE: f32 = 0.015;
+
 /// Limits tailored to this simple tic tac toe game.
 const LIMITS: wgpu::Limits = wgpu::Limits {
     max_texture_dimension_1d: 0,
-    max_texture_dimension_2d: 0,
+    // only the multisampled texture the size of the window, and whatever is captured, which are
+    // covered up to a window of 2048 times 2048 pixels like on any adapter
+    max_texture_dimension_2d: 2048,
     max_texture_dimension_3d: 0,
-    max_texture_array_layers: 0,
+    max_texture_array_layers: 1,
 
-    max_bind_groups: 0,
-    max_bindings_per_bind_group: 0,
+    // only the uniforms
+    max_bind_groups: 1,
+    max_bindings_per_bind_group: 1,
 
     max_dynamic_uniform_buffers_per_pipeline_layout: 0,
     max_dynamic_storage_buffers_per_pipeline_layout: 0,
@@ -56,19 +208,32 @@ const LIMITS: wgpu::Limits = wgpu::Limits {
     max_samplers_per_shader_stage: 0,
     max_storage_buffers_per_shader_stage: 0,
     max_storage_textures_per_shader_stage: 0,
-    max_uniform_buffers_per_shader_stage: 0,
+    max_uniform_buffers_per_shader_stage: 1,
 
-    max_uniform_buffer_binding_size: 0,
+    max_uniform_buffer_binding_size: mem::size_of::<Uniforms>() as u32,
     max_storage_buffer_binding_size: 0,
 
     // one for the vertices themselves, one for the instances
     max_vertex_buffers: 2,
-    // position + color of vertices + position of instances
-    max_vertex_attributes: 3,
-    max_vertex_buffer_array_stride: mem::size_of::<Vertex>() as u32,
-    // I'd be a lot happier if this could be kept in sync with CIRCLE_VERTEX_COUNT, but welp
-    max_buffer_size: mem::size_of::<Vertex>() as u64 * 48,
+    // position + color of vertices + position + scale + color + rotation of instances
+    max_vertex_attributes: 6,
+    max_vertex_buffer_array_stride: if mem::size_of::<Vertex>() > mem::size_of::<Instance>() {
+        mem::size_of::<Vertex>() as u32
+    } else {
+        mem::size_of::<Instance>() as u32
+    },
+    // I'd be a lot happier if this could be kept in sync with CIRCLE_VERTEX_COUNT, but welp. The
+    // instances of the move list on the largest board are the other candidate for the largest
+    // buffer.
+    max_buffer_size: if mem::size_of::<Vertex>() * 48
+        > mem::size_of::<Instance>() * MAX_BOARD_SIZE.pow(4)
+    {
+        (mem::size_of::<Vertex>() * 48) as u64
+    } else {
+        (mem::size_of::<Instance>() * MAX_BOARD_SIZE.pow(4)) as u64
+    },
 
+    // raised to PUSH_CONSTANT_SIZE if push constants are supported
     max_push_constant_size: 0,
     min_uniform_buffer_offset_alignment: !0,
     min_storage_buffer_offset_alignment: !0,
@@ -82,20 +247,275 @@ const LIMITS: wgpu::Limits = wgpu::Limits {
     max_compute_workgroups_per_dimension: 0,
 };
 
+/// Everything the game needs from whatever shows it. Drawing itself happens in reaction to
+/// [`Event::RedrawRequested`], hence the [`HandleEvent`](super::HandleEvent) supertrait.
+pub trait Renderer: super::HandleEvent {
+    /// Updates which shapes are visible on the screen.
+    fn update_instances(&mut self, board: &[Cell]);
+
+    /// Shows the marks on the given cells dimmer than the others, e.g. for a handicap which was
+    /// placed before the game started.
+    fn dim_marks(&mut self, dimmed: &[bool]);
+
+    /// Moves and turns the mark on each cell away from the center of the cell by the jitter with
+    /// the same index. Ghosts stay where they are.
+    fn jitter_marks(&mut self, jitter: &[Jitter]);
+
+    /// Updates which ghost marks are visible. Ghosts are only previews and are drawn translucent
+    /// over the actual board.
+    fn update_ghosts(&mut self, ghosts: &[Cell]);
+
+    /// Colors the background of each cell by how good marking it would be for the user: Green for
+    /// positive scores, red for negative ones, something neutral for zero. Cells without a score
+    /// stay uncolored.
+    fn update_evaluation(&mut self, scores: &[Option<i32>]);
+
+    /// Colors the background of each marked cell by how good the move marking it was: Green for
+    /// optimal, yellow for suboptimal, red for blunders. Cells without a rating stay uncolored.
+    fn update_analysis(&mut self, qualities: &[Option<MoveQuality>]);
+
+    /// Shows each cell as a button in the given state. Not calling this at all leaves the cells
+    /// without any background.
+    fn update_buttons(&mut self, states: &[ButtonState]);
+
+    /// Returns whether any animation is still running, so more frames need to be drawn.
+    fn is_animating(&self) -> bool;
+
+    /// Highlights the given cell on top of everything on the board, or nothing for `None`.
+    fn update_highlight(&mut self, cell: Option<usize>);
+
+    /// Lights up the background of the given cells, meant for the line which won the game. An
+    /// empty slice lights up nothing.
+    fn update_win_line(&mut self, cells: &[usize]);
+
+    /// Outlines the given cell as the one a click or enter would mark, or nothing for `None`. An
+    /// outline which is taken away shrinks until it vanishes.
+    fn set_cursor(&mut self, cell: Option<usize>);
+
+    /// Lets the mark on the given cell appear stroke by stroke over a moment, as if it was being
+    /// drawn, instead of all at once. Has to be called right after `update_instances` placed it.
+    /// A reveal still going on is completed by the next `update_instances`.
+    fn reveal_mark(&mut self, cell: usize);
+
+    /// Shows how much of the AI's thinking delay has elapsed, from 0 to 1, as up to three dots
+    /// filling in. `None` hides all of them.
+    fn update_thinking(&mut self, progress: Option<f32>);
+
+    /// Shows the wins of each faction in the top left corner, as a small mark followed by one
+    /// tally per win, up to [`TALLY_CAP`]. `None` hides the score.
+    fn update_score(&mut self, score: Option<Score>);
+
+    /// Shows the pause menu over everything else with the given option selected, or hides it for
+    /// `None`.
+    fn update_menu(&mut self, selected: Option<MenuOption>);
+
+    /// Shows or hides the grid, without having to rebuild it.
+    fn set_grid_visible(&mut self, visible: bool);
+
+    /// Shows a small dot on every spot any shape has an instance on, whether it's drawn or not, to
+    /// see how instances are laid out compared to where things end up.
+    fn set_instance_dots_visible(&mut self, visible: bool);
+
+    /// Lets the grid slowly grow and shrink a tiny bit while the game waits for the user, so the
+    /// board doesn't look frozen. Keeps animating as long as it's set.
+    fn set_idle(&mut self, idle: bool);
+
+    /// Sets a new background color, overwriting the previous one.
+    fn set_background(&mut self, color: wgpu::Color);
+
+    /// Recolors the grid and the marks to fit the given theme. The background is left alone,
+    /// since it also depends on the game, see `set_background`.
+    fn set_theme(&mut self, theme: &Theme);
+
+    /// Draws the current state once more, but into memory instead of onto the window.
+    fn capture(&mut self) -> Result<Frame, CaptureError>;
+
+    /// Counts what each shape consists of and how much of it is drawn.
+    fn stats(&self) -> RenderStats;
+
+    /// Describes the adapter drawing, or `None` if nothing is drawn at all.
+    fn adapter(&self) -> Option<wgpu::AdapterInfo>;
+
+    /// Moves the time animations see forward by exactly the given step, so captured frames are
+    /// evenly spaced no matter how long each took. From then on, animations only progress
+    /// through further calls, not on their own anymore.
+    fn advance_clock(&mut self, step: Duration);
+
+    /// Zooms and moves the whole board as described by the given view.
+    fn set_view(&mut self, view: View);
+}
+
+/// Where animations take the current time from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Clock {
+    /// The wall clock, so animations play as fast as they're meant to.
+    Real,
+    /// Stands still at the given time until advanced, see [`Renderer::advance_clock`].
+    Fixed(Instant),
+}
+
+impl Clock {
+    fn now(self) -> Instant {
+        match self {
+            Self::Real => Instant::now(),
+            Self::Fixed(now) => now,
+        }
+    }
+}
+
+/// How the board is zoomed and moved on the screen. Everything is in clip space, where the
+/// window ranges from -1 to 1 on both axes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct View {
+    pub scale: f32,
+    pub offset: [f32; 2],
+    /// How many quarter turns counter-clockwise the board is rotated by, from 0 to 3. Applied
+    /// before scaling and moving.
+    pub quarter_turns: u8,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            offset: [0.0, 0.0],
+            quarter_turns: 0,
+        }
+    }
+}
+
+impl View {
+    /// Maps a position on the screen back to the position on the board which ends up there,
+    /// undoing what the vertex shader does.
+    pub fn to_board(self, screen: [f32; 2]) -> [f32; 2] {
+        let mut board = Vec2::new(
+            (screen[0] - self.offset[0]) / self.scale,
+            (screen[1] - self.offset[1]) / self.scale,
+        );
+        self.rotor().reversed().rotate_vec(&mut board);
+        [board.x, board.y]
+    }
+
+    /// The same view, just turned by another quarter counter-clockwise.
+    pub fn turned(self) -> Self {
+        Self {
+            quarter_turns: (self.quarter_turns + 1) % 4,
+            ..self
+        }
+    }
+
+    fn rotor(self) -> Rotor2 {
+        Rotor2::from_angle(f32::from(self.quarter_turns) * PI / 2.0)
+    }
+}
+
+/// How far a mark is off from sitting perfectly in the center of its cell, so it looks placed by
+/// hand. See [`Renderer::jitter_marks`].
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Jitter {
+    /// Added to the center of the cell, in board space.
+    pub offset: [f32; 2],
+    /// Counterclockwise, in radians.
+    pub rotation: f32,
+}
+
+impl Jitter {
+    /// Rolls a jitter small enough that the mark still stays well inside its cell.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self {
+            offset: [rng.gen_range(-0.03..=0.03), rng.gen_range(-0.03..=0.03)],
+            rotation: rng.gen_range(-0.15..=0.15),
+        }
+    }
+}
+
+/// What a backend shows, for a board with the given number of fields per side.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// The board itself, one cell per field.
+    Board(usize),
+    /// All moves of the game, one row per move from the top down. Each row has one column per
+    /// field, the move's mark is placed in the column of the field it was placed on.
+    MoveList(usize),
+}
+
+impl Layout {
+    /// How many cells there are per side.
+    fn side(self) -> usize {
+        match self {
+            Self::Board(board_size) => board_size,
+            Self::MoveList(board_size) => board_size * board_size,
+        }
+    }
+}
+
 pub struct Backend {
-    _adapter: wgpu::Adapter,
+    adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
-    surface: wgpu::Surface,
+    // None when drawing offscreen, see headless
+    surface: Option<wgpu::Surface>,
     pipeline: wgpu::RenderPipeline,
+    // like pipeline, but without culling, see Shape::double_sided
+    double_sided_pipeline: wgpu::RenderPipeline,
+    // how many samples the pipelines expect per pixel, SAMPLE_COUNT if the adapter can do that
+    sample_count: u32,
+    // everything the shader gets apart from the vertex buffers, see Uniforms
+    uniforms: Uniforms,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    view: View,
+    // whether shape tints are pushed as constants, otherwise they're baked into the instances
+    push_constants: bool,
     preferred_format: wgpu::TextureFormat,
-
+    present_mode: wgpu::PresentMode,
+    alpha_mode: wgpu::CompositeAlphaMode,
+
+    // cell backgrounds making cells look like buttons, if wanted
+    buttons: Shape,
+    // cell backgrounds showing how good each move is for the user
+    evaluation: Shape,
+    // cell backgrounds rating each move of a finished game
+    analysis: Shape,
+    win_line: Shape,
     grid: Shape,
     cross: Shape,
     ring: Shape,
-
+    // faint previews of marks which aren't placed yet
+    ghost_cross: Shape,
+    ghost_ring: Shape,
+    // marks a single cell, e.g. the move which lost the game
+    highlight: Shape,
+    cursor: Shape,
+    // progress dots while the AI is thinking
+    thinking: Shape,
+    // the session score in the top left corner, see update_score
+    score_cross: Shape,
+    score_ring: Shape,
+    tallies: Shape,
+    // the pause menu, darkening everything below it
+    menu_backdrop: Shape,
+    menu_options: Shape,
+    // debug overlay marking where instances are laid out, see set_instance_dots_visible
+    instance_dots: Shape,
+
+    // events for any other window are none of our business, and without a window all are
+    window_id: Option<WindowId>,
     window_size: dpi::PhysicalSize<u32>,
+    // what the surface was last configured for, None if it wasn't yet
+    surface_size: Option<dpi::PhysicalSize<u32>>,
+    // drawn into first, then resolved into the frame, recreated along with the surface. Stays None
+    // with only one sample per pixel, everything is drawn right into the frame then.
+    multisampled: Option<wgpu::TextureView>,
     background: wgpu::Color,
+    // how much of the background shines through, only below 1 on transparent windows
+    background_alpha: f64,
+    // whether anything visible changed since the last presented frame
+    dirty: bool,
+    // where animations take their time from
+    clock: Clock,
+    // how many draw calls the last frame took, see RenderStats
+    draw_calls: u32,
 }
 
 impl Backend {
@@ -104,8 +524,23 @@ impl Backend {
     /// # Safety
     ///
     /// The given [`winit::window::Window`] must live as long as the returned backend.
+    ///
+    /// If `transparent` is set and the surface supports it, the background is rendered
+    /// translucent. The window has to be created transparent as well for that.
+    ///
+    /// `adapter_index` picks the adapter by its index in [`Backend::list_adapters`]. If `None`,
+    /// wgpu picks one itself.
+    ///
+    /// `layout` decides where the instances of all shapes are, and with that what
+    /// [`Renderer::update_instances`] expects.
     #[allow(unused_unsafe)]
-    pub async unsafe fn new(window: &Window) -> Result<Self, BackendError> {
+    pub async unsafe fn new(
+        window: &Window,
+        transparent: bool,
+        adapter_index: Option<usize>,
+        limits_mode: LimitsMode,
+        layout: Layout,
+    ) -> Result<Self, BackendError> {
         // The instance is the main starting point for everything in wgpu, there is no need to
         // "keep it alive" though (see the docs). We also need it only for surface and adapter
         // creation
@@ -115,18 +550,119 @@ impl Backend {
 
         // An adapter can be seen as a virtual handle to a physical graphics card or whatever that
         // might be
-        let adapter = instance
+        let adapter = match adapter_index {
+            Some(index) => instance
+                .enumerate_adapters(wgpu::Backends::all())
+                .nth(index)
+                .filter(|adapter| adapter.is_surface_supported(&surface))
+                .ok_or(BackendError::NoSuchAdapter(index))?,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::LowPower,
+                    force_fallback_adapter: false,
+                    compatible_surface: Some(&surface),
+                })
+                .await
+                .ok_or(BackendError::NoSuitableAdapter)?,
+        };
+        let info = adapter.get_info();
+        log::info!("Using adapter {} ({:?})", info.name, info.backend);
+
+        let caps = surface.get_capabilities(&adapter);
+        let surface_format = select_format(&caps.formats).ok_or(BackendError::NoSurfaceFormat)?;
+        let present_mode = select_present_mode(&caps.present_modes);
+        log::info!(
+            "Using surface format {:?}, present mode {:?}",
+            surface_format,
+            present_mode
+        );
+
+        let alpha_mode = select_alpha_mode(&caps.alpha_modes, transparent);
+        let background_alpha = match alpha_mode {
+            wgpu::CompositeAlphaMode::PreMultiplied | wgpu::CompositeAlphaMode::PostMultiplied => {
+                TRANSPARENT_BACKGROUND_ALPHA
+            }
+            _ => {
+                if transparent {
+                    log::warn!(
+                        "Transparency requested, but the surface only supports {:?}",
+                        caps.alpha_modes
+                    );
+                }
+                1.0
+            }
+        };
+        log::info!("Using alpha mode {:?}", alpha_mode);
+
+        let mut backend = Self::on_adapter(adapter, surface_format, limits_mode, layout).await?;
+        backend.surface = Some(surface);
+        backend.present_mode = present_mode;
+        backend.alpha_mode = alpha_mode;
+        backend.background_alpha = background_alpha;
+        backend.window_id = Some(window.id());
+        // The surface needs to be configured to be ready to be drawn onto, but that's only done
+        // right before the first draw. Until then, the window might still be moved onto another
+        // monitor with a different scale factor, making this size stale.
+        backend.window_size = window.inner_size();
+        Ok(backend)
+    }
+
+    /// Creates a backend drawing into a texture of the given size instead of onto a window, only
+    /// ever seen through [`Renderer::capture`].
+    #[cfg(test)]
+    async fn headless(
+        size: dpi::PhysicalSize<u32>,
+        limits_mode: LimitsMode,
+        layout: Layout,
+    ) -> Result<Self, BackendError> {
+        let mut backend = Self::on_adapter(
+            Self::fallback_adapter().await?,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            limits_mode,
+            layout,
+        )
+        .await?;
+        backend.window_size = size;
+        Ok(backend)
+    }
+
+    // The software adapter, so tests behave the same everywhere.
+    #[cfg(test)]
+    async fn fallback_adapter() -> Result<wgpu::Adapter, BackendError> {
+        wgpu::Instance::default()
             .request_adapter(&wgpu::RequestAdapterOptions {
                 power_preference: wgpu::PowerPreference::LowPower,
-                force_fallback_adapter: false,
-                compatible_surface: Some(&surface),
+                force_fallback_adapter: true,
+                compatible_surface: None,
             })
             .await
-            .ok_or(BackendError::NoSuitableAdapter)?;
+            .ok_or(BackendError::NoSuitableAdapter)
+    }
 
-        let caps = surface.get_capabilities(&adapter);
-        let surface_format = caps.formats[0]; // won't fail as no adapter can be found then
+    // Everything about creating a backend which doesn't depend on the window. Draws nowhere until
+    // the surface is set.
+    async fn on_adapter(
+        adapter: wgpu::Adapter,
+        surface_format: wgpu::TextureFormat,
+        limits_mode: LimitsMode,
+        layout: Layout,
+    ) -> Result<Self, BackendError> {
+        // Push constants are a native-only feature, so we can only use them if the adapter
+        // actually supports them.
+        let push_constants = adapter.features().contains(wgpu::Features::PUSH_CONSTANTS);
+        log::info!("Push constants supported: {}", push_constants);
+        Self::on_adapter_with(adapter, push_constants, surface_format, limits_mode, layout).await
+    }
 
+    // Same as `on_adapter`, but with push constants only used if told so, which the adapter has
+    // to support then.
+    async fn on_adapter_with(
+        adapter: wgpu::Adapter,
+        push_constants: bool,
+        surface_format: wgpu::TextureFormat,
+        limits_mode: LimitsMode,
+        layout: Layout,
+    ) -> Result<Self, BackendError> {
         // The device however refers to one specific API of a such graphics card. So if your card
         // supports, let's say, Vulkan and OpenGL ES, an adapter would refer to the card itself
         // while the device might refer to the Vulkan API of this card.
@@ -134,32 +670,38 @@ impl Backend {
         // And about the queue, you can imagine it as a conveyor belt which "slowly" flows towards
         // the GPU while trying to use space as useful as possible. That conveyor belt can contain
         // textures, cool buffers, but most importantly *sparkles* render commands *sparkles*.
+        let (features, needed) = if push_constants {
+            let limits = wgpu::Limits {
+                max_push_constant_size: PUSH_CONSTANT_SIZE,
+                ..LIMITS
+            };
+            (wgpu::Features::PUSH_CONSTANTS, limits)
+        } else {
+            (wgpu::Features::empty(), LIMITS)
+        };
+        // LIMITS is as small as it gets, which is nice as long as nothing is added that needs
+        // more. The other modes leave more room for that, and for windows larger than the
+        // downlevel defaults allow.
+        let limits = match limits_mode {
+            LimitsMode::Strict => needed,
+            LimitsMode::Adapter => adapter.limits(),
+            LimitsMode::Balanced => wgpu::Limits {
+                max_texture_dimension_2d: adapter.limits().max_texture_dimension_2d,
+                ..raise_limits(wgpu::Limits::downlevel_defaults(), &needed)
+            },
+        };
+        log::info!("Using {:?} device limits", limits_mode);
+        log::debug!("Device limits: {:?}", limits);
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: None,
-                    features: wgpu::Features::empty(),
-                    limits: LIMITS,
+                    features,
+                    limits,
                 },
                 None,
             )
             .await?;
-        // Generates an underlying structure for the surface to be ready to be drawn onto. If you
-        // don't do that, prepare for panics. I don't know why wgpu does not require this already
-        // on setup though.
-        let window_size = window.inner_size();
-        surface.configure(
-            &device,
-            &wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: surface_format,
-                view_formats: Vec::new(),
-                width: window_size.width,
-                height: window_size.height,
-                present_mode: wgpu::PresentMode::Fifo,
-                alpha_mode: wgpu::CompositeAlphaMode::Auto,
-            },
-        );
 
         // Shaders are small programs running on the GPU. In normal applications, you usually only
         // use:
@@ -173,21 +715,271 @@ impl Backend {
         //
         // The only other shader types I know are compute and geometry shaders, but they are for
         // more special cases. uwu.
-        let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+        //
+        // The shader is only compiled at runtime, so if it's broken, we only know now. Errors
+        // inside an error scope are caught instead of panicking, so we can report them properly.
+        //
+        // Declaring push constants without the feature is an error already, so where the tint
+        // comes from is prepended depending on that.
+        let tint_source = if push_constants {
+            include_str!("tint_push_constants.wgsl")
+        } else {
+            include_str!("tint_baked.wgsl")
+        };
+        // Bind groups are how the shader gets to see anything besides the vertex buffers. Here
+        // it's only one uniform buffer, which holds the same value for all vertices of a draw.
+        let uniform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(mem::size_of::<Uniforms>() as u64),
+                    },
+                    count: None,
+                }],
+            });
+        let source = tint_source.to_string() + include_str!("shader.wgsl");
+        let sample_count = sample_count(&adapter, surface_format);
+        log::info!("Using {} samples per pixel", sample_count);
+        let (pipeline, double_sided_pipeline) = Self::create_pipelines(
+            &device,
+            &source,
+            &uniform_bind_group_layout,
+            surface_format,
+            push_constants,
+            sample_count,
+        )
+        .await?;
+
+        let view = View::default();
+        let uniforms = Uniforms::default();
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::bytes_of(&uniforms),
+            // rewritten whenever any uniform changes, and read back in tests
+            usage: wgpu::BufferUsages::UNIFORM
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        // just enough to start with, these and the instance dots are laid out for the actual board
+        // by rebuild_shapes below
+        let CellShapes {
+            buttons,
+            evaluation,
+            analysis,
+            win_line,
+            grid,
+            cursor,
+            cross,
+            ring,
+            ghost_cross,
+            ghost_ring,
+            highlight,
+        } = CellShapes::new(&device, 1);
+        let clock = Clock::Real;
+        let thinking = Shape::thinking_dots(&device);
+        let score_cross = Shape::cross(&device, 0.16, &[Instance::new([-0.94, 0.94])]);
+        let score_ring = Shape::ring(&device, 0.16, &[Instance::new([-0.94, 0.86])]);
+        let tallies = Shape::tallies(&device);
+        // large enough to cover the window even when zoomed out
+        let mut menu_backdrop = Shape::rectangle(&device, [50.0, 50.0], &[Instance::new([0.0; 2])]);
+        menu_backdrop.set_tint([0.0, 0.0, 0.0, 0.6]);
+        let menu_options = Shape::rectangle(
+            &device,
+            BAR_HALF_SIZE,
+            &MenuOption::ALL.map(|option| Instance::new([0.0, option.center_y()])),
+        );
+        let instance_dots = Shape::rectangle(&device, [0.008, 0.008], &[Instance::new([0.0; 2])]);
+
+        let mut backend = Self {
+            buttons,
+            evaluation,
+            analysis,
+            win_line,
+            grid,
+            cursor,
+            cross,
+            ring,
+            ghost_cross,
+            ghost_ring,
+            highlight,
+            thinking,
+            score_cross,
+            score_ring,
+            tallies,
+            menu_backdrop,
+            menu_options,
+            instance_dots,
+            adapter,
+            device,
+            preferred_format: surface_format,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            queue,
+            surface: None,
+            pipeline,
+            double_sided_pipeline,
+            sample_count,
+            uniforms,
+            uniform_buffer,
+            uniform_bind_group,
+            view,
+            push_constants,
+            window_id: None,
+            window_size: dpi::PhysicalSize::new(0, 0),
+            surface_size: None,
+            multisampled: None,
+            background: Theme::DARK.background,
+            background_alpha: 1.0,
+            dirty: true,
+            clock,
+            draw_calls: 0,
+        };
+        let side = layout.side();
+        backend.rebuild_shapes(side, &Theme::DARK);
+        // Might seem strange, but no instances are activated by default on any shape. But since
+        // the grid should be visible all the time and it only has one instance, we activate it
+        // now. The move list has no grid, but bands in the background instead, one per board
+        // column, so the columns are easier to tell apart.
+        let now = backend.clock.now();
+        match layout {
+            Layout::Board(_) => {
+                backend.grid.update_instances(std::iter::once(true), now);
+            }
+            Layout::MoveList(board_size) => {
+                backend.evaluation.update_colors((0..side * side).map(|i| {
+                    let band = i / side / board_size;
+                    let brightness = if band.is_multiple_of(2) { 0.2 } else { 0.12 };
+                    [brightness, brightness, brightness, 1.0]
+                }));
+                backend
+                    .evaluation
+                    .update_instances((0..side * side).map(|_| true), now);
+            }
+        }
+
+        Ok(backend)
+    }
+
+    /// Recreates every shape laid out on the cells for a board with the given number of cells per
+    /// side, e.g. after switching to a larger board, and tints them by the given theme.
+    ///
+    /// The grid stays as visible as it was. Marks stay on their cells if the number of cells
+    /// didn't change, otherwise they're all gone until the next [`Renderer::update_instances`].
+    /// Cell backgrounds, ghosts, the highlight and the cursor always have to be set again. The
+    /// instance dots move onto the new cells, and stay as visible as they were.
+    pub fn rebuild_shapes(&mut self, board_size: usize, theme: &Theme) {
+        let now = self.clock.now();
+        let grid_visible = self.grid.enabled.clone();
+        let crosses = self.cross.enabled.clone();
+        let rings = self.ring.enabled.clone();
+
+        let shapes = CellShapes::new(&self.device, board_size);
+        self.buttons = shapes.buttons;
+        self.evaluation = shapes.evaluation;
+        self.analysis = shapes.analysis;
+        self.win_line = shapes.win_line;
+        self.grid = shapes.grid;
+        self.cursor = shapes.cursor;
+        self.cross = shapes.cross;
+        self.ring = shapes.ring;
+        self.ghost_cross = shapes.ghost_cross;
+        self.ghost_ring = shapes.ghost_ring;
+        self.highlight = shapes.highlight;
+
+        self.grid.update_instances(grid_visible.into_iter(), now);
+        if crosses.len() == self.cross.instances.len() {
+            // not fading in, so the marks which stay don't grow from nothing again
+            for (marks, enabled) in [(&mut self.cross, crosses), (&mut self.ring, rings)] {
+                marks.fades_in = false;
+                marks.update_instances(enabled.into_iter(), now);
+                marks.fades_in = true;
+            }
+        }
+
+        // every distinct spot any shape has an instance on, whether it's ever enabled or not
+        let mut spots: Vec<Instance> = Vec::new();
+        for shape in [
+            &self.buttons,
+            &self.evaluation,
+            &self.analysis,
+            &self.win_line,
+            &self.grid,
+            &self.cursor,
+            &self.cross,
+            &self.ring,
+            &self.highlight,
+            &self.thinking,
+            &self.score_cross,
+            &self.score_ring,
+            &self.tallies,
+            &self.menu_backdrop,
+            &self.menu_options,
+        ] {
+            for instance in &shape.instances {
+                if !spots.iter().any(|spot| spot.position == instance.position) {
+                    spots.push(Instance::new(instance.position));
+                }
+            }
+        }
+        let dots_visible = self.instance_dots.enabled.contains(&true);
+        self.instance_dots = Shape::rectangle(&self.device, [0.008, 0.008], &spots);
+        self.instance_dots.set_tint([1.0, 0.2, 0.8, 1.0]);
+        self.set_instance_dots_visible(dots_visible);
+
+        // also marks everything as dirty
+        self.set_theme(theme);
+    }
+
+    // Compiles the given shader and creates the pipelines drawing with it, the usual one and the
+    // double sided one. Anything wrong with the shader is reported instead of panicking.
+    async fn create_pipelines(
+        device: &wgpu::Device,
+        shader_source: &str,
+        uniform_bind_group_layout: &wgpu::BindGroupLayout,
+        surface_format: wgpu::TextureFormat,
+        push_constants: bool,
+        sample_count: u32,
+    ) -> Result<(wgpu::RenderPipeline, wgpu::RenderPipeline), BackendError> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("shader.wgsl"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(shader_source)),
+        });
 
         // Render pipelines and their layout define one "way" of how to handle rendering. "Way" as
         // in, one run to the GPU, through the vertex shader, fragment shader, and all the other
         // magic things that transform a few buffers to a wonderful pixel surface. You can
         // have multiple of them with ease, which allows you to have different shaders, rendering
         // modes and antialiasing methods.
-        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
+            bind_group_layouts: &[uniform_bind_group_layout],
+            push_constant_ranges: if push_constants {
+                &[wgpu::PushConstantRange {
+                    stages: wgpu::ShaderStages::VERTEX,
+                    range: 0..PUSH_CONSTANT_SIZE,
+                }]
+            } else {
+                &[]
+            },
         });
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let mut pipeline_descriptor = wgpu::RenderPipelineDescriptor {
             label: None,
-            layout: Some(&layout),
+            layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vertex_main",
@@ -214,16 +1006,37 @@ impl Backend {
                     },
                     // Instances are described by their name pretty well: They're used if you have a shape which is
                     // duplicated and also appears somewhere else in the scene, but modified in position, color,
-                    // rotation, scale, whatever you can imagine. Here we only define the position, no need for fancy
-                    // transformations.
+                    // rotation, scale, whatever you can imagine. Here we define the position, the scale for
+                    // animating marks as they vanish, the rotation for jittering marks, and a color
+                    // the vertex colors are multiplied with.
                     wgpu::VertexBufferLayout {
                         array_stride: mem::size_of::<Instance>() as wgpu::BufferAddress,
                         step_mode: wgpu::VertexStepMode::Instance,
-                        attributes: &[wgpu::VertexAttribute {
-                            format: wgpu::VertexFormat::Float32x2,
-                            offset: 0,
-                            shader_location: 2,
-                        }],
+                        attributes: &[
+                            wgpu::VertexAttribute {
+                                format: wgpu::VertexFormat::Float32x2,
+                                offset: 0,
+                                shader_location: 2,
+                            },
+                            wgpu::VertexAttribute {
+                                format: wgpu::VertexFormat::Float32,
+                                offset: bytemuck::offset_of!(Instance, scale)
+                                    as wgpu::BufferAddress,
+                                shader_location: 3,
+                            },
+                            wgpu::VertexAttribute {
+                                format: wgpu::VertexFormat::Float32x4,
+                                offset: bytemuck::offset_of!(Instance, color)
+                                    as wgpu::BufferAddress,
+                                shader_location: 4,
+                            },
+                            wgpu::VertexAttribute {
+                                format: wgpu::VertexFormat::Float32,
+                                offset: bytemuck::offset_of!(Instance, rotation)
+                                    as wgpu::BufferAddress,
+                                shader_location: 5,
+                            },
+                        ],
                     },
                 ],
             },
@@ -238,7 +1051,7 @@ impl Backend {
             },
             depth_stencil: None,
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -247,73 +1060,129 @@ impl Backend {
                 entry_point: "fragment_main",
                 targets: &[Some(wgpu::ColorTargetState {
                     format: surface_format,
-                    blend: None,
+                    // needed for the ghost marks, everything else is fully opaque anyways
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
                     write_mask: wgpu::ColorWrites::all(),
                 })],
             }),
             multiview: None,
-        });
+        };
+        let pipeline = device.create_render_pipeline(&pipeline_descriptor);
+        // Same as above, just with both sides of each triangle drawn, for shapes which are marked
+        // as double sided. Handy while bringing up a new shape whose windings aren't all right yet.
+        pipeline_descriptor.primitive.cull_mode = None;
+        let double_sided_pipeline = device.create_render_pipeline(&pipeline_descriptor);
+        if let Some(e) = device.pop_error_scope().await {
+            return Err(BackendError::ShaderError(e.to_string()));
+        }
 
-        let mut grid = Shape::grid(&device);
-        // Might seem strange, but no instances are activated by default on any shape. But since
-        // the grid should be visible all the time and it only has one instance, we activate it
-        // now.
-        grid.update_instances(std::iter::once(true));
-        let cross = Shape::cross(&device);
-        let ring = Shape::ring(&device);
+        Ok((pipeline, double_sided_pipeline))
+    }
 
-        Ok(Self {
-            grid,
-            cross,
-            ring,
-            _adapter: adapter,
-            device,
-            preferred_format: surface_format,
-            queue,
-            surface,
-            pipeline,
-            window_size,
-            background: wgpu::Color {
-                r: 0.04,
-                g: 0.09,
-                b: 0.09,
-                a: 1.0,
-            },
-        })
+    /// Lists all adapters on this system, in the order `new` counts them in.
+    pub fn list_adapters() -> Vec<wgpu::AdapterInfo> {
+        wgpu::Instance::default()
+            .enumerate_adapters(wgpu::Backends::all())
+            .map(|adapter| adapter.get_info())
+            .collect()
     }
 
     fn reconfigure_surface(&mut self) {
+        // the new surface has no frame yet, so there's nothing to skip
+        self.dirty = true;
+        self.surface_size = Some(self.window_size);
+        let size = self.frame_size();
         // reconfiguring the surface is enough for the underlying structures to be recalculated
-        self.surface.configure(
-            &self.device,
-            &wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: self.preferred_format,
-                view_formats: Vec::new(),
-                width: self.window_size.width,
-                height: self.window_size.height,
-                present_mode: wgpu::PresentMode::Fifo,
-                alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        if let Some(surface) = &self.surface {
+            surface.configure(
+                &self.device,
+                &wgpu::SurfaceConfiguration {
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    format: self.preferred_format,
+                    view_formats: Vec::new(),
+                    width: size.width,
+                    height: size.height,
+                    present_mode: self.present_mode,
+                    alpha_mode: self.alpha_mode,
+                },
+            );
+        }
+
+        if self.sample_count == 1 {
+            self.multisampled = None;
+            return;
+        }
+        let multisampled = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
             },
-        );
+            mip_level_count: 1,
+            sample_count: self.sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.preferred_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        self.multisampled = Some(multisampled.create_view(&wgpu::TextureViewDescriptor::default()));
+    }
+
+    // The size of the window, scaled down evenly until no side is larger than textures on this
+    // device can be. The surface is stretched back over the whole window then, blurry, but better
+    // than not drawing at all.
+    fn frame_size(&self) -> dpi::PhysicalSize<u32> {
+        let max = self.device.limits().max_texture_dimension_2d;
+        let (width, height) = (self.window_size.width, self.window_size.height);
+        let longest = width.max(height);
+        if longest <= max {
+            return self.window_size;
+        }
+        let scale = |side: u32| (u64::from(side) * u64::from(max) / u64::from(longest)).max(1);
+        dpi::PhysicalSize::new(scale(width) as u32, scale(height) as u32)
     }
 
     fn draw(&mut self) -> Result<(), BackendDrawError> {
+        // Minimized or not shown yet, and wgpu refuses to configure empty surfaces anyway.
+        if self.window_size.width == 0 || self.window_size.height == 0 {
+            return Ok(());
+        }
+        // Generates an underlying structure for the surface to be ready to be drawn onto. If you
+        // don't do that, prepare for panics. Only done here, so a burst of resizes reconfigures
+        // just once, and the first frame already has the size the window actually ended up with.
+        if self.surface_size != Some(self.window_size) {
+            self.reconfigure_surface();
+        }
+
+        // Redraws are requested generously, but presenting the very same frame again would only
+        // wake up the GPU for nothing.
+        if !self.dirty && !self.is_animating() {
+            return Ok(());
+        }
+
+        self.prepare_shapes();
+
         // We first have to tell the surface we want to have a fresh new frame to render to.
-        let next_frame_surface = self.surface.get_current_texture()?;
+        // Without one, the frame is drawn into a texture no one ever looks at.
+        let next_frame_surface = match &self.surface {
+            Some(surface) => Some(surface.get_current_texture()?),
+            None => None,
+        };
+        let offscreen;
+        let next_frame_texture = match &next_frame_surface {
+            Some(frame) => &frame.texture,
+            None => {
+                offscreen = self.create_frame_texture(wgpu::TextureUsages::RENDER_ATTACHMENT);
+                &offscreen
+            }
+        };
 
         // You can see a view as an actual "view" on the texture. It's possible to see something
-        // from a different angle or at another daylight. Here you have much less options though.
+        // from a different angle or at another daylight. Here you have much less options though,
+        // the defaults already see the whole frame in the format the surface was configured with.
         let next_frame_view =
-            next_frame_surface
-                .texture
-                .create_view(&wgpu::TextureViewDescriptor {
-                    label: None,
-                    // might seem pointless, but I want to ensure the format is Some
-                    format: Some(self.preferred_format),
-                    dimension: Some(wgpu::TextureViewDimension::D2),
-                    ..wgpu::TextureViewDescriptor::default()
-                });
+            next_frame_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
         // A command encoder is comparable to a recorder: You say some things and these things can
         // be heard in the same order later on. Same with the command encoder, just that it
@@ -323,33 +1192,7 @@ impl Backend {
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-
-        {
-            // Render passes are like one thing to do when rendering stuff on the screen. They take one
-            // "shape" (vertex buffers + one index buffer) , instance them as needed, and are then
-            // given to the encoder to take care of it.
-            // Note that the render pass is written into the encoder when dropping it, so we don't need
-            // to consume it or anything.
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &next_frame_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(self.background),
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: None,
-            });
-
-            render_pass.set_pipeline(&self.pipeline);
-
-            // Now that we finished the setup stuff, let's actually draw stuff.
-            self.grid.draw(&mut render_pass);
-            self.cross.draw(&mut render_pass);
-            self.ring.draw(&mut render_pass);
-        }
+        self.draw_calls = self.record_pass(&mut encoder, &next_frame_view);
 
         // Now that we're done recording what we want to do for now, we have to tell the
         // CommandEncoder to stop recording and place our resulting CommandBuffer on the conveyor
@@ -358,54 +1201,766 @@ impl Backend {
 
         // And finally, tell the surface texture for the next frame we're done with drawing to it,
         // it can "present" itself to the world now.
-        next_frame_surface.present();
+        if let Some(frame) = next_frame_surface {
+            frame.present();
+        }
+        self.dirty = false;
         Ok(())
     }
 
-    /// Updates which shapes are visible on the screen.
-    pub fn update_instances(&mut self, board: &[Cell]) {
-        self.ring
-            .update_instances(board.iter().map(|cell| matches!(cell, Cell::Ring)));
-        self.cross
-            .update_instances(board.iter().map(|cell| matches!(cell, Cell::Cross)));
-    }
-
-    /// Sets a new background color, overwriting the previous one.
-    pub fn set_background(&mut self, color: wgpu::Color) {
-        self.background = color;
+    // A texture a whole frame can be drawn into, just like the surface's.
+    fn create_frame_texture(&self, usage: wgpu::TextureUsages) -> wgpu::Texture {
+        let size = self.frame_size();
+        self.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.preferred_format,
+            usage,
+            view_formats: &[],
+        })
     }
-}
 
-impl super::HandleEvent for Backend {
-    fn handle(&mut self, event: Event<()>, flow: &mut ControlFlow) {
-        // handle only basic stuff such as quitting directly, forward everything else
-        match event {
-            // omitting window id checking since we only create one window
-            Event::WindowEvent { event, .. } => match event {
-                WindowEvent::CloseRequested => *flow = ControlFlow::Exit,
-                // window is unresizable, but who knows what great ideas a WM might have
-                WindowEvent::Resized(new_inner_size) => {
-                    self.window_size = new_inner_size;
-                    self.reconfigure_surface();
-                }
-                WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                    self.window_size = *new_inner_size;
-                    self.reconfigure_surface();
-                }
-                _ => (),
-            },
-            Event::RedrawRequested(_) => match self.draw() {
-                Err(BackendDrawError::SurfaceOutdated) => self.reconfigure_surface(),
-                Err(e) => {
-                    log::error!("Error while drawing: {}", e);
-                    *flow = ControlFlow::Exit;
-                }
-                _ => (),
-            },
-            _ => (),
+    fn prepare_shapes(&mut self) {
+        // Animations only need to be advanced right before they're seen. Any instances changed
+        // since the last frame need to arrive on the GPU before drawing. (The order doesn't
+        // matter here, only in draw_order.)
+        let now = self.clock.now();
+        let bake_tint = !self.push_constants;
+        let queue = &self.queue;
+        for shape in [
+            &mut self.buttons,
+            &mut self.evaluation,
+            &mut self.analysis,
+            &mut self.win_line,
+            &mut self.grid,
+            &mut self.cursor,
+            &mut self.cross,
+            &mut self.ring,
+            &mut self.ghost_cross,
+            &mut self.ghost_ring,
+            &mut self.highlight,
+            &mut self.thinking,
+            &mut self.score_cross,
+            &mut self.score_ring,
+            &mut self.tallies,
+            &mut self.menu_backdrop,
+            &mut self.menu_options,
+            &mut self.instance_dots,
+        ] {
+            shape.animate(now);
+            shape.upload_instances(queue, bake_tint);
         }
     }
-}
+
+    // Records drawing all shapes onto the given view into the encoder, which has to be as large as
+    // the surface was last configured. Returns how many draw calls were recorded.
+    fn record_pass(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) -> u32 {
+        // Everything is drawn with several samples per pixel if the adapter can, which are then
+        // averaged into the actual view.
+        let (target, resolve_target) = match &self.multisampled {
+            Some(multisampled) => (multisampled, Some(view)),
+            None => (view, None),
+        };
+
+        // Render passes are like one thing to do when rendering stuff on the screen. They take one
+        // "shape" (vertex buffers + one index buffer) , instance them as needed, and are then
+        // given to the encoder to take care of it.
+        // Note that the render pass is written into the encoder when dropping it, so we don't need
+        // to consume it or anything.
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(self.clear_color()),
+                    // only the resolved view is looked at afterwards, if there is one
+                    store: resolve_target.is_none(),
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        // the clear above still covers the whole window, drawing only the square in its center
+        let ([left, top], side) = letterbox(self.frame_size());
+        render_pass.set_viewport(left, top, side, side, 0.0, 1.0);
+
+        // Now that we finished the setup stuff, let's actually draw stuff. The pipeline is only
+        // switched when the next shape needs the other one.
+        let mut double_sided = None;
+        let mut draw_calls = 0;
+        for shape in self.draw_order() {
+            if double_sided != Some(shape.double_sided) {
+                double_sided = Some(shape.double_sided);
+                render_pass.set_pipeline(if shape.double_sided {
+                    &self.double_sided_pipeline
+                } else {
+                    &self.pipeline
+                });
+            }
+            draw_calls += shape.draw(&mut render_pass, self.push_constants);
+        }
+        draw_calls
+    }
+
+    /// All shapes, in the order they're drawn in. Later ones are blended over earlier ones, so
+    /// anything translucent has to come after what it should be seen on top of:
+    ///
+    /// 1. cell backgrounds (buttons, then the evaluation, analysis and win line tinting them)
+    /// 2. grid, and the cursor outlining the selected cell
+    /// 3. marks
+    /// 4. ghost marks, as previews over the actual board
+    /// 5. highlight, over the marks it points out
+    /// 6. status overlays (thinking dots, score)
+    /// 7. pause menu, over everything the game shows
+    /// 8. instance dots, since they're only for debugging
+    fn draw_order(&self) -> [&Shape; 18] {
+        [
+            &self.buttons,
+            &self.evaluation,
+            &self.analysis,
+            &self.win_line,
+            &self.grid,
+            &self.cursor,
+            &self.cross,
+            &self.ring,
+            &self.ghost_cross,
+            &self.ghost_ring,
+            &self.highlight,
+            &self.thinking,
+            &self.score_cross,
+            &self.score_ring,
+            &self.tallies,
+            &self.menu_backdrop,
+            &self.menu_options,
+            &self.instance_dots,
+        ]
+    }
+
+    // What each shape in draw_order is, for the stats.
+    const SHAPE_NAMES: [&'static str; 18] = [
+        "buttons",
+        "evaluation",
+        "analysis",
+        "win line",
+        "grid",
+        "cursor",
+        "cross",
+        "ring",
+        "ghost cross",
+        "ghost ring",
+        "highlight",
+        "thinking",
+        "score cross",
+        "score ring",
+        "tallies",
+        "menu backdrop",
+        "menu options",
+        "instance dots",
+    ];
+
+    // Uploads the uniforms after any of them changed. They're only read by the next draw, so
+    // it's marked dirty as well.
+    fn write_uniforms(&mut self) {
+        self.queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&self.uniforms));
+        self.dirty = true;
+    }
+
+    // Returns the background as it should be cleared with, respecting transparency.
+    fn clear_color(&self) -> wgpu::Color {
+        let alpha = self.background.a * self.background_alpha;
+        // premultiplied means the compositor expects the color to be multiplied with the alpha
+        // already
+        let factor = match self.alpha_mode {
+            wgpu::CompositeAlphaMode::PreMultiplied => alpha,
+            _ => 1.0,
+        };
+
+        wgpu::Color {
+            r: self.background.r * factor,
+            g: self.background.g * factor,
+            b: self.background.b * factor,
+            a: alpha,
+        }
+    }
+}
+
+impl Renderer for Backend {
+    fn update_instances(&mut self, board: &[Cell]) {
+        let now = self.clock.now();
+        self.dirty |= self.ring.finish_reveals();
+        self.dirty |= self.cross.finish_reveals();
+        self.dirty |= self
+            .ring
+            .update_instances(board.iter().map(|cell| matches!(cell, Cell::Ring)), now);
+        self.dirty |= self
+            .cross
+            .update_instances(board.iter().map(|cell| matches!(cell, Cell::Cross)), now);
+    }
+
+    fn update_ghosts(&mut self, ghosts: &[Cell]) {
+        let now = self.clock.now();
+        self.dirty |= self
+            .ghost_ring
+            .update_instances(ghosts.iter().map(|cell| matches!(cell, Cell::Ring)), now);
+        self.dirty |= self
+            .ghost_cross
+            .update_instances(ghosts.iter().map(|cell| matches!(cell, Cell::Cross)), now);
+    }
+
+    fn update_evaluation(&mut self, scores: &[Option<i32>]) {
+        let now = self.clock.now();
+        self.dirty |= self
+            .evaluation
+            .update_colors(scores.iter().copied().map(evaluation_color));
+        self.dirty |= self
+            .evaluation
+            .update_instances(scores.iter().map(Option::is_some), now);
+    }
+
+    fn dim_marks(&mut self, dimmed: &[bool]) {
+        let colors = || {
+            dimmed.iter().map(|&dimmed| {
+                if dimmed {
+                    [0.55, 0.55, 0.55, 1.0]
+                } else {
+                    [1.0; 4]
+                }
+            })
+        };
+        self.dirty |= self.cross.update_colors(colors());
+        self.dirty |= self.ring.update_colors(colors());
+    }
+
+    fn jitter_marks(&mut self, jitter: &[Jitter]) {
+        self.dirty |= self.cross.update_jitter(jitter.iter().copied());
+        self.dirty |= self.ring.update_jitter(jitter.iter().copied());
+    }
+
+    fn update_analysis(&mut self, qualities: &[Option<MoveQuality>]) {
+        let now = self.clock.now();
+        self.dirty |= self
+            .analysis
+            .update_colors(qualities.iter().map(|quality| match quality {
+                Some(MoveQuality::Blunder) => [0.6, 0.15, 0.15, 0.5],
+                Some(MoveQuality::Suboptimal) => [0.55, 0.5, 0.15, 0.5],
+                _ => [0.15, 0.6, 0.2, 0.5],
+            }));
+        self.dirty |= self
+            .analysis
+            .update_instances(qualities.iter().map(Option::is_some), now);
+    }
+
+    fn update_buttons(&mut self, states: &[ButtonState]) {
+        let now = self.clock.now();
+        // hovering darkens, pressing lightens
+        self.dirty |= self
+            .buttons
+            .update_colors(states.iter().map(|state| match state {
+                ButtonState::Idle => [0.16, 0.22, 0.22, 1.0],
+                ButtonState::Hovered => [0.1, 0.14, 0.14, 1.0],
+                ButtonState::Pressed => [0.3, 0.38, 0.38, 1.0],
+            }));
+        self.dirty |= self
+            .buttons
+            .update_instances(states.iter().map(|_| true), now);
+    }
+
+    fn is_animating(&self) -> bool {
+        self.draw_order().iter().any(|shape| shape.is_animating())
+    }
+
+    fn update_highlight(&mut self, cell: Option<usize>) {
+        let now = self.clock.now();
+        let cells = self.highlight.instances.len();
+        self.dirty |= self
+            .highlight
+            .update_instances((0..cells).map(|i| Some(i) == cell), now);
+    }
+
+    fn update_win_line(&mut self, cells: &[usize]) {
+        let now = self.clock.now();
+        let count = self.win_line.instances.len();
+        self.dirty |= self
+            .win_line
+            .update_instances((0..count).map(|i| cells.contains(&i)), now);
+    }
+
+    fn set_cursor(&mut self, cell: Option<usize>) {
+        let now = self.clock.now();
+        let cells = self.cursor.instances.len();
+        self.dirty |= self
+            .cursor
+            .update_instances((0..cells).map(|i| Some(i) == cell), now);
+    }
+
+    fn reveal_mark(&mut self, cell: usize) {
+        let now = self.clock.now();
+        self.ring.reveal(cell, now);
+        self.cross.reveal(cell, now);
+        self.dirty = true;
+    }
+
+    fn update_thinking(&mut self, progress: Option<f32>) {
+        let now = self.clock.now();
+        let active = progress.map_or(0, active_dots);
+        self.dirty |= self
+            .thinking
+            .update_instances((0..3).map(|i| i < active), now);
+    }
+
+    fn update_score(&mut self, score: Option<Score>) {
+        let now = self.clock.now();
+        let visible = score.is_some();
+        let Score { cross, ring } = score.unwrap_or_default();
+        self.dirty |= self
+            .score_cross
+            .update_instances(std::iter::once(visible), now);
+        self.dirty |= self
+            .score_ring
+            .update_instances(std::iter::once(visible), now);
+        // one row per faction, like the marks in front of them
+        let tallies = (0..2 * TALLY_CAP).map(|i| {
+            let wins = if i < TALLY_CAP { cross } else { ring };
+            i % TALLY_CAP < wins
+        });
+        self.dirty |= self.tallies.update_instances(tallies, now);
+    }
+
+    fn update_menu(&mut self, selected: Option<MenuOption>) {
+        let now = self.clock.now();
+        self.dirty |= self
+            .menu_backdrop
+            .update_instances(std::iter::once(selected.is_some()), now);
+        self.dirty |= self
+            .menu_options
+            .update_colors(MenuOption::ALL.into_iter().map(|option| {
+                if Some(option) == selected {
+                    [0.9, 0.9, 0.9, 1.0]
+                } else {
+                    [0.4, 0.45, 0.45, 1.0]
+                }
+            }));
+        self.dirty |= self
+            .menu_options
+            .update_instances(MenuOption::ALL.iter().map(|_| selected.is_some()), now);
+    }
+
+    fn set_grid_visible(&mut self, visible: bool) {
+        let now = self.clock.now();
+        self.dirty |= self.grid.update_instances(std::iter::once(visible), now);
+    }
+
+    fn set_instance_dots_visible(&mut self, visible: bool) {
+        let now = self.clock.now();
+        let count = self.instance_dots.instances.len();
+        self.dirty |= self
+            .instance_dots
+            .update_instances((0..count).map(|_| visible), now);
+    }
+
+    fn set_idle(&mut self, idle: bool) {
+        let now = self.clock.now();
+        self.dirty |= self.grid.set_breathing(idle, now);
+    }
+
+    fn set_background(&mut self, color: wgpu::Color) {
+        self.dirty |= self.background != color;
+        self.background = color;
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        let [r, g, b, a] = theme.marks_tint;
+        let ghost_tint = [r, g, b, a * GHOST_ALPHA];
+
+        self.grid.set_tint(theme.lines_tint);
+        self.cursor.set_tint(theme.lines_tint);
+        let [r, g, b, _] = theme.lines_tint;
+        self.win_line.set_tint([r, g, b, 0.2]);
+        self.thinking.set_tint(theme.lines_tint);
+        self.tallies.set_tint(theme.lines_tint);
+        self.score_cross.set_tint(theme.marks_tint);
+        self.score_ring.set_tint(theme.marks_tint);
+        self.cross.set_tint(theme.marks_tint);
+        self.ring.set_tint(theme.marks_tint);
+        self.ghost_cross.set_tint(ghost_tint);
+        self.ghost_ring.set_tint(ghost_tint);
+        self.dirty = true;
+    }
+
+    fn capture(&mut self) -> Result<Frame, CaptureError> {
+        let (width, height) = (self.window_size.width, self.window_size.height);
+        if width == 0 || height == 0 {
+            return Err(CaptureError::NothingDrawn);
+        }
+        let swap_red_blue = match self.preferred_format {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            format => return Err(CaptureError::UnsupportedFormat(format)),
+        };
+        // Copies out of textures need each row to start at a multiple of 256 bytes, so every row
+        // is followed by some padding which has to be cut away afterwards.
+        let row_size = width * 4;
+        let padded_row_size = row_size.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        // wgpu would panic instead of returning an error if the texture or buffer exceed the
+        // limits the device was created with
+        let limits = self.device.limits();
+        if width.max(height) > limits.max_texture_dimension_2d
+            || u64::from(padded_row_size) * u64::from(height) > limits.max_buffer_size
+        {
+            return Err(CaptureError::TooLarge(width, height));
+        }
+        // the multisampled texture has to match the size of the capture
+        if self.surface_size != Some(self.window_size) {
+            self.reconfigure_surface();
+        }
+
+        // The surface texture can't be read from, so everything is drawn once more into a
+        // texture which can be, in the very same format so the pipeline fits.
+        let texture = self.create_frame_texture(
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: wgpu::BufferAddress::from(padded_row_size * height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        self.prepare_shapes();
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        self.record_pass(&mut encoder, &view);
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: None,
+                },
+            },
+            texture.size(),
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        // Mapping only finishes while the device is polled, so this blocks until the GPU is done.
+        let slice = buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .expect("mapping callback to be called while waiting on the device")?;
+
+        let mapped = slice.get_mapped_range();
+        let mut rgba = Vec::with_capacity((row_size * height) as usize);
+        for row in mapped.chunks_exact(padded_row_size as usize) {
+            for pixel in row[..row_size as usize].chunks_exact(4) {
+                let (red, blue) = if swap_red_blue {
+                    (pixel[2], pixel[0])
+                } else {
+                    (pixel[0], pixel[2])
+                };
+                rgba.extend([red, pixel[1], blue, u8::MAX]);
+            }
+        }
+        // the buffer can't be unmapped while its contents are still being looked at
+        drop(mapped);
+        buffer.unmap();
+
+        Ok(Frame {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    fn stats(&self) -> RenderStats {
+        RenderStats {
+            shapes: Self::SHAPE_NAMES
+                .into_iter()
+                .zip(self.draw_order())
+                .map(|(name, shape)| shape.stats(name))
+                .collect(),
+            draw_calls: self.draw_calls,
+        }
+    }
+
+    fn adapter(&self) -> Option<wgpu::AdapterInfo> {
+        Some(self.adapter.get_info())
+    }
+
+    fn advance_clock(&mut self, step: Duration) {
+        self.clock = Clock::Fixed(self.clock.now() + step);
+        self.dirty = true;
+    }
+
+    fn set_view(&mut self, view: View) {
+        if view != self.view {
+            self.view = view;
+            self.uniforms.set_view(view);
+            self.write_uniforms();
+        }
+    }
+}
+
+/// Draws nothing at all. Stands in for a moment while the actual backend is being replaced, since
+/// a window can only have one surface at a time.
+pub struct NullRenderer;
+
+impl Renderer for NullRenderer {
+    fn update_instances(&mut self, _: &[Cell]) {}
+    fn update_ghosts(&mut self, _: &[Cell]) {}
+    fn dim_marks(&mut self, _: &[bool]) {}
+    fn jitter_marks(&mut self, _: &[Jitter]) {}
+    fn update_evaluation(&mut self, _: &[Option<i32>]) {}
+    fn update_analysis(&mut self, _: &[Option<MoveQuality>]) {}
+    fn update_buttons(&mut self, _: &[ButtonState]) {}
+    fn is_animating(&self) -> bool {
+        false
+    }
+    fn update_highlight(&mut self, _: Option<usize>) {}
+    fn update_win_line(&mut self, _: &[usize]) {}
+    fn set_cursor(&mut self, _: Option<usize>) {}
+    fn reveal_mark(&mut self, _: usize) {}
+    fn update_thinking(&mut self, _: Option<f32>) {}
+    fn update_score(&mut self, _: Option<Score>) {}
+    fn update_menu(&mut self, _: Option<MenuOption>) {}
+    fn set_grid_visible(&mut self, _: bool) {}
+    fn set_instance_dots_visible(&mut self, _: bool) {}
+    fn set_idle(&mut self, _: bool) {}
+    fn set_background(&mut self, _: wgpu::Color) {}
+    fn set_theme(&mut self, _: &Theme) {}
+    fn capture(&mut self) -> Result<Frame, CaptureError> {
+        Err(CaptureError::NothingDrawn)
+    }
+    fn stats(&self) -> RenderStats {
+        RenderStats::default()
+    }
+    fn adapter(&self) -> Option<wgpu::AdapterInfo> {
+        None
+    }
+    fn advance_clock(&mut self, _: Duration) {}
+    fn set_view(&mut self, _: View) {}
+}
+
+impl super::HandleEvent for NullRenderer {
+    fn handle(&mut self, _: Event<AppEvent>, _: &mut ControlFlow) {}
+}
+
+/// The largest square centered in a window of the given physical size, as its top left corner and
+/// side length in physical pixels. Everything is drawn into it, so the board stays square however
+/// the window is resized. The rest of the window only shows the background.
+pub fn letterbox(window_size: dpi::PhysicalSize<u32>) -> ([f32; 2], f32) {
+    let (width, height) = (window_size.width as f32, window_size.height as f32);
+    let side = width.min(height);
+    ([(width - side) / 2.0, (height - side) / 2.0], side)
+}
+
+/// Side length of one cell in clip space, on a board with the given number of cells per side.
+fn cell_size(board_size: usize) -> f32 {
+    2.0 / board_size as f32
+}
+
+/// The lines between the cells of a board with the given number of cells per side, each a quad
+/// in clip space going counter-clockwise. The end facing away from the center is slanted, so the
+/// grid looks slightly hand-drawn.
+pub fn grid_lines(board_size: usize) -> Vec<[[f32; 2]; 4]> {
+    // half of the thickness of each line
+    const HALF_WIDTH: f32 = 0.02;
+
+    (1..board_size)
+        .flat_map(|i| {
+            let center = -1.0 + cell_size(board_size) * i as f32;
+            let outwards = if center < 0.0 { -1.0 } else { 1.0 };
+            let outer = center + outwards * HALF_WIDTH;
+            let inner = center - outwards * HALF_WIDTH;
+            let vertical = [[outer, 0.93], [inner, 0.9], [inner, -0.9], [outer, -0.87]];
+            let horizontal = [[-0.93, outer], [-0.9, inner], [0.9, inner], [0.87, outer]];
+            // lines left of or below the center wind the other way around, flip them back
+            [vertical, horizontal].map(|mut quad| {
+                if outwards < 0.0 {
+                    quad.reverse();
+                }
+                quad
+            })
+        })
+        .collect()
+}
+
+/// How much marks have to be scaled to fit into the cells of a board with the given size. They're
+/// designed for cells of a 3 times 3 board.
+fn mark_scale(board_size: usize) -> f32 {
+    cell_size(board_size) / cell_size(3)
+}
+
+/// Returns how many of the three thinking dots are filled in after the given fraction of the
+/// thinking delay. The last one only appears once the delay is over completely.
+fn active_dots(progress: f32) -> usize {
+    ((progress.clamp(0.0, 1.0) * 3.0) as usize).min(3)
+}
+
+/// The color of a cell with the given score, see [`Renderer::update_evaluation`]. Cells without
+/// one aren't shown at all, so their color doesn't matter.
+fn evaluation_color(score: Option<i32>) -> [f32; 4] {
+    match score {
+        Some(score) if score < 0 => [0.6, 0.15, 0.15, 0.5],
+        Some(score) if score > 0 => [0.15, 0.6, 0.2, 0.5],
+        _ => [0.45, 0.45, 0.25, 0.5],
+    }
+}
+
+/// How many samples per pixel the given adapter can draw with into the given format, either
+/// [`SAMPLE_COUNT`] or just one.
+fn sample_count(adapter: &wgpu::Adapter, format: wgpu::TextureFormat) -> u32 {
+    let flags = adapter.get_texture_format_features(format).flags;
+    if flags.sample_count_supported(SAMPLE_COUNT) {
+        SAMPLE_COUNT
+    } else {
+        1
+    }
+}
+
+/// Raises every limit in `limits` the game uses to at least what `needed` asks for.
+fn raise_limits(limits: wgpu::Limits, needed: &wgpu::Limits) -> wgpu::Limits {
+    wgpu::Limits {
+        max_texture_dimension_2d: limits
+            .max_texture_dimension_2d
+            .max(needed.max_texture_dimension_2d),
+        max_texture_array_layers: limits
+            .max_texture_array_layers
+            .max(needed.max_texture_array_layers),
+        max_bind_groups: limits.max_bind_groups.max(needed.max_bind_groups),
+        max_bindings_per_bind_group: limits
+            .max_bindings_per_bind_group
+            .max(needed.max_bindings_per_bind_group),
+        max_uniform_buffers_per_shader_stage: limits
+            .max_uniform_buffers_per_shader_stage
+            .max(needed.max_uniform_buffers_per_shader_stage),
+        max_uniform_buffer_binding_size: limits
+            .max_uniform_buffer_binding_size
+            .max(needed.max_uniform_buffer_binding_size),
+        max_vertex_buffers: limits.max_vertex_buffers.max(needed.max_vertex_buffers),
+        max_vertex_attributes: limits
+            .max_vertex_attributes
+            .max(needed.max_vertex_attributes),
+        max_vertex_buffer_array_stride: limits
+            .max_vertex_buffer_array_stride
+            .max(needed.max_vertex_buffer_array_stride),
+        max_buffer_size: limits.max_buffer_size.max(needed.max_buffer_size),
+        max_push_constant_size: limits
+            .max_push_constant_size
+            .max(needed.max_push_constant_size),
+        max_inter_stage_shader_components: limits
+            .max_inter_stage_shader_components
+            .max(needed.max_inter_stage_shader_components),
+        ..limits
+    }
+}
+
+/// sRGB formats are preferred, so the colors look the same on every backend. Otherwise, whatever
+/// the surface supports first is taken. `None` if it supports nothing at all, which only happens
+/// if the adapter can't draw onto it.
+fn select_format(supported: &[wgpu::TextureFormat]) -> Option<wgpu::TextureFormat> {
+    supported
+        .iter()
+        .copied()
+        .find(wgpu::TextureFormat::is_srgb)
+        .or_else(|| supported.first().copied())
+}
+
+/// `Fifo` waits for the display like vsync, which is plenty for a board game and spares the GPU.
+/// It's supported everywhere, but if a surface claims otherwise, its first mode is taken instead.
+fn select_present_mode(supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+    if supported.contains(&wgpu::PresentMode::Fifo) {
+        wgpu::PresentMode::Fifo
+    } else {
+        supported
+            .first()
+            .copied()
+            .unwrap_or(wgpu::PresentMode::Fifo)
+    }
+}
+
+/// Only the (pre|post)multiplied ones let anything behind the window shine through, so these are
+/// preferred if `transparent` is set. Otherwise, or if neither is supported, `Opaque` is
+/// preferred, falling back to whatever the surface supports first.
+fn select_alpha_mode(
+    supported: &[wgpu::CompositeAlphaMode],
+    transparent: bool,
+) -> wgpu::CompositeAlphaMode {
+    use wgpu::CompositeAlphaMode as Mode;
+
+    let translucent = supported
+        .iter()
+        .copied()
+        .find(|mode| matches!(mode, Mode::PreMultiplied | Mode::PostMultiplied));
+
+    match translucent {
+        Some(mode) if transparent => mode,
+        _ if supported.contains(&Mode::Opaque) => Mode::Opaque,
+        // there's always at least one supported, and Auto would pick one of them anyways
+        _ => supported.first().copied().unwrap_or(Mode::Auto),
+    }
+}
+
+impl super::HandleEvent for Backend {
+    fn handle(&mut self, event: Event<AppEvent>, flow: &mut ControlFlow) {
+        // handle only basic stuff such as quitting directly, forward everything else
+        match event {
+            Event::WindowEvent { window_id, event } if Some(window_id) == self.window_id => {
+                match event {
+                    WindowEvent::CloseRequested => *flow = ControlFlow::Exit,
+                    WindowEvent::Resized(new_inner_size) => {
+                        self.window_size = new_inner_size;
+                        self.dirty = true;
+                    }
+                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                        self.window_size = *new_inner_size;
+                        self.dirty = true;
+                    }
+                    // whatever was presented before might be gone by now
+                    WindowEvent::Occluded(false) => self.dirty = true,
+                    _ => (),
+                }
+            }
+            Event::RedrawRequested(window_id) if Some(window_id) == self.window_id => {
+                match self.draw() {
+                    Err(BackendDrawError::SurfaceOutdated) => self.reconfigure_surface(),
+                    Err(e) => {
+                        log::error!("Error while drawing: {}", e);
+                        *flow = ControlFlow::Exit;
+                    }
+                    _ => (),
+                }
+            }
+            // Some backends complain or flash if buffers and textures are dropped while the GPU
+            // is still working with them, so everything submitted is waited for first.
+            Event::LoopDestroyed => {
+                self.device.poll(wgpu::Maintain::Wait);
+                log::debug!("GPU work finished, shutting down");
+            }
+            _ => (),
+        }
+    }
+}
 
 #[repr(C)]
 #[derive(Default, Debug, Copy, Clone)]
@@ -419,34 +1974,162 @@ unsafe impl bytemuck::Pod for Vertex {}
 
 macro_rules! vertices {
     (color: { r: $r:expr, g: $g:expr, b: $b:expr $(,)? }, position: [ $( $x:expr, $y:expr $(,)? );+ $(;)? ]$(,)?) => {
+        vertices! {
+            color: { r: $r, g: $g, b: $b, a: 1.0 },
+            position: [ $( $x, $y; )+ ],
+        }
+    };
+    (color: { r: $r:expr, g: $g:expr, b: $b:expr, a: $a:expr $(,)? }, position: [ $( $x:expr, $y:expr $(,)? );+ $(;)? ]$(,)?) => {
         &[$(
-            Vertex { position: [$x, $y], color: [$r, $g, $b, 1.0] },
+            Vertex { position: [$x, $y], color: [$r, $g, $b, $a] },
         )*]
     };
 }
 
+// Scales all given vertices around the origin.
+fn scaled(vertices: &[Vertex], factor: f32) -> Vec<Vertex> {
+    vertices
+        .iter()
+        .map(|vertex| Vertex {
+            position: vertex.position.map(|coordinate| coordinate * factor),
+            ..*vertex
+        })
+        .collect()
+}
+
+/// Everything in the uniform buffer, the same for all vertices of all shapes. Has to match
+/// `Uniforms` in the shader, including the order of fields.
+///
+/// Uniform buffers have stricter layout rules than vertex buffers: Each field has to be aligned
+/// to its own size (rounded up to a power of two), and the whole struct padded to 16 bytes.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct Uniforms {
+    view_offset: [f32; 2],
+    view_scale: f32,
+    _padding: f32,
+    // cosine and sine of the angle the view is turned by, i.e. where the x axis ends up
+    view_rotation: [f32; 2],
+}
+
+unsafe impl bytemuck::Zeroable for Uniforms {}
+unsafe impl bytemuck::Pod for Uniforms {}
+
+impl Default for Uniforms {
+    fn default() -> Self {
+        let mut uniforms = Self {
+            view_offset: [0.0; 2],
+            view_scale: 0.0,
+            _padding: 0.0,
+            view_rotation: [0.0; 2],
+        };
+        uniforms.set_view(View::default());
+        uniforms
+    }
+}
+
+impl Uniforms {
+    fn set_view(&mut self, view: View) {
+        self.view_offset = view.offset;
+        self.view_scale = view.scale;
+        let mut x_axis = Vec2::unit_x();
+        view.rotor().rotate_vec(&mut x_axis);
+        self.view_rotation = [x_axis.x, x_axis.y];
+    }
+}
+
 #[repr(C)]
 #[derive(Default, Debug, Copy, Clone, PartialEq)]
 struct Instance {
     position: [f32; 2],
+    scale: f32,
+    // Counterclockwise, in radians, around the origin of the shape.
+    rotation: f32,
+    color: [f32; 4],
 }
 
 unsafe impl bytemuck::Zeroable for Instance {}
 unsafe impl bytemuck::Pod for Instance {}
 
 impl Instance {
-    /// Returns instances laid out in a 3 times 3 grid, ranging on both X and Y from -0.5 to 0.5.
-    fn grid() -> [Instance; 9] {
-        let mut grid = Vec::new();
+    /// Creates an instance at the given position, at full scale, unturned and without changing
+    /// the color of the shape.
+    fn new(position: [f32; 2]) -> Self {
+        Self {
+            position,
+            scale: 1.0,
+            rotation: 0.0,
+            color: [1.0; 4],
+        }
+    }
 
-        for x in [-0.66, 0.0, 0.66] {
-            for y in [-0.66, 0.0, 0.66] {
-                grid.push(Instance { position: [x, y] });
+    /// Returns instances laid out in a grid with `side` cells per side, in the center of each
+    /// cell. Indexed by `x * side + y`, with y+ up.
+    fn grid(side: usize) -> Vec<Instance> {
+        let center = |i| -1.0 + cell_size(side) * (i as f32 + 0.5);
+        let mut grid = Vec::with_capacity(side * side);
+
+        for x in 0..side {
+            for y in 0..side {
+                grid.push(Instance::new([center(x), center(y)]));
             }
         }
 
-        grid.try_into()
-            .expect("matching size of function output and generation")
+        grid
+    }
+}
+
+/// Every shape which depends on how many cells there are, as they're laid out for a fresh board.
+/// Nothing is activated yet, so whatever should be visible right away still has to be.
+struct CellShapes {
+    buttons: Shape,
+    evaluation: Shape,
+    analysis: Shape,
+    win_line: Shape,
+    grid: Shape,
+    cursor: Shape,
+    cross: Shape,
+    ring: Shape,
+    ghost_cross: Shape,
+    ghost_ring: Shape,
+    highlight: Shape,
+}
+
+impl CellShapes {
+    fn new(device: &wgpu::Device, side: usize) -> Self {
+        let cells = Instance::grid(side);
+        let mark_scale = mark_scale(side);
+
+        let mut win_line = Shape::cell_background(device, mark_scale, &cells);
+        win_line.set_tint([1.0, 1.0, 1.0, 0.2]);
+        let mut cross = Shape::cross(device, mark_scale, &cells);
+        cross.fades_in = true;
+        cross.fades_out = true;
+        let mut ring = Shape::ring(device, mark_scale, &cells);
+        ring.fades_in = true;
+        ring.fades_out = true;
+        let mut ghost_cross = Shape::cross(device, mark_scale, &cells);
+        ghost_cross.set_tint([1.0, 1.0, 1.0, GHOST_ALPHA]);
+        let mut ghost_ring = Shape::ring(device, mark_scale, &cells);
+        ghost_ring.set_tint([1.0, 1.0, 1.0, GHOST_ALPHA]);
+        let mut highlight = Shape::cell_background(device, mark_scale, &cells);
+        highlight.set_tint([1.0, 0.45, 0.1, 0.4]);
+        let mut cursor = Shape::cell_outline(device, mark_scale, &cells);
+        cursor.fades_out = true;
+
+        Self {
+            buttons: Shape::cell_background(device, mark_scale, &cells),
+            evaluation: Shape::cell_background(device, mark_scale, &cells),
+            analysis: Shape::cell_background(device, mark_scale, &cells),
+            win_line,
+            grid: Shape::grid(device, side),
+            cursor,
+            cross,
+            ring,
+            ghost_cross,
+            ghost_ring,
+            highlight,
+        }
     }
 }
 
@@ -454,11 +2137,39 @@ impl Instance {
 struct Shape {
     vertices: wgpu::Buffer,
     indices: wgpu::Buffer,
+    vertex_count: u32,
     index_count: u32,
     // Not all instances we render to have to be active, but they all need to be present on the GPU
     // anyways so we don't have to reupload them all the time.
     all_instances: wgpu::Buffer,
+    // Same as all_instances, but on the CPU side, so single instances can be changed and then
+    // reuploaded.
+    instances: Vec<Instance>,
+    instances_changed: bool,
+    // Where each instance was placed when creating the shape, before any jitter.
+    origins: Vec<[f32; 2]>,
+    enabled: Vec<bool>,
     active_ranges: Vec<Range<u32>>,
+
+    // Multiplied with the colors of all instances, see set_tint.
+    tint: [f32; 4],
+
+    // Whether enabled instances grow from nothing, instead of appearing immediately.
+    fades_in: bool,
+    // Whether disabled instances shrink until they vanish, instead of disappearing immediately.
+    fades_out: bool,
+    // Whether the back faces of its triangles are drawn as well, instead of being culled. Meant
+    // for bringing up new shapes, so a single mis-wound triangle doesn't just vanish.
+    double_sided: bool,
+    // Instances which are enabled but still growing, with the time they were enabled.
+    appearing: Vec<(usize, Instant)>,
+    // Instances which are disabled but still shrinking, with the time they were disabled.
+    fading: Vec<(usize, Instant)>,
+    // Instances which are being drawn stroke by stroke, with the time they started and how many
+    // of the indices to draw of them so far. Drawn on their own, not as part of active_ranges.
+    revealing: Vec<(usize, Instant, u32)>,
+    // Since when all instances slowly grow and shrink, if they do.
+    breathing_since: Option<Instant>,
 }
 
 impl Shape {
@@ -478,7 +2189,8 @@ impl Shape {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: None,
             contents: bytemuck::cast_slice(vertices),
-            usage: wgpu::BufferUsages::VERTEX,
+            // read back in tests
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_SRC,
         });
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: None,
@@ -488,35 +2200,256 @@ impl Shape {
         let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: None,
             contents: bytemuck::cast_slice(instances),
-            usage: wgpu::BufferUsages::VERTEX,
+            // written to again whenever single instances change, and read back in tests
+            usage: wgpu::BufferUsages::VERTEX
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
         });
 
         Self {
             vertices: vertex_buffer,
             indices: index_buffer,
+            vertex_count: vertices.len() as u32,
             index_count: indices.len() as u32,
             all_instances: instance_buffer,
+            instances: instances.to_vec(),
+            instances_changed: false,
+            origins: instances.iter().map(|instance| instance.position).collect(),
+            enabled: vec![false; instances.len()],
             active_ranges: Vec::new(),
+            tint: [1.0; 4],
+            fades_in: false,
+            fades_out: false,
+            double_sided: false,
+            appearing: Vec::new(),
+            fading: Vec::new(),
+            revealing: Vec::new(),
+            breathing_since: None,
         }
     }
 
-    /// Updates the active instances of this shape.
-    fn update_instances<I>(&mut self, enabled: I)
+    /// Updates the active instances of this shape. Returns whether any of them changed.
+    fn update_instances<I>(&mut self, enabled: I, now: Instant) -> bool
     where
         I: Iterator<Item = bool> + ExactSizeIterator,
     {
+        let enabled: Vec<bool> = enabled.collect();
+        if enabled == self.enabled {
+            return false;
+        }
+
+        if self.fades_in {
+            for (i, (&was, &is)) in self.enabled.iter().zip(&enabled).enumerate() {
+                if !was && is {
+                    self.appearing.push((i, now));
+                } else if !is {
+                    self.appearing.retain(|&(appearing, _)| appearing != i);
+                }
+            }
+        }
+        if self.fades_out {
+            for (i, (&was, &is)) in self.enabled.iter().zip(&enabled).enumerate() {
+                if was && !is {
+                    self.fading.push((i, now));
+                    self.revealing.retain(|&(revealing, ..)| revealing != i);
+                } else if is {
+                    // re-enabled before it vanished, so it's just there again
+                    self.fading.retain(|&(fading, _)| fading != i);
+                }
+            }
+        }
+
+        self.enabled = enabled;
+        self.recalculate_ranges();
+        true
+    }
+
+    /// Sets the color each instance's vertex colors are multiplied with. Returns whether any of
+    /// them changed.
+    fn update_colors<I>(&mut self, colors: I) -> bool
+    where
+        I: Iterator<Item = [f32; 4]>,
+    {
+        let mut changed = false;
+        for (instance, color) in self.instances.iter_mut().zip(colors) {
+            if instance.color != color {
+                instance.color = color;
+                changed = true;
+            }
+        }
+
+        self.instances_changed |= changed;
+        changed
+    }
+
+    /// Moves and turns each instance away from where it was placed when creating the shape.
+    /// Returns whether any of them changed.
+    fn update_jitter<I>(&mut self, jitter: I) -> bool
+    where
+        I: Iterator<Item = Jitter>,
+    {
+        let mut changed = false;
+        for ((instance, origin), jitter) in self.instances.iter_mut().zip(&self.origins).zip(jitter)
+        {
+            let position = [origin[0] + jitter.offset[0], origin[1] + jitter.offset[1]];
+            if instance.position != position || instance.rotation != jitter.rotation {
+                instance.position = position;
+                instance.rotation = jitter.rotation;
+                changed = true;
+            }
+        }
+
+        self.instances_changed |= changed;
+        changed
+    }
+
+    /// Sets the color all instance colors are multiplied with, on top of their own.
+    fn set_tint(&mut self, tint: [f32; 4]) {
+        self.tint = tint;
+        // only matters if the tint is baked, but then it does
+        self.instances_changed = true;
+    }
+
+    /// Writes the instances to the GPU, if any of them changed since the last call. If
+    /// `bake_tint` is set, the tint is multiplied into their colors, for when it can't be pushed
+    /// on drawing.
+    fn upload_instances(&mut self, queue: &wgpu::Queue, bake_tint: bool) {
+        if !self.instances_changed {
+            return;
+        }
+
+        if bake_tint {
+            let tinted: Vec<Instance> = self
+                .instances
+                .iter()
+                .map(|instance| Instance {
+                    color: array::from_fn(|i| instance.color[i] * self.tint[i]),
+                    ..*instance
+                })
+                .collect();
+            queue.write_buffer(&self.all_instances, 0, bytemuck::cast_slice(&tinted));
+        } else {
+            queue.write_buffer(
+                &self.all_instances,
+                0,
+                bytemuck::cast_slice(&self.instances),
+            );
+        }
+        self.instances_changed = false;
+    }
+
+    /// Advances the fade-in animation of all enabled instances and the fade-out animation of all
+    /// disabled ones, and stops drawing the ones which have vanished completely. Same for
+    /// instances being revealed, which are drawn as a whole once done.
+    fn animate(&mut self, now: Instant) {
+        self.appearing
+            .retain(|&(_, since)| now.duration_since(since) < FADE_IN_DURATION);
+        let fading_before = self.fading.len();
+        self.fading
+            .retain(|&(_, since)| now.duration_since(since) < FADE_OUT_DURATION);
+
+        let breath = self.breathing_since.map_or(1.0, |since| {
+            let phase = now.duration_since(since).as_secs_f32() / BREATH_PERIOD.as_secs_f32();
+            1.0 + BREATH_AMPLITUDE * (phase * 2.0 * PI).sin()
+        });
+
+        for (i, instance) in self.instances.iter_mut().enumerate() {
+            let fading = self.fading.iter().find(|&&(fading, _)| fading == i);
+            let appearing = self.appearing.iter().find(|&&(other, _)| other == i);
+            let scale = match (fading, appearing) {
+                (Some(&(_, since)), _) => {
+                    1.0 - now.duration_since(since).as_secs_f32() / FADE_OUT_DURATION.as_secs_f32()
+                }
+                (None, Some(&(_, since))) => {
+                    breath * now.duration_since(since).as_secs_f32()
+                        / FADE_IN_DURATION.as_secs_f32()
+                }
+                (None, None) => breath,
+            };
+
+            if instance.scale != scale {
+                instance.scale = scale;
+                self.instances_changed = true;
+            }
+        }
+
+        let revealing_before = self.revealing.len();
+        self.revealing
+            .retain(|&(_, since, _)| now.duration_since(since) < REVEAL_DURATION);
+        for (_, since, index_count) in &mut self.revealing {
+            let progress = now.duration_since(*since).as_secs_f32() / REVEAL_DURATION.as_secs_f32();
+            // only whole triangles make sense
+            *index_count = (self.index_count as f32 * progress) as u32 / 3 * 3;
+        }
+
+        if self.fading.len() != fading_before || self.revealing.len() != revealing_before {
+            self.recalculate_ranges();
+        }
+    }
+
+    /// Starts drawing the given instance stroke by stroke, if it's enabled at all. The strokes are
+    /// drawn in the order of the indices.
+    fn reveal(&mut self, i: usize, now: Instant) {
+        if self.enabled.get(i).copied().unwrap_or(false) {
+            self.revealing.push((i, now, 0));
+            self.recalculate_ranges();
+        }
+    }
+
+    /// Completes all reveals immediately. Returns whether there were any.
+    fn finish_reveals(&mut self) -> bool {
+        if self.revealing.is_empty() {
+            return false;
+        }
+        self.revealing.clear();
+        self.recalculate_ranges();
+        true
+    }
+
+    /// Returns whether any instance is still fading in or out, or being revealed.
+    fn is_animating(&self) -> bool {
+        !self.appearing.is_empty()
+            || !self.fading.is_empty()
+            || !self.revealing.is_empty()
+            || self.breathing_since.is_some()
+    }
+
+    /// Starts or stops breathing, see `animate`. Stopping snaps back to the usual scale on the next
+    /// frame. Returns whether that changed anything.
+    fn set_breathing(&mut self, breathing: bool, now: Instant) -> bool {
+        if breathing == self.breathing_since.is_some() {
+            return false;
+        }
+        self.breathing_since = breathing.then_some(now);
+        true
+    }
+
+    // Groups all enabled or still fading instances into as few ranges as possible, so they can be
+    // drawn with one call per range.
+    fn recalculate_ranges(&mut self) {
         // thanks AsykoSkrwl! even though your solution had a few unfixable flaws when I tried to
         // apply it, I took some useful things from it
         self.active_ranges.clear();
 
-        let length = enabled.len();
+        let length = self.enabled.len();
         if length == 0 {
             return;
         }
 
         let mut possible_start = None;
 
-        for (i, active) in enabled.enumerate() {
+        let visible = self
+            .enabled
+            .iter()
+            .enumerate()
+            .map(|(i, &enabled)| enabled || self.fading.iter().any(|&(fading, _)| fading == i))
+            .enumerate()
+            // revealed instances are drawn on their own
+            .map(|(i, visible)| {
+                visible && !self.revealing.iter().any(|&(revealing, ..)| revealing == i)
+            });
+
+        for (i, active) in visible.enumerate() {
             // basically just analyzing a flip-flop: note down when it's positive and note down when it
             // ends being positive
             match (possible_start, active) {
@@ -534,13 +2467,23 @@ impl Shape {
         }
     }
 
-    /// Draws this shape using the given render pass.
+    /// Draws this shape using the given render pass, returning how many draw calls that took.
     ///
     /// The pipeline defines how the vertices contained by this shape are to be interpreted, e.g.
     /// if as lines, triangles, triangle strips...
-    fn draw<'b, 'a: 'b>(&'a self, render_pass: &mut wgpu::RenderPass<'b>) {
+    ///
+    /// `push_tint` has to be set if push constants are supported, and unset if the tint is baked
+    /// into the instances instead.
+    fn draw<'b, 'a: 'b>(&'a self, render_pass: &mut wgpu::RenderPass<'b>, push_tint: bool) -> u32 {
         // We happen to be able to just reuse the renderpass to draw new things. This allows us to
         // set everything we want, draw, set everything we want, draw again...
+        if push_tint {
+            render_pass.set_push_constants(
+                wgpu::ShaderStages::VERTEX,
+                0,
+                bytemuck::cast_slice(&self.tint),
+            );
+        }
         render_pass.set_vertex_buffer(0, self.vertices.slice(..));
         render_pass.set_vertex_buffer(1, self.all_instances.slice(..));
         render_pass.set_index_buffer(self.indices.slice(..), wgpu::IndexFormat::Uint16);
@@ -548,18 +2491,39 @@ impl Shape {
         for instance_range in &self.active_ranges {
             render_pass.draw_indexed(0..self.index_count, 0, instance_range.clone());
         }
+        for &(i, _, index_count) in &self.revealing {
+            let i = i as u32;
+            render_pass.draw_indexed(0..index_count, 0, i..i + 1);
+        }
+        (self.active_ranges.len() + self.revealing.len()) as u32
+    }
+
+    /// Counts the vertices, indices and instances drawn of this shape, labeled with the given
+    /// name.
+    fn stats(&self, name: &'static str) -> ShapeStats {
+        let enabled: u32 = self
+            .active_ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum();
+        ShapeStats {
+            name,
+            vertices: self.vertex_count,
+            indices: self.index_count,
+            active_instances: enabled + self.revealing.len() as u32,
+        }
     }
 }
 
-/// Pre-defined shapes. All methods in here have their instances laid out as in
-/// [`Instance::grid`] (except, well, `grid` itself which has only one).
+/// Pre-defined shapes. Most methods in here take their instances, which are usually laid out as
+/// in [`Instance::grid`].
 impl Shape {
-    /// Creates a new cross-like shape.
+    /// Creates a new cross-like shape, scaled by the given factor.
     #[rustfmt::skip]
-    fn cross(device: &wgpu::Device) -> Self {
+    fn cross(device: &wgpu::Device, scale: f32, instances: &[Instance]) -> Self {
         Self::new(
             device,
-            vertices! {
+            &scaled(vertices! {
                 color: { r: 0.27, g: 0.87, b: 0.7 },
                 position: [
                     -0.25, 0.25;
@@ -578,7 +2542,7 @@ impl Shape {
                     -0.2, -0.15;
                     -0.15, -0.2;
                 ],
-            },
+            }, scale),
             &[
                 // corners
                 1, 2, 0,
@@ -593,13 +2557,13 @@ impl Shape {
                 5, 10, 11,
                 11, 4, 5,
             ],
-            &Instance::grid()
+            instances
         )
     }
 
-    /// Creates a new ring-like shape with 48 vertices.
+    /// Creates a new ring-like shape with 48 vertices, scaled by the given factor.
     #[rustfmt::skip]
-    fn ring(device: &wgpu::Device) -> Self {
+    fn ring(device: &wgpu::Device, scale: f32, instances: &[Instance]) -> Self {
         const CIRCLE_VERTEX_COUNT: u32 = 24;
 
         fn wrap_at_max(x: u32) -> u32 {
@@ -616,8 +2580,9 @@ impl Shape {
         let mut vector = Vec2::new(1.0, 0.0);
 
         for i in (0..CIRCLE_VERTEX_COUNT).map(|x| x * 2) {
-            vertices.push(Vertex { position: [vector.x * 0.15, vector.y * 0.15], color: [0.76, 0.3, 1.0, 1.0] });
-            vertices.push(Vertex { position: [vector.x * 0.25, vector.y * 0.25], color: [0.76, 0.3, 1.0, 1.0] });
+            let (inner, outer) = (vector * 0.15 * scale, vector * 0.25 * scale);
+            vertices.push(Vertex { position: [inner.x, inner.y], color: [0.76, 0.3, 1.0, 1.0] });
+            vertices.push(Vertex { position: [outer.x, outer.y], color: [0.76, 0.3, 1.0, 1.0] });
 
             // Might seem confusing, but let me explain:
             //
@@ -643,66 +2608,1045 @@ impl Shape {
             rotor.rotate_vec(&mut vector);
         }
 
-        Self::new(device, &vertices, &indices, &Instance::grid())
+        Self::new(device, &vertices, &indices, instances)
     }
 
-    /// A 3 times 3 grid.
-    ///
-    /// ```
-    ///    |   |
-    /// ---+---+---
-    ///    |   |
-    /// ---+---+---
-    ///    |   |
-    /// ```
+    /// A white square filling most of one cell, meant to be colored per instance. Scaled like
+    /// the marks.
+    #[rustfmt::skip]
+    fn cell_background(device: &wgpu::Device, scale: f32, instances: &[Instance]) -> Self {
+        Self::new(
+            device,
+            &scaled(vertices! {
+                color: { r: 1.0, g: 1.0, b: 1.0 },
+                position: [
+                    -0.3, -0.3;
+                    0.3, -0.3;
+                    0.3, 0.3;
+                    -0.3, 0.3;
+                ],
+            }, scale),
+            &[
+                0, 1, 2,
+                2, 3, 0,
+            ],
+            instances
+        )
+    }
+
+    /// A thin white frame along the edge of what `cell_background` fills, meant to be tinted.
+    /// Scaled like the marks.
+    #[rustfmt::skip]
+    fn cell_outline(device: &wgpu::Device, scale: f32, instances: &[Instance]) -> Self {
+        Self::new(
+            device,
+            &scaled(vertices! {
+                color: { r: 1.0, g: 1.0, b: 1.0 },
+                position: [
+                    // outer corners
+                    -0.3, -0.3;
+                    0.3, -0.3;
+                    0.3, 0.3;
+                    -0.3, 0.3;
+
+                    // inner corners
+                    -0.28, -0.28;
+                    0.28, -0.28;
+                    0.28, 0.28;
+                    -0.28, 0.28;
+                ],
+            }, scale),
+            &[
+                // bottom
+                0, 1, 5,
+                5, 4, 0,
+                // right
+                1, 2, 6,
+                6, 5, 1,
+                // top
+                2, 3, 7,
+                7, 6, 2,
+                // left
+                3, 0, 4,
+                4, 7, 3,
+            ],
+            instances
+        )
+    }
+
+    /// A white rectangle with the given half width and height, meant to be colored per instance
+    /// or by the tint.
     #[rustfmt::skip]
-    fn grid(device: &wgpu::Device) -> Self {
+    fn rectangle(device: &wgpu::Device, half_size: [f32; 2], instances: &[Instance]) -> Self {
+        let [x, y] = half_size;
+        Self::new(
+            device,
+            vertices! {
+                color: { r: 1.0, g: 1.0, b: 1.0 },
+                position: [
+                    -x, -y;
+                    x, -y;
+                    x, y;
+                    -x, y;
+                ],
+            },
+            &[
+                0, 1, 2,
+                2, 3, 0,
+            ],
+            instances
+        )
+    }
+
+    /// Three small dots in a row at the bottom edge, filled in one after another while the AI is
+    /// thinking. Doesn't follow [`Instance::grid`].
+    #[rustfmt::skip]
+    fn thinking_dots(device: &wgpu::Device) -> Self {
         Self::new(
             device,
             vertices! {
                 color: { r: 0.9, g: 0.9, b: 0.9 },
                 position: [
-                    // left-hand vertical line
-                    -0.35, 0.93;
-                    -0.31, 0.9;
-                    -0.35, -0.87;
-                    -0.31, -0.9;
-
-                    // right-hand vertical line
-                    0.35, 0.93;
-                    0.31, 0.9;
-                    0.35, -0.87;
-                    0.31, -0.9;
-
-                    // bottom horizontal line
-                    -0.93, -0.35;
-                    -0.9, -0.31;
-                    0.87, -0.35;
-                    0.9, -0.31;
-
-                    // top horizontal line
-                    -0.93, 0.35;
-                    -0.9, 0.31;
-                    0.87, 0.35;
-                    0.9, 0.31;
+                    -0.015, -0.015;
+                    0.015, -0.015;
+                    0.015, 0.015;
+                    -0.015, 0.015;
                 ],
             },
             &[
-                2, 1, 0,
-                1, 2, 3,
+                0, 1, 2,
+                2, 3, 0,
+            ],
+            &[
+                Instance::new([-0.06, -0.96]),
+                Instance::new([0.0, -0.96]),
+                Instance::new([0.06, -0.96]),
+            ]
+        )
+    }
+
+    /// Thin bars for counting wins, [`TALLY_CAP`] in a row for crosses and as many below for
+    /// rings. Every fifth one is followed by a gap, so they can be counted at a glance.
+    fn tallies(device: &wgpu::Device) -> Self {
+        let rows = [0.94, 0.86];
+        let instances: Vec<Instance> = rows
+            .into_iter()
+            .flat_map(|y| {
+                (0..TALLY_CAP).map(move |i| {
+                    let x = -0.88 + 0.022 * i as f32 + 0.02 * (i / 5) as f32;
+                    Instance::new([x, y])
+                })
+            })
+            .collect();
+        Self::rectangle(device, [0.005, 0.03], &instances)
+    }
+
+    /// The lines between the cells of a board with the given number of cells per side, like this
+    /// for the classic one:
+    ///
+    /// ```
+    ///    |   |
+    /// ---+---+---
+    ///    |   |
+    /// ---+---+---
+    ///    |   |
+    /// ```
+    fn grid(device: &wgpu::Device, board_size: usize) -> Self {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for quad in grid_lines(board_size) {
+            let first = vertices.len() as u16;
+            vertices.extend(quad.map(|position| Vertex {
+                position,
+                color: [0.9, 0.9, 0.9, 1.0],
+            }));
+            indices.extend([0, 1, 2, 0, 2, 3].map(|offset| first + offset));
+        }
+
+        Self::new(device, &vertices, &indices, &[Instance::new([0.0, 0.0])])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            game::{Faction, Lines},
+            HandleEvent,
+        },
+    };
+
+    /// A backend for the classic board drawing into a small texture, preferably on the software
+    /// fallback so tests behave the same everywhere. `None` if there's no adapter at all, in which
+    /// case the test calling this can't do anything.
+    fn backend() -> Option<Backend> {
+        let size = dpi::PhysicalSize::new(64, 64);
+        let backend = Backend::headless(size, LimitsMode::Balanced, Layout::Board(3));
+        match pollster::block_on(backend) {
+            Ok(backend) => Some(backend),
+            Err(e) => {
+                eprintln!("Skipping, no backend: {}", e);
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn hiding_the_grid_deactivates_its_instance() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        assert_eq!(backend.grid.active_ranges, vec![0..1]);
+
+        backend.set_grid_visible(false);
+        assert!(backend.grid.active_ranges.is_empty());
+        backend.set_grid_visible(true);
+        assert_eq!(backend.grid.active_ranges, vec![0..1]);
+    }
+
+    #[test]
+    fn removed_marks_shrink_until_they_are_gone() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        let mut board = [Cell::Empty; 9];
+        board[4] = Cell::Ring;
+        // fixes the clock, so the animation only progresses as told
+        backend.advance_clock(Duration::ZERO);
+        backend.update_instances(&board);
+        backend.advance_clock(FADE_IN_DURATION);
+        backend.prepare_shapes();
+        assert!(!backend.is_animating());
+
+        backend.update_instances(&[Cell::Empty; 9]);
+        // still drawn while fading, even though it's not on the board anymore
+        assert_eq!(backend.ring.active_ranges, vec![4..5]);
+        assert!(backend.is_animating());
+
+        backend.advance_clock(FADE_OUT_DURATION / 2);
+        backend.prepare_shapes();
+        assert_eq!(backend.ring.active_ranges, vec![4..5]);
+        assert!((backend.ring.instances[4].scale - 0.5).abs() < 0.01);
+
+        backend.advance_clock(FADE_OUT_DURATION / 2);
+        backend.prepare_shapes();
+        assert!(backend.ring.active_ranges.is_empty());
+        assert!(!backend.is_animating());
+    }
+
+    #[test]
+    fn evaluation_colors_cells_by_score() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        // cross threatens to complete the left column, so ring has to block on 2 or lose, and
+        // blocking sets up a fork on 6 which wins
+        let mut board = [Cell::Empty; 9];
+        board[0] = Cell::Cross;
+        board[1] = Cell::Cross;
+        board[4] = Cell::Ring;
+        board[8] = Cell::Ring;
+        let scores = crate::ai::evaluate_moves(&board, Faction::Ring, &Lines::default());
+        assert!(scores[2].unwrap() > 0);
+        backend.update_evaluation(&scores);
+
+        let red = [0.6, 0.15, 0.15, 0.5];
+        let green = [0.15, 0.6, 0.2, 0.5];
+        for (i, cell) in board.iter().enumerate() {
+            let shown = backend.evaluation.enabled[i];
+            let color = backend.evaluation.instances[i].color;
+            match (i, cell) {
+                (_, Cell::Cross | Cell::Ring) => assert!(!shown, "cell {}", i),
+                (2, _) => assert_eq!((shown, color), (true, green)),
+                _ => assert_eq!((shown, color), (true, red), "cell {}", i),
+            }
+        }
+    }
+
+    // Waits for everything submitted so far, and then reads the whole buffer back from the GPU.
+    fn read_back(backend: &Backend, buffer: &wgpu::Buffer) -> Vec<u8> {
+        let staging = backend.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: buffer.size(),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = backend
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, buffer.size());
+        backend.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+        backend.device.poll(wgpu::Maintain::Wait);
+        let bytes = slice.get_mapped_range().to_vec();
+        bytes
+    }
 
-                5, 6, 4,
-                6, 5, 7,
+    #[test]
+    fn instance_colors_arrive_on_the_gpu() {
+        let Some(backend) = backend() else {
+            return;
+        };
+        let mut shape = Shape::cell_background(&backend.device, 1.0, &Instance::grid(3));
+        let colors: Vec<[f32; 4]> = (0..9).map(|i| [i as f32 / 9.0, 0.5, 1.0, 0.25]).collect();
+        assert!(shape.update_colors(colors.iter().copied()));
+
+        shape.upload_instances(&backend.queue, false);
+        let uploaded = read_back(&backend, &shape.all_instances);
+        let uploaded: &[Instance] = bytemuck::cast_slice(&uploaded);
+        assert_eq!(uploaded, shape.instances);
+        let uploaded: Vec<_> = uploaded.iter().map(|instance| instance.color).collect();
+        assert_eq!(uploaded, colors);
+
+        // without push constants, the tint ends up in the instances instead
+        shape.set_tint([0.5, 1.0, 1.0, 1.0]);
+        shape.upload_instances(&backend.queue, true);
+        let uploaded = read_back(&backend, &shape.all_instances);
+        let uploaded: &[Instance] = bytemuck::cast_slice(&uploaded);
+        assert_eq!(uploaded[8].color, [4.0 / 9.0, 0.5, 1.0, 0.25]);
+    }
 
-                10, 9, 8,
-                9, 10, 11,
+    #[test]
+    fn instance_dots_sit_on_the_grid() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        let dots: Vec<_> = backend
+            .instance_dots
+            .instances
+            .iter()
+            .map(|dot| dot.position)
+            .collect();
+        for cell in Instance::grid(3) {
+            assert!(dots.contains(&cell.position), "{:?}", cell.position);
+        }
+        // shapes sharing spots only get one dot there
+        for (i, dot) in dots.iter().enumerate() {
+            assert!(!dots[..i].contains(dot));
+        }
 
-                13, 14, 12,
-                14, 13, 15,
+        backend.set_instance_dots_visible(true);
+        assert_eq!(
+            backend.instance_dots.active_ranges,
+            vec![0..dots.len() as u32]
+        );
+        backend.prepare_shapes();
+        let uploaded = read_back(&backend, &backend.instance_dots.all_instances);
+        let uploaded: &[Instance] = bytemuck::cast_slice(&uploaded);
+        let uploaded: Vec<_> = uploaded.iter().map(|dot| dot.position).collect();
+        assert_eq!(uploaded, dots);
+    }
+
+    #[test]
+    fn rebuilding_lays_shapes_out_for_the_new_board() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        backend.advance_clock(Duration::ZERO);
+        backend.update_instances(&[Cell::Cross; 9]);
+
+        backend.rebuild_shapes(4, &Theme::LIGHT);
+        assert_eq!(backend.grid.enabled, [true]);
+        assert_eq!(backend.grid.tint, Theme::LIGHT.lines_tint);
+        let vertices = read_back(&backend, &backend.grid.vertices);
+        let vertices: &[Vertex] = bytemuck::cast_slice(&vertices);
+        let lines: Vec<_> = grid_lines(4).into_iter().flatten().collect();
+        assert_eq!(lines.len(), 6 * 4);
+        assert_eq!(
+            vertices
+                .iter()
+                .map(|vertex| vertex.position)
+                .collect::<Vec<_>>(),
+            lines
+        );
+        assert_eq!(backend.grid.index_count, 6 * 6);
+
+        let cells: Vec<_> = Instance::grid(4)
+            .into_iter()
+            .map(|cell| cell.position)
+            .collect();
+        for shape in [&backend.cross, &backend.ring, &backend.cursor] {
+            assert_eq!(shape.origins, cells);
+        }
+        // the marks of the old board don't fit anymore, until the new board is known
+        assert!(!backend.cross.enabled.contains(&true));
+        backend.update_instances(&[Cell::Ring; 16]);
+        assert_eq!(backend.ring.enabled, [true; 16]);
+
+        // the same size again keeps the marks where they are
+        backend.rebuild_shapes(4, &Theme::DARK);
+        assert_eq!(backend.ring.enabled, [true; 16]);
+        assert!(backend.ring.appearing.is_empty());
+        backend.capture().unwrap();
+    }
+
+    #[test]
+    fn shutdown_waits_for_the_gpu() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        backend.update_instances(&[Cell::Cross; 9]);
+        backend.capture().unwrap();
+        let mut flow = ControlFlow::Exit;
+        backend.handle(Event::LoopDestroyed, &mut flow);
+        // nothing left in flight
+        assert!(backend.device.poll(wgpu::Maintain::Poll));
+    }
+
+    #[test]
+    fn cursor_outline_follows_the_selected_cell() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        backend.advance_clock(Duration::ZERO);
+        backend.set_cursor(Some(4));
+        assert_eq!(backend.cursor.active_ranges, vec![4..5]);
+
+        // the old outline shrinks away while the new one is already there
+        backend.set_cursor(Some(0));
+        assert_eq!(backend.cursor.active_ranges, vec![0..1, 4..5]);
+        backend.advance_clock(FADE_OUT_DURATION);
+        backend.prepare_shapes();
+        assert_eq!(backend.cursor.active_ranges, vec![0..1]);
+
+        backend.set_cursor(None);
+        backend.advance_clock(FADE_OUT_DURATION);
+        backend.prepare_shapes();
+        assert!(backend.cursor.active_ranges.is_empty());
+    }
+
+    #[test]
+    fn broken_shader_is_reported() {
+        let Some(backend) = backend() else {
+            return;
+        };
+        let layout = backend
+            .device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[],
+            });
+        let pipelines = Backend::create_pipelines(
+            &backend.device,
+            "fn vertex_main( -> {}",
+            &layout,
+            backend.preferred_format,
+            false,
+            backend.sample_count,
+        );
+        assert!(matches!(
+            pollster::block_on(pipelines),
+            Err(BackendError::ShaderError(_))
+        ));
+    }
+
+    #[test]
+    fn thinking_dots_fill_in_with_the_delay() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        let active = |backend: &Backend| backend.thinking.enabled.iter().filter(|&&on| on).count();
+        for (progress, dots) in [(0.0, 0), (0.5, 1), (0.99, 2), (1.0, 3)] {
+            backend.update_thinking(Some(progress));
+            assert_eq!(active(&backend), dots, "at {}", progress);
+        }
+        backend.update_thinking(None);
+        assert_eq!(active(&backend), 0);
+    }
+
+    #[test]
+    fn marks_fit_into_their_cells() {
+        let Some(backend) = backend() else {
+            return;
+        };
+        for board_size in 3..=MAX_BOARD_SIZE {
+            let half_cell = cell_size(board_size) / 2.0;
+            let scale = mark_scale(board_size);
+            let cells = Instance::grid(board_size);
+            for (name, shape) in [
+                ("cross", Shape::cross(&backend.device, scale, &cells)),
+                ("ring", Shape::ring(&backend.device, scale, &cells)),
+            ] {
+                let vertices = read_back(&backend, &shape.vertices);
+                let vertices: &[Vertex] = bytemuck::cast_slice(&vertices);
+                let extent = vertices
+                    .iter()
+                    .flat_map(|vertex| vertex.position)
+                    .fold(0.0_f32, |extent, coordinate| extent.max(coordinate.abs()));
+                assert!(
+                    extent < half_cell,
+                    "{} reaching {} out of {} on {}x{}",
+                    name,
+                    extent,
+                    half_cell,
+                    board_size,
+                    board_size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unchanged_frames_are_not_drawn_again() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        let mut board = [Cell::Empty; 9];
+        board[4] = Cell::Cross;
+        backend.advance_clock(Duration::ZERO);
+        backend.update_instances(&board);
+        backend.advance_clock(FADE_IN_DURATION);
+        backend.draw().unwrap();
+        assert!(!backend.dirty);
+        assert!(backend.draw_calls > 0);
+
+        // the very same state again changes nothing, so there's nothing to draw either
+        backend.draw_calls = 0;
+        backend.update_instances(&board);
+        backend.update_highlight(None);
+        backend.set_grid_visible(true);
+        assert!(!backend.dirty);
+        backend.draw().unwrap();
+        assert_eq!(backend.draw_calls, 0);
+
+        backend.update_highlight(Some(0));
+        assert!(backend.dirty);
+        backend.draw().unwrap();
+        assert!(backend.draw_calls > 0);
+    }
+
+    #[test]
+    fn view_is_uploaded_and_drawn_with() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        // whether every pixel in the left half of the frame is the same, so there's no board
+        let left_half_empty = |frame: &Frame| {
+            let mut left = frame
+                .rgba
+                .chunks_exact(4 * frame.width as usize)
+                .flat_map(|row| row[..row.len() / 2].chunks_exact(4));
+            let first = left.next().unwrap();
+            left.all(|pixel| pixel == first)
+        };
+        assert!(!left_half_empty(&backend.capture().unwrap()));
+
+        // half as large and moved into the top right quarter
+        let view = View {
+            scale: 0.5,
+            offset: [0.5, 0.5],
+            quarter_turns: 1,
+        };
+        backend.set_view(view);
+        let mut expected = Uniforms::default();
+        expected.set_view(view);
+        assert_eq!(
+            read_back(&backend, &backend.uniform_buffer),
+            bytemuck::bytes_of(&expected)
+        );
+        assert!(backend.dirty);
+
+        backend.draw().unwrap();
+        assert!(!backend.dirty);
+        assert!(left_half_empty(&backend.capture().unwrap()));
+    }
+
+    #[test]
+    fn tints_look_the_same_pushed_or_baked() {
+        let adapter = || pollster::block_on(Backend::fallback_adapter());
+        let Ok(probe) = adapter() else {
+            eprintln!("Skipping, no adapter");
+            return;
+        };
+        let supported = probe.features().contains(wgpu::Features::PUSH_CONSTANTS);
+        let capture = |push_constants| {
+            let backend = Backend::on_adapter_with(
+                adapter().unwrap(),
+                push_constants,
+                wgpu::TextureFormat::Rgba8UnormSrgb,
+                LimitsMode::Balanced,
+                Layout::Board(3),
+            );
+            let mut backend = pollster::block_on(backend).unwrap();
+            assert_eq!(backend.push_constants, push_constants);
+            backend.window_size = dpi::PhysicalSize::new(64, 64);
+
+            let mut board = [Cell::Empty; 9];
+            board[4] = Cell::Ring;
+            backend.advance_clock(Duration::ZERO);
+            backend.update_instances(&board);
+            // tinted at a fifth of its color
+            backend.update_win_line(&[0, 4, 8]);
+            backend.advance_clock(FADE_IN_DURATION);
+            backend.capture().unwrap()
+        };
+
+        let baked = capture(false);
+        if supported {
+            assert_eq!(capture(true).rgba, baked.rgba);
+        } else {
+            eprintln!("Only tried baked tints, push constants aren't supported");
+        }
+    }
+
+    #[test]
+    fn highlight_is_translucent_over_marks() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        // the center of the frame, where the two strokes of a cross in the center cell meet
+        let center = |backend: &mut Backend, cross: bool, highlight: bool| {
+            let mut board = [Cell::Empty; 9];
+            if cross {
+                board[4] = Cell::Cross;
+            }
+            backend.advance_clock(Duration::ZERO);
+            backend.update_instances(&board);
+            backend.update_highlight(highlight.then_some(4));
+            // long after everything faded in or out
+            backend.advance_clock(Duration::from_secs(5));
+            let frame = backend.capture().unwrap();
+            let at = (32 * frame.width as usize + 32) * 4;
+            frame.rgba[at..at + 4].to_vec()
+        };
+
+        let mark = center(&mut backend, true, false);
+        let highlight = center(&mut backend, false, true);
+        let both = center(&mut backend, true, true);
+        // drawn over the mark, but the mark still shines through
+        assert_ne!(both, mark);
+        assert_ne!(both, highlight);
+    }
+
+    #[test]
+    fn mis_wound_shapes_show_only_when_double_sided() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        // clockwise, so its front faces away
+        let vertices = vertices! {
+            color: { r: 1.0, g: 1.0, b: 1.0 },
+            position: [
+                -0.5, -0.5;
+                -0.5, 0.5;
+                0.5, 0.5;
+                0.5, -0.5;
             ],
-            &[Instance {
-                position: [0.0, 0.0],
-            }]
-        )
+        };
+        let mut shape = Shape::new(
+            &backend.device,
+            vertices,
+            &[0, 1, 2, 0, 2, 3],
+            &[Instance::new([0.0, 0.0])],
+        );
+        shape.update_instances(std::iter::once(true), Instant::now());
+        backend.highlight = shape;
+
+        let center = |backend: &mut Backend| {
+            let frame = backend.capture().unwrap();
+            let at = (32 * frame.width as usize + 32) * 4;
+            frame.rgba[at..at + 4].to_vec()
+        };
+        assert_ne!(center(&mut backend), [255; 4]);
+        backend.highlight.double_sided = true;
+        assert_eq!(center(&mut backend), [255; 4]);
+    }
+
+    #[test]
+    fn turned_instances_cover_their_turned_bounds() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        // wide and flat, beside the center so turning around the wrong origin would show
+        let half_size = [0.6, 0.1];
+        let offset = [0.25, 0.0];
+
+        for rotation in [0.0, std::f32::consts::FRAC_PI_2] {
+            let instance = Instance {
+                rotation,
+                ..Instance::new(offset)
+            };
+            let mut shape = Shape::rectangle(&backend.device, half_size, &[instance]);
+            shape.update_instances(std::iter::once(true), Instant::now());
+            backend.highlight = shape;
+            let frame = backend.capture().unwrap();
+
+            // the same as the shader does, in pixels of the frame with y+ down
+            let (sin, cos) = rotation.sin_cos();
+            let corners = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]].map(|[x, y]| {
+                let [x, y] = [x * half_size[0], y * half_size[1]];
+                let [x, y] = [cos * x - sin * y + offset[0], sin * x + cos * y + offset[1]];
+                [(x + 1.0) / 2.0 * 64.0, (1.0 - y) / 2.0 * 64.0]
+            });
+            let min = |axis: usize| corners.iter().map(|c| c[axis]).fold(f32::MAX, f32::min);
+            let max = |axis: usize| corners.iter().map(|c| c[axis]).fold(f32::MIN, f32::max);
+            // only pixels whose center is covered are completely white
+            let expected = [
+                (min(0) - 0.5).ceil() as usize,
+                (max(0) - 0.5).floor() as usize,
+                (min(1) - 0.5).ceil() as usize,
+                (max(1) - 0.5).floor() as usize,
+            ];
+
+            let white: Vec<(usize, usize)> = (0..frame.height as usize)
+                .flat_map(|y| (0..frame.width as usize).map(move |x| (x, y)))
+                .filter(|&(x, y)| {
+                    let at = (y * frame.width as usize + x) * 4;
+                    frame.rgba[at..at + 4] == [255; 4]
+                })
+                .collect();
+            let bounds = [
+                white.iter().map(|&(x, _)| x).min().unwrap(),
+                white.iter().map(|&(x, _)| x).max().unwrap(),
+                white.iter().map(|&(_, y)| y).min().unwrap(),
+                white.iter().map(|&(_, y)| y).max().unwrap(),
+            ];
+            for (bound, expected) in bounds.into_iter().zip(expected) {
+                assert!(
+                    bound.abs_diff(expected) <= 1,
+                    "turned by {}: {:?} instead of {:?}",
+                    rotation,
+                    bounds,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stats_count_the_marks_on_the_board() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        let active = |backend: &Backend, name| {
+            let stats = backend.stats();
+            let shape = stats.shapes.iter().find(|shape| shape.name == name);
+            shape.unwrap().active_instances
+        };
+        assert_eq!(backend.stats().shapes.len(), Backend::SHAPE_NAMES.len());
+        assert_eq!(active(&backend, "cross"), 0);
+
+        let mut board = [Cell::Empty; 9];
+        for index in [0, 4, 8] {
+            board[index] = Cell::Cross;
+        }
+        board[1] = Cell::Ring;
+        backend.advance_clock(Duration::ZERO);
+        backend.update_instances(&board);
+        assert_eq!(active(&backend, "cross"), 3);
+        assert_eq!(active(&backend, "ring"), 1);
+        assert_eq!(active(&backend, "grid"), 1);
+
+        // still drawn while fading out, but not anymore once gone
+        backend.update_instances(&[Cell::Empty; 9]);
+        assert_eq!(active(&backend, "cross"), 3);
+        backend.advance_clock(FADE_OUT_DURATION);
+        backend.prepare_shapes();
+        assert_eq!(active(&backend, "cross"), 0);
+    }
+
+    #[test]
+    fn one_tally_per_win_up_to_the_cap() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        let tallied = |backend: &Backend| {
+            let (cross, ring) = backend.tallies.enabled.split_at(TALLY_CAP as usize);
+            let count = |row: &[bool]| row.iter().filter(|&&enabled| enabled).count();
+            (count(cross), count(ring))
+        };
+
+        backend.update_score(Some(Score { cross: 3, ring: 0 }));
+        assert_eq!(tallied(&backend), (3, 0));
+        assert!(backend.score_cross.enabled[0] && backend.score_ring.enabled[0]);
+
+        backend.update_score(Some(Score {
+            cross: 3,
+            ring: TALLY_CAP + 5,
+        }));
+        assert_eq!(tallied(&backend), (3, TALLY_CAP as usize));
+
+        backend.update_score(None);
+        assert_eq!(tallied(&backend), (0, 0));
+        assert!(!backend.score_cross.enabled[0]);
+    }
+
+    #[test]
+    fn surface_settings_are_picked_from_the_capabilities() {
+        use wgpu::{CompositeAlphaMode as Alpha, PresentMode, TextureFormat as Format};
+
+        // as a surface might report them
+        let caps = wgpu::SurfaceCapabilities {
+            formats: vec![
+                Format::Bgra8Unorm,
+                Format::Rgba16Float,
+                Format::Bgra8UnormSrgb,
+            ],
+            present_modes: vec![PresentMode::Mailbox, PresentMode::Fifo],
+            alpha_modes: vec![Alpha::Opaque, Alpha::PreMultiplied],
+        };
+        assert_eq!(select_format(&caps.formats), Some(Format::Bgra8UnormSrgb));
+        assert_eq!(select_present_mode(&caps.present_modes), PresentMode::Fifo);
+        assert_eq!(select_alpha_mode(&caps.alpha_modes, false), Alpha::Opaque);
+        assert_eq!(
+            select_alpha_mode(&caps.alpha_modes, true),
+            Alpha::PreMultiplied
+        );
+
+        // without anything preferred, the first supported one is taken
+        assert_eq!(
+            select_format(&[Format::Rgba16Float, Format::Bgra8Unorm]),
+            Some(Format::Rgba16Float)
+        );
+        assert_eq!(select_format(&[]), None);
+        assert_eq!(
+            select_present_mode(&[PresentMode::Immediate]),
+            PresentMode::Immediate
+        );
+        assert_eq!(
+            select_alpha_mode(&[Alpha::Inherit, Alpha::PostMultiplied], false),
+            Alpha::Inherit
+        );
+    }
+
+    #[test]
+    fn drawing_adapter_is_listed() {
+        // before there's a backend, on GL dropping the instance listing them tears down the display
+        // the backend draws with
+        let adapters = Backend::list_adapters();
+        let Some(backend) = backend() else {
+            return;
+        };
+        assert!(!adapters.is_empty());
+        assert!(adapters.contains(&backend.adapter().unwrap()));
+        assert_eq!(NullRenderer.adapter(), None);
+    }
+
+    #[test]
+    fn only_events_for_its_own_window_are_handled() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        let own = WindowId::from(1);
+        let other = WindowId::from(2);
+        backend.window_id = Some(own);
+        let mut send = |window_id, event| {
+            let mut flow = ControlFlow::Wait;
+            backend.handle(Event::WindowEvent { window_id, event }, &mut flow);
+            (flow, backend.window_size)
+        };
+        let resized = || WindowEvent::Resized(dpi::PhysicalSize::new(32, 32));
+        let unchanged = dpi::PhysicalSize::new(64, 64);
+        let changed = dpi::PhysicalSize::new(32, 32);
+
+        assert_eq!(send(other, resized()), (ControlFlow::Wait, unchanged));
+        assert_eq!(
+            send(other, WindowEvent::CloseRequested).0,
+            ControlFlow::Wait
+        );
+        assert_eq!(send(own, resized()), (ControlFlow::Wait, changed));
+        assert_eq!(send(own, WindowEvent::CloseRequested).0, ControlFlow::Exit);
+    }
+
+    #[test]
+    fn revealed_ring_is_complete_once_done() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        let mut board = [Cell::Empty; 9];
+        board[4] = Cell::Ring;
+        let settled = FADE_IN_DURATION.max(REVEAL_DURATION);
+        backend.advance_clock(Duration::ZERO);
+        backend.update_instances(&board);
+        backend.advance_clock(settled);
+        let placed = backend.capture().unwrap();
+        // on GL, a backend still around tears down the display of the next one once dropped
+        drop(backend);
+
+        let mut backend = self::backend().unwrap();
+        backend.advance_clock(Duration::ZERO);
+        backend.update_instances(&board);
+        backend.reveal_mark(4);
+        backend.advance_clock(REVEAL_DURATION / 2);
+        backend.prepare_shapes();
+        let (_, _, halfway) = backend.ring.revealing[0];
+        assert!(0 < halfway && halfway < backend.ring.index_count);
+        assert_ne!(backend.capture().unwrap().rgba, placed.rgba);
+
+        backend.advance_clock(settled - REVEAL_DURATION / 2);
+        backend.prepare_shapes();
+        assert!(backend.ring.revealing.is_empty());
+        assert_eq!(backend.ring.active_ranges, vec![4..5]);
+        assert_eq!(backend.capture().unwrap().rgba, placed.rgba);
+    }
+
+    #[test]
+    fn draws_and_wins_have_their_own_background() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        // nothing is drawn into the top left corner of an empty board, so only the background is
+        // there
+        let mut corner = |background| {
+            backend.set_background(background);
+            backend.capture().unwrap().rgba[..3].to_vec()
+        };
+        // the frame is sRGB, while the background is linear
+        let encoded = |color: wgpu::Color| {
+            [color.r, color.g, color.b].map(|linear| {
+                let srgb = if linear <= 0.003_130_8 {
+                    12.92 * linear
+                } else {
+                    1.055 * linear.powf(1.0 / 2.4) - 0.055
+                };
+                (srgb * 255.0).round() as i32
+            })
+        };
+
+        for theme in [Theme::DARK, Theme::LIGHT] {
+            let drawn = corner(theme.background_drawn);
+            let won = corner(theme.background_won);
+            assert_ne!(drawn, won);
+            for (shown, expected) in [(drawn, theme.background_drawn), (won, theme.background_won)]
+            {
+                for (shown, expected) in shown.into_iter().zip(encoded(expected)) {
+                    assert!((i32::from(shown) - expected).abs() <= 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_limits_mode_draws_the_same() {
+        let size = dpi::PhysicalSize::new(64, 64);
+        let mut frames = Vec::new();
+        for mode in [
+            LimitsMode::Strict,
+            LimitsMode::Adapter,
+            LimitsMode::Balanced,
+        ] {
+            let backend = Backend::headless(size, mode, Layout::Board(3));
+            let Ok(mut backend) = pollster::block_on(backend) else {
+                return;
+            };
+            let limits = backend.device.limits();
+            assert!(limits.max_texture_dimension_2d >= 2048, "{:?}", mode);
+            if mode != LimitsMode::Strict {
+                assert_eq!(
+                    limits.max_texture_dimension_2d,
+                    backend.adapter.limits().max_texture_dimension_2d
+                );
+            }
+
+            let mut board = [Cell::Empty; 9];
+            board[4] = Cell::Cross;
+            backend.advance_clock(Duration::ZERO);
+            backend.update_instances(&board);
+            backend.advance_clock(FADE_IN_DURATION);
+            frames.push(backend.capture().unwrap());
+        }
+        assert!(frames.windows(2).all(|pair| pair[0].rgba == pair[1].rgba));
+    }
+
+    #[test]
+    fn too_large_frames_are_not_captured() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        let side = backend.device.limits().max_texture_dimension_2d + 1;
+        backend.window_size = dpi::PhysicalSize::new(side, 1);
+        assert!(matches!(
+            backend.capture(),
+            Err(CaptureError::TooLarge(width, 1)) if width == side
+        ));
+
+        backend.window_size = dpi::PhysicalSize::new(64, 64);
+        assert!(backend.capture().is_ok());
+    }
+
+    #[test]
+    fn windows_larger_than_textures_are_drawn_scaled_down() {
+        let size = dpi::PhysicalSize::new(64, 64);
+        let backend = Backend::headless(size, LimitsMode::Strict, Layout::Board(3));
+        let Ok(mut backend) = pollster::block_on(backend) else {
+            return;
+        };
+        let max = backend.device.limits().max_texture_dimension_2d;
+        assert_eq!(backend.frame_size(), size);
+
+        // e.g. maximized on a wide screen
+        backend.window_size = dpi::PhysicalSize::new(max * 2, 64);
+        assert_eq!(backend.frame_size(), dpi::PhysicalSize::new(max, 32));
+        backend.draw().unwrap();
+        assert_eq!(backend.surface_size, Some(backend.window_size));
+        // so thin that it'd vanish completely
+        backend.window_size = dpi::PhysicalSize::new(1, max * 4);
+        assert_eq!(backend.frame_size(), dpi::PhysicalSize::new(1, max));
+        backend.draw().unwrap();
+    }
+
+    #[test]
+    fn sample_count_is_what_the_adapter_supports() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        let flags = backend
+            .adapter
+            .get_texture_format_features(backend.preferred_format)
+            .flags;
+        assert_eq!(
+            backend.sample_count == SAMPLE_COUNT,
+            flags.sample_count_supported(SAMPLE_COUNT)
+        );
+        assert_eq!(
+            sample_count(&backend.adapter, backend.preferred_format),
+            backend.sample_count
+        );
+
+        backend.capture().unwrap();
+        assert_eq!(backend.multisampled.is_some(), backend.sample_count > 1);
+    }
+
+    #[test]
+    fn first_frame_has_the_final_size() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        assert_eq!(backend.surface_size, None);
+        // resized before anything was drawn, e.g. to fit a scaled monitor
+        backend.window_size = dpi::PhysicalSize::new(96, 96);
+        backend.draw().unwrap();
+        assert_eq!(backend.surface_size, Some(backend.window_size));
+        assert_eq!(backend.capture().unwrap().width, 96);
+    }
+
+    #[test]
+    fn breathing_grid_keeps_drawing() {
+        let Some(mut backend) = backend() else {
+            return;
+        };
+        backend.advance_clock(Duration::ZERO);
+        backend.set_idle(true);
+        backend.draw().unwrap();
+        assert!(!backend.dirty);
+        backend.draw_calls = 0;
+        // nothing changed, but the grid moves on its own
+        backend.draw().unwrap();
+        assert!(backend.draw_calls > 0);
+
+        backend.advance_clock(BREATH_PERIOD / 4);
+        backend.prepare_shapes();
+        let scale = backend.grid.instances[0].scale;
+        assert!((scale - (1.0 + BREATH_AMPLITUDE)).abs() < 1e-4);
+
+        backend.set_idle(false);
+        backend.draw().unwrap();
+        assert_eq!(backend.grid.instances[0].scale, 1.0);
+        backend.draw_calls = 0;
+        backend.draw().unwrap();
+        assert_eq!(backend.draw_calls, 0);
     }
 }
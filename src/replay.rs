@@ -0,0 +1,91 @@
+use {
+    super::{
+        ai,
+        announce::cell_name,
+        game::{DecodeError, Faction, Game, MoveError, Outcome, BOARD_SIZE},
+    },
+    std::{fs, io},
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("Missing value for {0}")]
+    MissingValue(&'static str),
+    #[error("Invalid cell {0:?}, expected 0 to 8")]
+    InvalidCell(String),
+    #[error("Unknown faction {0:?}, expected one of `cross` or `ring`")]
+    UnknownFaction(String),
+    #[error("The moves are not a valid game: {0}")]
+    IllegalMove(#[from] MoveError),
+    #[error("Could not read saved game: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid saved game: {0}")]
+    Decode(#[from] DecodeError),
+}
+
+/// A logged game to be looked at move by move, without opening a window.
+#[derive(Debug)]
+pub struct Replay {
+    game: Game,
+}
+
+impl Replay {
+    /// Parses the arguments following `--replay`, which are the cells marked in order, separated
+    /// by whitespace or commas, and optionally `--first <faction>` for who made the first move.
+    /// Instead of the cells, `--saved <path>` replays a game saved from the window.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, ReplayError> {
+        let mut moves = Vec::new();
+        let mut first = Faction::Ring;
+
+        while let Some(arg) = args.next() {
+            if arg == "--saved" {
+                let path = args.next().ok_or(ReplayError::MissingValue("--saved"))?;
+                return Ok(Self {
+                    game: Game::from_bytes(&fs::read(path)?)?,
+                });
+            }
+            if arg == "--first" {
+                let value = args.next().ok_or(ReplayError::MissingValue("--first"))?;
+                first = match value.as_str() {
+                    "cross" => Faction::Cross,
+                    "ring" => Faction::Ring,
+                    _ => return Err(ReplayError::UnknownFaction(value)),
+                };
+                continue;
+            }
+
+            for cell in arg.split(',').filter(|cell| !cell.is_empty()) {
+                match cell.trim().parse() {
+                    Ok(index @ 0..=8) => moves.push(index),
+                    _ => return Err(ReplayError::InvalidCell(cell.to_string())),
+                }
+            }
+        }
+
+        Ok(Self {
+            game: Game::replay_from_moves(&moves, first)?,
+        })
+    }
+
+    /// Prints every move with how good it was, followed by how the game ended.
+    pub fn print(&self) {
+        let analysis = ai::analyze(&self.game);
+        for (number, (played, quality)) in self.game.history().zip(analysis).enumerate() {
+            println!(
+                "{}. {:?} on {}: {}",
+                number + 1,
+                played.faction,
+                cell_name(played.index, BOARD_SIZE),
+                quality
+            );
+        }
+
+        match self.game.outcome() {
+            Some(Outcome::Win(winner)) => println!("{:?} wins", winner),
+            Some(Outcome::Draw) => println!("Draw"),
+            None => println!("Not over yet, {:?} to move", self.game.turn()),
+        }
+        println!("Fingerprint {:016x}", self.game.fingerprint());
+    }
+}
@@ -0,0 +1,113 @@
+use super::game::{Faction, Outcome};
+
+/// Counts how many games in a row the user got through without losing, for the challenge mode.
+///
+/// Against the minimax AI, a draw is the best one can get, so this is effectively a draw streak.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Streak {
+    current: u32,
+    best: u32,
+}
+
+impl Streak {
+    /// Records a finished game, extending the streak unless the user lost it.
+    pub fn record(&mut self, winner: Option<Faction>, user_faction: Faction) {
+        if winner == Some(user_faction.opposite()) {
+            self.current = 0;
+        } else {
+            self.current += 1;
+            self.best = self.best.max(self.current);
+        }
+    }
+
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    pub fn best(&self) -> u32 {
+        self.best
+    }
+}
+
+/// How the games against the AI in this session ended, from the user's point of view.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl Tally {
+    /// Records a finished game in which the user played the given faction.
+    pub fn record(&mut self, outcome: Outcome, user_faction: Faction) {
+        match outcome {
+            Outcome::Win(winner) if winner == user_faction => self.wins += 1,
+            Outcome::Win(_) => self.losses += 1,
+            Outcome::Draw => self.draws += 1,
+        }
+    }
+
+    /// How many games have been recorded at all.
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+}
+
+/// How many games each faction won in this session, draws aside.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    pub cross: u32,
+    pub ring: u32,
+}
+
+impl Score {
+    /// Records a finished game, counting it for its winner if there is one.
+    pub fn record(&mut self, winner: Option<Faction>) {
+        match winner {
+            Some(Faction::Cross) => self.cross += 1,
+            Some(Faction::Ring) => self.ring += 1,
+            None => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::game::Game};
+
+    #[test]
+    fn loss_ends_draw_streak_but_keeps_best() {
+        let mut streak = Streak::default();
+        for _ in 0..3 {
+            streak.record(None, Faction::Ring);
+        }
+        assert_eq!((streak.current(), streak.best()), (3, 3));
+
+        streak.record(Some(Faction::Cross), Faction::Ring);
+        assert_eq!((streak.current(), streak.best()), (0, 3));
+
+        streak.record(None, Faction::Ring);
+        // winning against the AI would count just as well, it's only not happening with minimax
+        streak.record(Some(Faction::Ring), Faction::Ring);
+        assert_eq!((streak.current(), streak.best()), (2, 3));
+    }
+
+    #[test]
+    fn win_with_the_last_cell_is_no_draw() {
+        // ring completes the diagonal from the bottom left with the ninth mark
+        let game = Game::replay_from_moves(&[0, 1, 2, 3, 4, 5, 7, 6, 8], Faction::Ring).unwrap();
+        assert!(game.board().iter().all(|cell| !cell.is_empty()));
+        let outcome = game.outcome().unwrap();
+        assert_eq!(outcome, Outcome::Win(Faction::Ring));
+
+        let mut tally = Tally::default();
+        tally.record(outcome, Faction::Ring);
+        assert_eq!((tally.wins, tally.losses, tally.draws), (1, 0, 0));
+        let mut score = Score::default();
+        score.record(game.winner());
+        assert_eq!(score, Score { cross: 0, ring: 1 });
+        let mut streak = Streak::default();
+        streak.record(game.winner(), Faction::Cross);
+        assert_eq!(streak.current(), 0);
+    }
+}
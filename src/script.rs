@@ -0,0 +1,95 @@
+use {
+    super::AppEvent,
+    std::{
+        io::{self, BufRead},
+        thread,
+    },
+    winit::event_loop::EventLoopProxy,
+};
+
+/// Spawns a thread reading one cell index or `reset` per line from stdin, and forwards each to the
+/// event loop. Allows scripts to play in a real window.
+pub fn spawn_stdin_reader(proxy: EventLoopProxy<AppEvent>) {
+    thread::spawn(move || read_script(io::stdin().lock(), |event| proxy.send_event(event)));
+}
+
+/// Reads one cell index or `reset` per line from the given input, and passes each on to `send`
+/// until the input ends or `send` fails.
+pub fn read_script<E>(input: impl BufRead, mut send: impl FnMut(AppEvent) -> Result<(), E>) {
+    for line in input.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                log::error!("Could not read script: {}", e);
+                return;
+            }
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let event = match line {
+            "reset" => AppEvent::ResetGame,
+            _ => match line.parse() {
+                // whether the cell is on the board at all is up to the game
+                Ok(index) => AppEvent::ApplyMove(index),
+                Err(_) => {
+                    log::warn!(
+                        "Ignoring {:?} in script, expected a cell index or `reset`",
+                        line
+                    );
+                    continue;
+                }
+            },
+        };
+
+        // only fails if the event loop is gone, so there's no one to play with anymore
+        if send(event).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(script: &str) -> Vec<AppEvent> {
+        let mut events = Vec::new();
+        read_script(script.as_bytes(), |event| {
+            events.push(event);
+            Ok::<_, ()>(())
+        });
+        events
+    }
+
+    #[test]
+    fn reads_moves_and_resets() {
+        assert_eq!(
+            read("4\n  0 \n\nreset\n8\n"),
+            vec![
+                AppEvent::ApplyMove(4),
+                AppEvent::ApplyMove(0),
+                AppEvent::ResetGame,
+                AppEvent::ApplyMove(8),
+            ],
+        );
+    }
+
+    #[test]
+    fn skips_invalid_lines() {
+        assert_eq!(read("middle\n-1\n3\n"), vec![AppEvent::ApplyMove(3)]);
+    }
+
+    #[test]
+    fn stops_once_sending_fails() {
+        let mut sent = 0;
+        read_script("1\n2\n3\n".as_bytes(), |_| {
+            sent += 1;
+            Err(())
+        });
+        assert_eq!(sent, 1);
+    }
+}
@@ -0,0 +1,390 @@
+use {
+    super::{
+        ai::{AiStrategy, Difficulty},
+        button::PressOrRelease,
+        chooser::ChooserMode,
+        daily::Date,
+        game::{FactionChoice, Handicap, Lines, BOARD_SIZE, MAX_BOARD_SIZE},
+        render::LimitsMode,
+    },
+    std::{env, fmt::Display, num::ParseIntError, path::PathBuf, str::FromStr, time::Duration},
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+#[error("Invalid value {value:?} for {name}: {reason}")]
+pub struct SettingsError {
+    name: &'static str,
+    value: String,
+    reason: String,
+}
+
+/// Everything configurable about the game, read once at startup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Seed for all randomness in the game. `None` means a different one each run. `daily` in
+    /// the environment variable turns into the seed of the current day, see [`Date::seed`].
+    pub seed: Option<u64>,
+    pub faction: FactionChoice,
+    pub strategy: AiStrategy,
+    /// Whether to read scripted moves from stdin.
+    pub stdin: bool,
+    /// Whether to print every move and result to stdout as a sentence, for screen readers.
+    pub announce: bool,
+    /// Whether the window background should be see-through.
+    pub transparent: bool,
+    /// Whether to play against an unbeatable AI while tracking how long the user survives.
+    pub challenge: bool,
+    /// Whether to show cells as buttons, which react to hovering and pressing.
+    pub buttons: bool,
+    /// Whether marks are placed when the mouse button goes down or up.
+    pub place_on: PressOrRelease,
+    /// How long clicks are ignored after placing a mark, so a double click doesn't place twice.
+    pub debounce: Duration,
+    /// Whether to snap the window back to a square whenever it's resized to something else.
+    pub square: bool,
+    /// How long the AI pretends to think before answering a move.
+    pub thinking_delay: Duration,
+    /// Whether a click while the AI thinks is placed right after its move, instead of ignored.
+    pub queue_clicks: bool,
+    /// When to let the user pick their faction and opponent by key.
+    pub chooser: ChooserMode,
+    /// Which cells have to be filled by one faction to end the game, and whether that wins or
+    /// loses it. Also decides how many cells the board has per side.
+    pub lines: Lines,
+    /// Marks placed before each game starts.
+    pub handicap: Handicap,
+    /// Index of the GPU adapter to draw with, as listed by `--list-adapters`. `None` lets wgpu
+    /// decide.
+    pub adapter: Option<usize>,
+    /// Which limits to request the device with.
+    pub limits: LimitsMode,
+    /// Whether to open a second window listing the moves of the current game.
+    pub move_list: bool,
+    /// Whether the AI's marks appear stroke by stroke, as if they were being drawn.
+    pub strokes: bool,
+    /// Whether marks are placed slightly off and turned, as if placed by hand.
+    pub jitter: bool,
+    /// Whether the selected cell is outlined while it could be marked.
+    pub cursor: bool,
+    /// Whether to stay quiet even if built with sound.
+    pub mute: bool,
+    /// Whether to show how many games each faction won as tallies in the top left corner.
+    pub score: bool,
+    /// Whether the grid breathes slightly while waiting for the user's move.
+    pub idle_animation: bool,
+    /// How many of the user's turns can be taken back at most. `None` allows going back to the
+    /// very start.
+    pub undo_levels: Option<usize>,
+    /// Directory to save every frame into, for turning a game into a video afterwards.
+    pub record: Option<PathBuf>,
+    /// How many frames per second a recording has, which fixes how far animations advance
+    /// between two frames.
+    pub record_fps: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            faction: FactionChoice::Random,
+            strategy: Difficulty::Medium.into(),
+            stdin: false,
+            announce: false,
+            transparent: false,
+            challenge: false,
+            buttons: false,
+            place_on: PressOrRelease::Release,
+            debounce: Duration::from_millis(120),
+            square: false,
+            thinking_delay: Duration::ZERO,
+            queue_clicks: false,
+            chooser: ChooserMode::Off,
+            lines: Lines::default(),
+            handicap: Handicap::default(),
+            adapter: None,
+            limits: LimitsMode::Balanced,
+            move_list: false,
+            strokes: false,
+            jitter: false,
+            cursor: true,
+            mute: false,
+            score: false,
+            idle_animation: false,
+            undo_levels: None,
+            record: None,
+            record_fps: 30,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads all settings from their `TICTACGPU_*` environment variables, using the default for
+    /// each one that isn't set.
+    pub fn from_env() -> Result<Self, SettingsError> {
+        let defaults = Self::default();
+        let size =
+            parse_with("TICTACGPU_BOARD_SIZE", parse_board_size)?.unwrap_or(defaults.lines.size());
+        let on_board = |value: &str| parse_win_length(value, size);
+        let length = parse_with("TICTACGPU_WIN_LENGTH", on_board)?;
+        let lines = parse_with("TICTACGPU_LINES", |path| Lines::from_file(path, size))?;
+        if let (Some(length), Some(_)) = (length, &lines) {
+            log::warn!(
+                "Ignoring TICTACGPU_WIN_LENGTH of {}, the lines from TICTACGPU_LINES decide how \
+                 long they are",
+                length
+            );
+        }
+        let lines = lines
+            .unwrap_or_else(|| Lines::in_a_row(size, length.unwrap_or(size)))
+            .with_win_mode(parse("TICTACGPU_WIN_MODE")?.unwrap_or_default());
+        // only the lines can tell whether the handicap leaves anything to play
+        let handicap = parse_with("TICTACGPU_HANDICAP", |source| {
+            source
+                .parse()
+                .and_then(|handicap: Handicap| handicap.validate(&lines))
+        })?
+        .unwrap_or(defaults.handicap);
+
+        Ok(Self {
+            seed: parse_with("TICTACGPU_SEED", parse_seed)?.or(defaults.seed),
+            faction: parse("TICTACGPU_FACTION")?.unwrap_or(defaults.faction),
+            strategy: parse("TICTACGPU_STRATEGY")?.unwrap_or(defaults.strategy),
+            stdin: parse_flag("TICTACGPU_STDIN")?.unwrap_or(defaults.stdin),
+            announce: parse_flag("TICTACGPU_ANNOUNCE")?.unwrap_or(defaults.announce),
+            transparent: parse_flag("TICTACGPU_TRANSPARENT")?.unwrap_or(defaults.transparent),
+            challenge: parse_flag("TICTACGPU_CHALLENGE")?.unwrap_or(defaults.challenge),
+            buttons: parse_flag("TICTACGPU_BUTTONS")?.unwrap_or(defaults.buttons),
+            place_on: parse("TICTACGPU_PLACE_ON")?.unwrap_or(defaults.place_on),
+            debounce: parse("TICTACGPU_DEBOUNCE")?
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.debounce),
+            square: parse_flag("TICTACGPU_SQUARE")?.unwrap_or(defaults.square),
+            thinking_delay: parse("TICTACGPU_THINKING_DELAY")?
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.thinking_delay),
+            queue_clicks: parse_flag("TICTACGPU_QUEUE_CLICKS")?.unwrap_or(defaults.queue_clicks),
+            chooser: parse("TICTACGPU_CHOOSER")?.unwrap_or(defaults.chooser),
+            lines,
+            handicap,
+            adapter: parse("TICTACGPU_ADAPTER")?.or(defaults.adapter),
+            limits: parse("TICTACGPU_LIMITS")?.unwrap_or(defaults.limits),
+            move_list: parse_flag("TICTACGPU_MOVE_LIST")?.unwrap_or(defaults.move_list),
+            strokes: parse_flag("TICTACGPU_STROKES")?.unwrap_or(defaults.strokes),
+            jitter: parse_flag("TICTACGPU_JITTER")?.unwrap_or(defaults.jitter),
+            cursor: parse_flag("TICTACGPU_CURSOR")?.unwrap_or(defaults.cursor),
+            mute: parse_flag("TICTACGPU_MUTE")?.unwrap_or(defaults.mute),
+            score: parse_flag("TICTACGPU_SCORE")?.unwrap_or(defaults.score),
+            idle_animation: parse_flag("TICTACGPU_IDLE_ANIMATION")?
+                .unwrap_or(defaults.idle_animation),
+            undo_levels: parse("TICTACGPU_UNDO_LEVELS")?.or(defaults.undo_levels),
+            record: parse("TICTACGPU_RECORD")?.or(defaults.record),
+            record_fps: parse("TICTACGPU_RECORD_FPS")?.unwrap_or(defaults.record_fps),
+        })
+    }
+
+    /// Logs the effective configuration, so runs can be reproduced.
+    pub fn log(&self) {
+        log::info!(
+            "Settings: seed {}, faction {:?}, strategy {}, stdin {}, announce {}, transparent {}, \
+             challenge {}, buttons {}, place on {:?}, debounce {}ms, square {}, thinking delay \
+             {}ms, queue clicks {}, chooser {:?}, {} win lines ({:?}) on {}x{} cells, handicap {}, \
+             adapter {}, limits {:?}, move list {}, strokes {}, jitter {}, cursor {}, mute {}, \
+             score {}, idle animation {}, undo levels {}, record {} at {} fps",
+            self.seed
+                .map_or_else(|| "random".to_string(), |seed| seed.to_string()),
+            self.faction,
+            self.strategy,
+            self.stdin,
+            self.announce,
+            self.transparent,
+            self.challenge,
+            self.buttons,
+            self.place_on,
+            self.debounce.as_millis(),
+            self.square,
+            self.thinking_delay.as_millis(),
+            self.queue_clicks,
+            self.chooser,
+            self.lines.len(),
+            self.lines.win_mode(),
+            self.lines.size(),
+            self.lines.size(),
+            self.handicap,
+            self.adapter
+                .map_or_else(|| "automatic".to_string(), |index| index.to_string()),
+            self.limits,
+            self.move_list,
+            self.strokes,
+            self.jitter,
+            self.cursor,
+            self.mute,
+            self.score,
+            self.idle_animation,
+            self.undo_levels
+                .map_or_else(|| "unlimited".to_string(), |levels| levels.to_string()),
+            self.record
+                .as_ref()
+                .map_or_else(|| "off".to_string(), |dir| dir.display().to_string()),
+            self.record_fps,
+        );
+    }
+}
+
+// Parses the given environment variable, if it is set at all.
+fn parse<T>(name: &'static str) -> Result<Option<T>, SettingsError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    parse_with(name, str::parse)
+}
+
+// Same as `parse`, but with a custom parsing function.
+fn parse_with<T, E, F>(name: &'static str, parse: F) -> Result<Option<T>, SettingsError>
+where
+    E: Display,
+    F: FnOnce(&str) -> Result<T, E>,
+{
+    env::var(name)
+        .ok()
+        .map(|value| {
+            parse(&value).map_err(|e| SettingsError {
+                name,
+                reason: e.to_string(),
+                value,
+            })
+        })
+        .transpose()
+}
+
+// Seeds are plain numbers, or `daily` for the one everyone gets on the same day.
+fn parse_seed(value: &str) -> Result<u64, ParseIntError> {
+    if value == "daily" {
+        let today = Date::today();
+        log::info!("Using the daily seed of {}", today);
+        return Ok(today.seed());
+    }
+    value.parse()
+}
+
+// Boards go from the classic size up to the largest one the AI can still look through quickly.
+fn parse_board_size(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(size @ BOARD_SIZE..=MAX_BOARD_SIZE) => Ok(size),
+        _ => Err(format!(
+            "expected a number from {} to {}",
+            BOARD_SIZE, MAX_BOARD_SIZE
+        )),
+    }
+}
+
+// Runs have to fit onto the board, and any shorter than on the classic board would be over
+// before it even began.
+fn parse_win_length(value: &str, board_size: usize) -> Result<usize, String> {
+    match value.parse() {
+        Ok(length) if (BOARD_SIZE..=board_size).contains(&length) => Ok(length),
+        _ => Err(format!(
+            "expected a number from {} to the board size of {}",
+            BOARD_SIZE, board_size
+        )),
+    }
+}
+
+// Same as `parse`, but for on/off switches.
+fn parse_flag(name: &'static str) -> Result<Option<bool>, SettingsError> {
+    env::var(name)
+        .ok()
+        .map(|value| match value.as_str() {
+            "1" | "true" => Ok(true),
+            "0" | "false" => Ok(false),
+            _ => Err(SettingsError {
+                name,
+                value,
+                reason: "expected one of `1`, `0`, `true` or `false`".to_string(),
+            }),
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::game::WinMode};
+
+    // Tests run in parallel but share the environment, so this is the only one reading all
+    // variables at once.
+    #[test]
+    fn reads_representative_environment() {
+        let vars = [
+            ("TICTACGPU_SEED", "42"),
+            ("TICTACGPU_FACTION", "alternate"),
+            ("TICTACGPU_STRATEGY", "minimax"),
+            ("TICTACGPU_BOARD_SIZE", "4"),
+            ("TICTACGPU_WIN_LENGTH", "3"),
+            ("TICTACGPU_WIN_MODE", "misere"),
+            ("TICTACGPU_HANDICAP", "x5"),
+            ("TICTACGPU_DEBOUNCE", "50"),
+            ("TICTACGPU_CHOOSER", "once"),
+            ("TICTACGPU_LIMITS", "strict"),
+            ("TICTACGPU_CURSOR", "false"),
+            ("TICTACGPU_SCORE", "1"),
+            ("TICTACGPU_UNDO_LEVELS", "2"),
+        ];
+        for (name, value) in vars {
+            env::set_var(name, value);
+        }
+        let settings = Settings::from_env();
+        for (name, _) in vars {
+            env::remove_var(name);
+        }
+
+        assert_eq!(
+            settings.unwrap(),
+            Settings {
+                seed: Some(42),
+                faction: FactionChoice::Alternate,
+                strategy: AiStrategy::Minimax,
+                debounce: Duration::from_millis(50),
+                chooser: ChooserMode::Once,
+                lines: Lines::in_a_row(4, 3).with_win_mode(WinMode::Misere),
+                handicap: "x5".parse().unwrap(),
+                limits: LimitsMode::Strict,
+                cursor: false,
+                score: true,
+                undo_levels: Some(2),
+                ..Settings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn board_size_has_to_be_supported() {
+        assert_eq!(parse_board_size("3"), Ok(3));
+        assert_eq!(parse_board_size("5"), Ok(MAX_BOARD_SIZE));
+        assert!(parse_board_size("2").is_err());
+        assert!(parse_board_size("6").is_err());
+        assert!(parse_board_size("three").is_err());
+    }
+
+    #[test]
+    fn win_length_has_to_fit_onto_the_board() {
+        assert_eq!(parse_win_length("3", 5), Ok(3));
+        assert_eq!(parse_win_length("4", 5), Ok(4));
+        assert_eq!(parse_win_length("5", 5), Ok(5));
+        assert!(parse_win_length("4", 3).is_err());
+        assert!(parse_win_length("2", 5).is_err());
+        assert!(parse_win_length("four", 5).is_err());
+    }
+
+    #[test]
+    fn flags_are_parsed_strictly() {
+        // names no other test uses, since the environment is shared
+        assert_eq!(parse_flag("TICTACGPU_TEST_UNSET").unwrap(), None);
+        env::set_var("TICTACGPU_TEST_ON", "true");
+        assert_eq!(parse_flag("TICTACGPU_TEST_ON").unwrap(), Some(true));
+        env::set_var("TICTACGPU_TEST_OFF", "0");
+        assert_eq!(parse_flag("TICTACGPU_TEST_OFF").unwrap(), Some(false));
+        env::set_var("TICTACGPU_TEST_YES", "yes");
+        assert!(parse_flag("TICTACGPU_TEST_YES").is_err());
+    }
+}
@@ -5,8 +5,26 @@ struct Vertex {
 
 struct Instance {
 	@location(2) offset: vec2<f32>,
+	@location(3) scale: f32,
+	@location(4) color: vec4<f32>,
+	// counterclockwise, in radians
+	@location(5) rotation: f32,
 };
 
+// tint() is defined in one of the tint_*.wgsl files, depending on whether push constants are
+// supported.
+
+// Has to match Uniforms in render.rs.
+struct Uniforms {
+	view_offset: vec2<f32>,
+	view_scale: f32,
+	// cosine and sine of the angle the board is turned by
+	view_rotation: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
 struct ModifiedVertex {
 	@builtin(position) position: vec4<f32>,
 	@location(0) color: vec4<f32>,
@@ -18,8 +36,19 @@ fn vertex_main(
 	instance: Instance,
 ) -> ModifiedVertex {
 	var out: ModifiedVertex;
-	out.position = vec4<f32>(source.position + instance.offset, 0.0, 1.0);
-	out.color = source.color;
+	let scaled = source.position * instance.scale;
+	let spin = vec2<f32>(cos(instance.rotation), sin(instance.rotation));
+	let board_position = vec2<f32>(
+		spin.x * scaled.x - spin.y * scaled.y,
+		spin.y * scaled.x + spin.x * scaled.y,
+	) + instance.offset;
+	let rotation = uniforms.view_rotation;
+	let turned = vec2<f32>(
+		rotation.x * board_position.x - rotation.y * board_position.y,
+		rotation.y * board_position.x + rotation.x * board_position.y,
+	);
+	out.position = vec4<f32>(turned * uniforms.view_scale + uniforms.view_offset, 0.0, 1.0);
+	out.color = source.color * instance.color * tint();
 	return out;
 }
 
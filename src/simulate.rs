@@ -0,0 +1,202 @@
+use {
+    super::{
+        ai::{AiStrategy, UnknownStrategyError},
+        game::{Faction, FactionChoice, Game, Handicap, Lines, Outcome},
+    },
+    rand::{rngs::StdRng, SeedableRng},
+    std::num::ParseIntError,
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    #[error("Missing value for {0}")]
+    MissingValue(&'static str),
+    #[error("Invalid number for {0}: {1}")]
+    InvalidNumber(&'static str, ParseIntError),
+    #[error(transparent)]
+    UnknownStrategy(#[from] UnknownStrategyError),
+    #[error("Unknown argument {0:?}")]
+    UnknownArgument(String),
+}
+
+/// Configuration for a headless self-play run, where both factions are played by an AI.
+#[derive(Debug)]
+pub struct Simulation {
+    seed: u64,
+    games: u32,
+    cross: AiStrategy,
+    ring: AiStrategy,
+}
+
+impl Simulation {
+    /// Parses the arguments following `--ai-both`, which are the seed and then optionally
+    /// `--games <n>`, `--cross <strategy>` and `--ring <strategy>` in any order.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, SimulationError> {
+        let seed = args
+            .next()
+            .ok_or(SimulationError::MissingValue("--ai-both"))?;
+        let mut simulation = Self {
+            seed: seed
+                .parse()
+                .map_err(|e| SimulationError::InvalidNumber("--ai-both", e))?,
+            games: 1000,
+            cross: AiStrategy::Minimax,
+            ring: AiStrategy::Minimax,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--games" => {
+                    let value = args
+                        .next()
+                        .ok_or(SimulationError::MissingValue("--games"))?;
+                    simulation.games = value
+                        .parse()
+                        .map_err(|e| SimulationError::InvalidNumber("--games", e))?;
+                }
+                "--cross" => {
+                    let value = args
+                        .next()
+                        .ok_or(SimulationError::MissingValue("--cross"))?;
+                    simulation.cross = value.parse()?;
+                }
+                "--ring" => {
+                    let value = args.next().ok_or(SimulationError::MissingValue("--ring"))?;
+                    simulation.ring = value.parse()?;
+                }
+                _ => return Err(SimulationError::UnknownArgument(arg)),
+            }
+        }
+
+        Ok(simulation)
+    }
+
+    /// Plays all games and tallies up how they ended.
+    pub fn run(&self) -> Tally {
+        // the user's faction doesn't matter at all here, both sides are played by the AI
+        let mut game = Game::new(
+            StdRng::seed_from_u64(self.seed),
+            FactionChoice::Random,
+            Lines::default(),
+            Handicap::default(),
+        );
+        let mut tally = Tally::default();
+
+        for _ in 0..self.games {
+            while !game.is_over() {
+                let strategy = match game.turn() {
+                    Faction::Cross => self.cross,
+                    Faction::Ring => self.ring,
+                };
+                game.ai_turn(strategy);
+            }
+
+            match game.outcome() {
+                Some(Outcome::Win(Faction::Cross)) => tally.cross_wins += 1,
+                Some(Outcome::Win(Faction::Ring)) => tally.ring_wins += 1,
+                Some(Outcome::Draw) | None => tally.draws += 1,
+            }
+            game.reset();
+        }
+
+        tally
+    }
+
+    /// Runs the simulation and prints a short summary to stdout.
+    pub fn run_and_print(&self) {
+        let tally = self.run();
+        let percentage = |count: u32| f64::from(count) * 100.0 / f64::from(self.games.max(1));
+
+        println!("{} games with seed {}", self.games, self.seed);
+        println!(
+            "cross ({}) wins: {} ({:.1}%)",
+            self.cross,
+            tally.cross_wins,
+            percentage(tally.cross_wins),
+        );
+        println!(
+            "ring ({}) wins: {} ({:.1}%)",
+            self.ring,
+            tally.ring_wins,
+            percentage(tally.ring_wins),
+        );
+        println!("draws: {} ({:.1}%)", tally.draws, percentage(tally.draws));
+    }
+}
+
+/// How many games ended in which way.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub cross_wins: u32,
+    pub ring_wins: u32,
+    pub draws: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(source: &str) -> impl Iterator<Item = String> + '_ {
+        source.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn minimax_against_itself_always_draws() {
+        let simulation = Simulation::from_args(args("7 --games 20")).unwrap();
+        assert_eq!(
+            simulation.run(),
+            Tally {
+                cross_wins: 0,
+                ring_wins: 0,
+                draws: 20,
+            }
+        );
+    }
+
+    #[test]
+    fn minimax_never_loses_against_random() {
+        let simulation = Simulation::from_args(args("7 --games 10 --cross random")).unwrap();
+        let tally = simulation.run();
+        assert_eq!(tally.cross_wins, 0);
+        assert_eq!(tally.ring_wins + tally.draws, 10);
+    }
+
+    #[test]
+    fn parses_all_options_in_any_order() {
+        let simulation =
+            Simulation::from_args(args("42 --ring random --games 5 --cross mixed")).unwrap();
+        assert_eq!(simulation.seed, 42);
+        assert_eq!(simulation.games, 5);
+        assert_eq!(simulation.cross, AiStrategy::Mixed);
+        assert_eq!(simulation.ring, AiStrategy::Random);
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        assert!(matches!(
+            Simulation::from_args(args("")),
+            Err(SimulationError::MissingValue("--ai-both"))
+        ));
+        assert!(matches!(
+            Simulation::from_args(args("seed")),
+            Err(SimulationError::InvalidNumber("--ai-both", _))
+        ));
+        assert!(matches!(
+            Simulation::from_args(args("1 --games")),
+            Err(SimulationError::MissingValue("--games"))
+        ));
+        assert!(matches!(
+            Simulation::from_args(args("1 --games -3")),
+            Err(SimulationError::InvalidNumber("--games", _))
+        ));
+        assert!(matches!(
+            Simulation::from_args(args("1 --cross perfect")),
+            Err(SimulationError::UnknownStrategy(_))
+        ));
+        assert!(matches!(
+            Simulation::from_args(args("1 --fast")),
+            Err(SimulationError::UnknownArgument(arg)) if arg == "--fast"
+        ));
+    }
+}
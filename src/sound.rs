@@ -0,0 +1,190 @@
+use {
+    super::Sound,
+    std::{
+        f32::consts::TAU,
+        io::{self, Write},
+        process::{Child, Command, Stdio},
+        thread,
+    },
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+pub enum SoundError {
+    #[error("Neither of {0:?} could play the sound, is one of them installed?")]
+    NoPlayer(Vec<String>),
+    #[error("Could not pass the sound to {0}: {1}")]
+    Io(String, io::Error),
+}
+
+/// A program which plays the WAV file it's given on stdin, along with its arguments.
+#[derive(Debug, Clone, Copy)]
+pub struct Player<'a> {
+    pub name: &'a str,
+    pub args: &'a [&'a str],
+}
+
+/// What's tried in order to play sounds, first for PulseAudio or PipeWire, then for bare ALSA.
+pub const PLAYERS: [Player; 2] = [
+    Player {
+        name: "paplay",
+        args: &[],
+    },
+    Player {
+        name: "aplay",
+        args: &["--quiet", "-"],
+    },
+];
+
+/// How many samples make up one second of sound.
+const SAMPLE_RATE: u32 = 44100;
+
+/// Plays sounds through the first of [`PLAYERS`] which is installed.
+pub struct Speaker {
+    tick: Vec<u8>,
+}
+
+impl Default for Speaker {
+    fn default() -> Self {
+        Self { tick: tick() }
+    }
+}
+
+impl Sound for Speaker {
+    fn tick(&self) {
+        match play_with(&PLAYERS, &self.tick) {
+            // waited for elsewhere, the sound is short but still shouldn't hold up the game
+            Ok(mut child) => {
+                thread::spawn(move || child.wait());
+            }
+            Err(e) => log::debug!("Could not play tick: {}", e),
+        }
+    }
+}
+
+/// Starts the first of the given players which can be started on the given data. Returns the
+/// still running player, which is done once the sound is over.
+pub fn play_with(players: &[Player], data: &[u8]) -> Result<Child, SoundError> {
+    for player in players {
+        let mut child = match Command::new(player.name)
+            .args(player.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(SoundError::Io(player.name.to_string(), e)),
+        };
+
+        // dropping the stdin handle right away tells the player there's nothing more to come
+        child
+            .stdin
+            .take()
+            .expect("stdin to be piped")
+            .write_all(data)
+            .map_err(|e| SoundError::Io(player.name.to_string(), e))?;
+        return Ok(child);
+    }
+
+    Err(SoundError::NoPlayer(
+        players
+            .iter()
+            .map(|player| player.name.to_string())
+            .collect(),
+    ))
+}
+
+/// A soft tick as a WAV file: A short, quiet sine wave fading out quickly, so it doesn't click.
+pub fn tick() -> Vec<u8> {
+    const FREQUENCY: f32 = 1200.0;
+    const DURATION: f32 = 0.03;
+    const VOLUME: f32 = 0.15;
+
+    let count = (SAMPLE_RATE as f32 * DURATION) as u32;
+    let samples = (0..count).map(|i| {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        let fade = 1.0 - i as f32 / count as f32;
+        let sample = (TAU * FREQUENCY * t).sin() * fade * fade * VOLUME;
+        (sample * f32::from(i16::MAX)) as i16
+    });
+    wav(samples, count)
+}
+
+// Wraps the given number of 16 bit mono samples into a WAV file.
+fn wav(samples: impl Iterator<Item = i16>, count: u32) -> Vec<u8> {
+    let data_size = count * 2;
+    let mut wav = Vec::with_capacity(44 + data_size as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    // size of the format chunk, PCM, mono
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    // bytes per second, bytes per sample, bits per sample
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::fs};
+
+    #[test]
+    fn tick_is_a_short_quiet_wav() {
+        let tick = tick();
+        assert_eq!(&tick[..4], b"RIFF");
+        assert_eq!(&tick[8..16], b"WAVEfmt ");
+        let data_size = u32::from_le_bytes(tick[40..44].try_into().unwrap());
+        assert_eq!(tick.len(), 44 + data_size as usize);
+
+        let samples: Vec<_> = tick[44..]
+            .chunks(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        assert!(samples.len() < SAMPLE_RATE as usize / 10);
+        assert!(samples.iter().all(|sample| sample.abs() < i16::MAX / 4));
+        assert!(samples.iter().any(|&sample| sample != 0));
+    }
+
+    #[test]
+    fn sound_is_passed_to_the_first_installed_player() {
+        let path = std::env::temp_dir().join(format!("tic-tac-gpu-sound-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let players = [
+            Player {
+                name: "tic-tac-gpu-not-installed",
+                args: &[],
+            },
+            // stands in for the speakers, writing to a file instead
+            Player {
+                name: "sh",
+                args: &["-c", "cat > \"$0\"", path],
+            },
+        ];
+
+        let tick = tick();
+        assert!(play_with(&players, &tick)
+            .unwrap()
+            .wait()
+            .unwrap()
+            .success());
+        let played = fs::read(path).unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(played, tick);
+
+        assert!(matches!(
+            play_with(&players[..1], &tick),
+            Err(SoundError::NoPlayer(names)) if names == ["tic-tac-gpu-not-installed"]
+        ));
+    }
+}
@@ -0,0 +1,138 @@
+use {
+    super::{
+        game::{Cell, BOARD_SIZE},
+        render::grid_lines,
+    },
+    std::fmt::Write,
+};
+
+/// Both bars of a cross around the origin, each with pointy ends like on the screen.
+const CROSS_BARS: [[[f32; 2]; 6]; 2] = [
+    [
+        [-0.25, 0.25],
+        [-0.2, 0.15],
+        [0.15, -0.2],
+        [0.25, -0.25],
+        [0.2, -0.15],
+        [-0.15, 0.2],
+    ],
+    [
+        [0.25, 0.25],
+        [0.15, 0.2],
+        [-0.2, -0.15],
+        [-0.25, -0.25],
+        [-0.15, -0.2],
+        [0.2, 0.15],
+    ],
+];
+
+/// Draws the board with the given number of cells per side like the window does, without needing
+/// a GPU. Coordinates are the same as in clip space, just with y flipped as SVG expects it.
+pub fn board_to_svg(board: &[Cell], board_size: usize) -> String {
+    let mut svg = String::from(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-1 -1 2 2\" width=\"400\" \
+         height=\"400\">\n\
+         <rect x=\"-1\" y=\"-1\" width=\"2\" height=\"2\" fill=\"#0a1717\"/>\n\
+         <g transform=\"scale(1 -1)\">\n",
+    );
+
+    for line in grid_lines(board_size) {
+        let _ = writeln!(
+            svg,
+            "<path d=\"{}\" fill=\"#e6e6e6\"/>",
+            path(&line, [0.0; 2], 1.0)
+        );
+    }
+
+    // marks are designed for the cells of the classic board
+    let scale = BOARD_SIZE as f32 / board_size as f32;
+    for (index, cell) in board.iter().enumerate() {
+        let center = cell_center(index, board_size);
+        match cell {
+            Cell::Cross => {
+                let bars: Vec<_> = CROSS_BARS
+                    .iter()
+                    .map(|bar| path(bar, center, scale))
+                    .collect();
+                let _ = writeln!(svg, "<path d=\"{}\" fill=\"#45deb3\"/>", bars.join(" "));
+            }
+            // the ring on the screen spans from 0.15 to 0.25 around its center
+            Cell::Ring => {
+                let _ = writeln!(
+                    svg,
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"#c24dff\" \
+                     stroke-width=\"{}\"/>",
+                    center[0],
+                    center[1],
+                    0.2 * scale,
+                    0.1 * scale
+                );
+            }
+            Cell::Empty => (),
+        }
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+// Where the given cell is centered in clip space, indexed by x * board_size + y with y+ up.
+fn cell_center(index: usize, board_size: usize) -> [f32; 2] {
+    let cell_size = 2.0 / board_size as f32;
+    let center = |i: usize| -1.0 + cell_size * (i as f32 + 0.5);
+    [center(index / board_size), center(index % board_size)]
+}
+
+// A closed SVG path through the given points, scaled around the origin and then moved by the
+// given offset.
+fn path(points: &[[f32; 2]], offset: [f32; 2], scale: f32) -> String {
+    let points: Vec<_> = points
+        .iter()
+        .map(|point| {
+            format!(
+                "{} {}",
+                point[0] * scale + offset[0],
+                point[1] * scale + offset[1]
+            )
+        })
+        .collect();
+    format!("M {} Z", points.join(" L "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(svg: &str, element: &str) -> usize {
+        svg.matches(&format!("<{} ", element)).count()
+    }
+
+    #[test]
+    fn each_line_and_mark_is_one_element() {
+        let empty = board_to_svg(&[Cell::Empty; 9], 3);
+        assert_eq!(count(&empty, "path"), 4);
+        assert_eq!(count(&empty, "circle"), 0);
+
+        let mut board = [Cell::Cross; 9];
+        for index in [1, 3, 5, 7] {
+            board[index] = Cell::Ring;
+        }
+        let full = board_to_svg(&board, 3);
+        assert_eq!(count(&full, "path"), 4 + 5);
+        assert_eq!(count(&full, "circle"), 4);
+
+        let larger = board_to_svg(&[Cell::Empty; 16], 4);
+        assert_eq!(count(&larger, "path"), 6);
+    }
+
+    #[test]
+    fn rings_are_centered_in_their_cells() {
+        let mut board = [Cell::Empty; 9];
+        board[0] = Cell::Ring;
+        let svg = board_to_svg(&board, 3);
+        let [x, y] = cell_center(0, 3);
+        assert_eq!(x, y);
+        assert!((x - (-2.0 / 3.0)).abs() < 1e-6);
+        assert!(svg.contains(&format!("<circle cx=\"{}\" cy=\"{}\" r=\"0.2\"", x, y)));
+    }
+}
@@ -0,0 +1,72 @@
+use winit::window;
+
+/// All colors which depend on whether the system prefers light or dark windows.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Theme {
+    /// Background while a game is going on.
+    pub background: wgpu::Color,
+    /// Background once a game has been won by either side.
+    pub background_won: wgpu::Color,
+    /// Background once a game has ended in a draw.
+    pub background_drawn: wgpu::Color,
+    /// Multiplied with the grid and the thinking dots, which are nearly white on their own.
+    pub lines_tint: [f32; 4],
+    /// Multiplied with all marks, including the ghosts previewing them.
+    pub marks_tint: [f32; 4],
+}
+
+impl Theme {
+    pub const DARK: Self = Self {
+        background: wgpu::Color {
+            r: 0.04,
+            g: 0.09,
+            b: 0.09,
+            a: 1.0,
+        },
+        background_won: wgpu::Color {
+            r: 0.3,
+            g: 0.35,
+            b: 0.35,
+            a: 1.0,
+        },
+        background_drawn: wgpu::Color {
+            r: 0.33,
+            g: 0.31,
+            b: 0.24,
+            a: 1.0,
+        },
+        lines_tint: [1.0; 4],
+        marks_tint: [1.0; 4],
+    };
+
+    pub const LIGHT: Self = Self {
+        background: wgpu::Color {
+            r: 0.93,
+            g: 0.93,
+            b: 0.9,
+            a: 1.0,
+        },
+        background_won: wgpu::Color {
+            r: 0.72,
+            g: 0.8,
+            b: 0.8,
+            a: 1.0,
+        },
+        background_drawn: wgpu::Color {
+            r: 0.85,
+            g: 0.8,
+            b: 0.66,
+            a: 1.0,
+        },
+        lines_tint: [0.2, 0.2, 0.2, 1.0],
+        marks_tint: [0.65, 0.65, 0.65, 1.0],
+    };
+
+    /// The preset fitting what the system prefers. Dark if it doesn't tell.
+    pub fn for_system(theme: Option<window::Theme>) -> Self {
+        match theme {
+            Some(window::Theme::Light) => Self::LIGHT,
+            Some(window::Theme::Dark) | None => Self::DARK,
+        }
+    }
+}
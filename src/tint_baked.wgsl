@@ -0,0 +1,7 @@
+// Prepended to shader.wgsl if push constants aren't supported. The tint is then already baked into
+// the instance colors, so there's nothing left to do here.
+
+fn tint() -> vec4<f32> {
+	return vec4<f32>(1.0);
+}
+
@@ -0,0 +1,13 @@
+// Prepended to shader.wgsl if push constants are supported. The tint is then pushed right before
+// each shape is drawn.
+
+struct Draw {
+	tint: vec4<f32>,
+};
+
+var<push_constant> draw: Draw;
+
+fn tint() -> vec4<f32> {
+	return draw.tint;
+}
+